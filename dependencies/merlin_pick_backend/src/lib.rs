@@ -162,12 +162,24 @@ pub fn texture_atlas_sprite_picking(
                     let position = global_transform.translation();
                     let sprite_position = position.truncate();
 
-                    let extents = sprite
+                    let size = sprite
                         .custom_size
-                        .or_else(|| texture_atlases.get(atlas_handle).map(|a| a.textures[sprite.index].size()))
-                        .map(|size| size / 2.0 * global_transform.to_scale_rotation_translation().0.truncate())?;
-
-                    let anchor_offset = sprite.anchor.as_vec() * extents * -2.0;
+                        .or_else(|| texture_atlases.get(atlas_handle).map(|a| a.textures[sprite.index].size()))?;
+
+                    // Bounding box of the rotated and scaled sprite, relative to its position
+                    let (scale, rotation, _) = global_transform.to_scale_rotation_translation();
+                    let anchor = sprite.anchor.as_vec();
+                    let (local_min, local_max) = [
+                        Vec2::new(-0.5, -0.5),
+                        Vec2::new(0.5, -0.5),
+                        Vec2::new(-0.5, 0.5),
+                        Vec2::new(0.5, 0.5),
+                    ]
+                    .iter()
+                    .map(|corner| (rotation * ((*corner - anchor) * size * scale.truncate()).extend(0.0)).truncate())
+                    .fold((Vec2::splat(f32::MAX), Vec2::splat(f32::MIN)), |(min, max), corner| {
+                        (min.min(corner), max.max(corner))
+                    });
 
                     let target = if let Some(t) =
                         location.target.get_render_target_info(&windows, &images)
@@ -178,14 +190,14 @@ pub fn texture_atlas_sprite_picking(
                     };
 
                     #[cfg(not(feature = "pixel_camera"))]
-                    let min = sprite_position - extents + anchor_offset + target / 2.0;
+                    let min = sprite_position + local_min + target / 2.0;
                     #[cfg(not(feature = "pixel_camera"))]
-                    let max = sprite_position + extents + anchor_offset + target / 2.0;
+                    let max = sprite_position + local_max + target / 2.0;
 
                     #[cfg(feature = "pixel_camera")]
-                    let min = pixel_zoom as f32 * (sprite_position - extents + anchor_offset) + target / 2.0;
+                    let min = pixel_zoom as f32 * (sprite_position + local_min) + target / 2.0;
                     #[cfg(feature = "pixel_camera")]
-                    let max = pixel_zoom as f32 * (sprite_position + extents + anchor_offset) + target / 2.0;
+                    let max = pixel_zoom as f32 * (sprite_position + local_max) + target / 2.0;
 
                     let contains_cursor = (min.x..max.x).contains(&cursor_position.x)
                         && (min.y..max.y).contains(&cursor_position.y);
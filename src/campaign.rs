@@ -0,0 +1,380 @@
+//! Campaigns put levels into chapters and say which have to be completed before others open.\
+//! Each campaign is a directory of level files with a [MANIFEST_NAME] listing them, e.g.
+//! ```ron
+//! (
+//!     name: "Main",
+//!     chapters: [
+//!         (
+//!             name: "Wiring",
+//!             introduces: ["WirePiece", "GateNot"],
+//!             levels: [(id: "1"), (id: "2")],
+//!         ),
+//!         (
+//!             name: "Analog",
+//!             requires: ["Wiring"],
+//!             levels: [(id: "3"), (id: "4", requires: ["2"])],
+//!         ),
+//!     ],
+//! )
+//! ```
+//! The levels are shown in the order they are listed. A level is in `{id}.json` next to the manifest unless it gives a `file`.\
+//! The main campaign is in [LEVEL_DIR], level packs each get their own directory in [PACKS_DIR].
+use std::{collections::HashSet, fmt, fs::File, path::{Path, PathBuf}};
+
+use bevy::prelude::*;
+use glob::glob;
+use serde::{Deserialize, Serialize};
+
+use crate::sim::{levels::LEVEL_DIR, model::DummyComponent, palette::component_key};
+use strum::IntoEnumIterator;
+
+/// Each directory in here holding a [MANIFEST_NAME] is a level pack
+pub const PACKS_DIR: &str = "data/packs";
+pub const MANIFEST_NAME: &str = "campaign.ron";
+/// Id of the campaign in [LEVEL_DIR]
+pub const MAIN_CAMPAIGN: &str = "main";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CampaignManifest {
+    pub name: String,
+    pub chapters: Vec<Chapter>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Chapter {
+    pub name: String,
+    /// Components first needed in this chapter, by the names levels use for them
+    #[serde(default)]
+    pub introduces: Vec<String>,
+    /// Chapters which have to be completed before any level in this one opens
+    #[serde(default)]
+    pub requires: Vec<String>,
+    pub levels: Vec<LevelEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LevelEntry {
+    pub id: String,
+    /// File name next to the manifest, `{id}.json` when left out
+    #[serde(default)]
+    pub file: Option<String>,
+    /// Levels which have to be completed before this one opens, the level listed before it when left out
+    #[serde(default)]
+    pub requires: Option<Vec<String>>,
+}
+
+/// A level in a campaign, shown as `{campaign}/{level}` or just `{level}` in the main campaign.\
+/// That is also how saves and scores are keyed, so ones from before campaigns still belong to their level.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct LevelId {
+    pub campaign: String,
+    pub level: String,
+}
+
+impl fmt::Display for LevelId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.campaign == MAIN_CAMPAIGN {
+            true => write!(f, "{}", self.level),
+            false => write!(f, "{}/{}", self.campaign, self.level),
+        }
+    }
+}
+
+/// A problem with a campaign manifest
+#[derive(Debug, Clone)]
+pub enum CampaignError {
+    Open(String),
+    Parse(String),
+    DuplicateLevel(String),
+    MissingFile { level: String, path: PathBuf },
+    /// A level requires a level which isn't in the campaign
+    UnknownLevel { level: String, requires: String },
+    /// A chapter requires a chapter which isn't in the campaign
+    UnknownChapter { chapter: String, requires: String },
+    UnknownComponent { chapter: String, name: String },
+    /// Chapters which end up requiring themselves, the first one repeated at the end
+    ChapterCycle(Vec<String>),
+    /// Levels which end up requiring themselves, the first one repeated at the end
+    LevelCycle(Vec<String>),
+}
+
+impl fmt::Display for CampaignError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CampaignError::Open(e) => write!(f, "Could not open the manifest: {}", e),
+            CampaignError::Parse(e) => write!(f, "Could not parse the manifest: {}", e),
+            CampaignError::DuplicateLevel(id) => write!(f, "There is more than one level with id {}", id),
+            CampaignError::MissingFile { level, path } => write!(f, "The file {} for level {} does not exist", path.display(), level),
+            CampaignError::UnknownLevel { level, requires } => write!(f, "Level {} requires {} which is not in the campaign", level, requires),
+            CampaignError::UnknownChapter { chapter, requires } => write!(f, "Chapter {} requires {} which is not in the campaign", chapter, requires),
+            CampaignError::UnknownComponent { chapter, name } => write!(f, "Chapter {} introduces {} which is not a component", chapter, name),
+            CampaignError::ChapterCycle(chapters) => write!(f, "Chapters require each other so never open: {}", chapters.join(" -> ")),
+            CampaignError::LevelCycle(levels) => write!(f, "Levels require each other so never open: {}", levels.join(" -> ")),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Campaign {
+    /// [MAIN_CAMPAIGN] or the name of the pack's directory
+    pub id: String,
+    pub dir: PathBuf,
+    pub manifest: CampaignManifest,
+}
+
+impl Campaign {
+    /// Read the manifest in `dir`, checking it against the level files
+    pub fn load(id: &str, dir: &Path) -> Result<Self, Vec<CampaignError>> {
+        let path = dir.join(MANIFEST_NAME);
+        let file = File::open(&path).map_err(|e| vec![CampaignError::Open(e.to_string())])?;
+        let manifest: CampaignManifest = ron::de::from_reader(file).map_err(|e| vec![CampaignError::Parse(e.to_string())])?;
+        let campaign = Campaign { id: id.to_string(), dir: dir.to_path_buf(), manifest };
+        let errors = campaign.validate();
+        match errors.is_empty() {
+            true => Ok(campaign),
+            false => Err(errors),
+        }
+    }
+
+    /// For a directory of `{number}.json` levels without a manifest, one chapter in number order
+    pub fn numbered(id: &str, dir: &Path) -> Self {
+        let levels = numbered_levels(dir).into_iter()
+            .map(|n| LevelEntry { id: n.to_string(), file: None, requires: None })
+            .collect();
+        Campaign {
+            id: id.to_string(),
+            dir: dir.to_path_buf(),
+            manifest: CampaignManifest {
+                name: id.to_string(),
+                chapters: vec![Chapter { name: "Levels".to_string(), introduces: Vec::new(), requires: Vec::new(), levels }],
+            },
+        }
+    }
+
+    /// Every problem with the manifest, empty if it is fine to play
+    pub fn validate(&self) -> Vec<CampaignError> {
+        let mut errors = Vec::new();
+        let ids: Vec<&str> = self.levels().map(|(_, l)| l.id.as_str()).collect();
+        let known_components: Vec<String> = DummyComponent::iter().map(component_key).collect();
+
+        for (i, (_, level)) in self.levels().enumerate() {
+            if ids[..i].contains(&level.id.as_str()) {
+                errors.push(CampaignError::DuplicateLevel(level.id.clone()));
+            }
+            let path = self.path(level);
+            if !path.exists() {
+                errors.push(CampaignError::MissingFile { level: level.id.clone(), path });
+            }
+            for requires in level.requires.iter().flatten().filter(|r| !ids.contains(&r.as_str())) {
+                errors.push(CampaignError::UnknownLevel { level: level.id.clone(), requires: requires.clone() });
+            }
+        }
+        for chapter in &self.manifest.chapters {
+            for requires in chapter.requires.iter().filter(|r| !self.manifest.chapters.iter().any(|c| c.name == **r)) {
+                errors.push(CampaignError::UnknownChapter { chapter: chapter.name.clone(), requires: requires.clone() });
+            }
+            for name in chapter.introduces.iter().filter(|n| !known_components.contains(n)) {
+                errors.push(CampaignError::UnknownComponent { chapter: chapter.name.clone(), name: name.clone() });
+            }
+        }
+
+        // A chapter cycle locks every level in it too, so that is all which gets reported
+        let chapter_names: Vec<String> = self.manifest.chapters.iter().map(|c| c.name.clone()).collect();
+        let chapter_requires = |name: &str| {
+            self.manifest.chapters.iter().find(|c| c.name == name).map(|c| c.requires.clone()).unwrap_or_default()
+        };
+        let level_ids: Vec<String> = ids.iter().map(|id| id.to_string()).collect();
+        if let Some(cycle) = find_cycle(&chapter_names, chapter_requires) {
+            errors.push(CampaignError::ChapterCycle(cycle));
+        } else if let Some(cycle) = find_cycle(&level_ids, |id| self.requirements(id)) {
+            errors.push(CampaignError::LevelCycle(cycle));
+        }
+        errors
+    }
+
+    /// Every level in the order they are shown, with the chapter it is in
+    pub fn levels(&self) -> impl Iterator<Item = (&Chapter, &LevelEntry)> {
+        self.manifest.chapters.iter().flat_map(|c| c.levels.iter().map(move |l| (c, l)))
+    }
+
+    pub fn level_id(&self, level: &LevelEntry) -> LevelId {
+        LevelId { campaign: self.id.clone(), level: level.id.clone() }
+    }
+
+    pub fn path(&self, level: &LevelEntry) -> PathBuf {
+        match &level.file {
+            Some(file) => self.dir.join(file),
+            None => self.dir.join(format!("{}.json", level.id)),
+        }
+    }
+
+    /// Ids of every level which has to be completed before `level` opens
+    pub fn requirements(&self, level: &str) -> Vec<String> {
+        let mut requirements = Vec::new();
+        let mut previous = None;
+        for (chapter, entry) in self.levels() {
+            if entry.id == level {
+                match &entry.requires {
+                    Some(requires) => requirements.extend(requires.iter().cloned()),
+                    None => requirements.extend(previous),
+                }
+                let required_chapters = self.manifest.chapters.iter().filter(|c| chapter.requires.contains(&c.name));
+                requirements.extend(required_chapters.flat_map(|c| c.levels.iter().map(|l| l.id.clone())));
+                break;
+            }
+            previous = Some(entry.id.clone());
+        }
+        requirements
+    }
+}
+
+/// A loop of names which each require the next, starting from the first of `names` found in one
+fn find_cycle(names: &[String], requires: impl Fn(&str) -> Vec<String>) -> Option<Vec<String>> {
+    fn visit(name: &str, requires: &dyn Fn(&str) -> Vec<String>, path: &mut Vec<String>, done: &mut HashSet<String>) -> Option<Vec<String>> {
+        if let Some(start) = path.iter().position(|p| p == name) {
+            let mut cycle = path[start..].to_vec();
+            cycle.push(name.to_string());
+            return Some(cycle);
+        }
+        if done.contains(name) {return None}
+        path.push(name.to_string());
+        for next in requires(name) {
+            if let Some(cycle) = visit(&next, requires, path, done) {
+                return Some(cycle);
+            }
+        }
+        path.pop();
+        done.insert(name.to_string());
+        None
+    }
+
+    let mut done = HashSet::new();
+    names.iter().find_map(|name| visit(name, &requires, &mut Vec::new(), &mut done))
+}
+
+/// Every level file in `dir` named by number, in order
+pub fn numbered_levels(dir: &Path) -> Vec<u16> {
+    let mut numbers: Vec<u16> = glob(&format!("{}/*.json", dir.display())).expect("Failed to read glob pattern")
+        .flatten()
+        .filter_map(|path| path.file_stem()?.to_str()?.parse().ok())
+        .collect();
+    numbers.sort();
+    numbers
+}
+
+/// Id and directory of the main campaign and every level pack
+pub fn campaign_dirs() -> Vec<(String, PathBuf)> {
+    let packs = glob(&format!("{}/*/{}", PACKS_DIR, MANIFEST_NAME)).expect("Failed to read glob pattern")
+        .flatten()
+        .filter_map(|manifest| {
+            let dir = manifest.parent()?;
+            Some((dir.file_name()?.to_str()?.to_string(), dir.to_path_buf()))
+        })
+        .filter(|(id, _)| id != MAIN_CAMPAIGN);
+    std::iter::once((MAIN_CAMPAIGN.to_string(), PathBuf::from(LEVEL_DIR))).chain(packs).collect()
+}
+
+/// Every campaign found at startup, the main one first
+#[derive(Resource, Debug, Default)]
+pub struct Campaigns(pub Vec<Campaign>);
+
+impl FromWorld for Campaigns {
+    fn from_world(_: &mut World) -> Self {
+        Campaigns::load()
+    }
+}
+
+impl Campaigns {
+    /// Campaigns with problems are left out and logged, the main one falls back to its numbered levels
+    pub fn load() -> Self {
+        let mut campaigns = Vec::new();
+        for (id, dir) in campaign_dirs() {
+            match Campaign::load(&id, &dir) {
+                Ok(campaign) => campaigns.push(campaign),
+                Err(errors) => {
+                    for e in errors {
+                        error!("Campaign {}: {}", id, e);
+                    }
+                    if id == MAIN_CAMPAIGN {
+                        campaigns.push(Campaign::numbered(MAIN_CAMPAIGN, &dir));
+                    }
+                },
+            }
+        }
+        Campaigns(campaigns)
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Campaign> {
+        self.0.iter().find(|c| c.id == id)
+    }
+
+    /// Where the level's file is, if it is in a campaign
+    pub fn path(&self, level: &LevelId) -> Option<PathBuf> {
+        let campaign = self.get(&level.campaign)?;
+        let (_, entry) = campaign.levels().find(|(_, l)| l.id == level.level)?;
+        Some(campaign.path(entry))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn level(id: &str, requires: Option<&[&str]>) -> LevelEntry {
+        LevelEntry { id: id.to_string(), file: None, requires: requires.map(|r| r.iter().map(|s| s.to_string()).collect()) }
+    }
+
+    fn chapter(name: &str, requires: &[&str], levels: Vec<LevelEntry>) -> Chapter {
+        Chapter { name: name.to_string(), introduces: Vec::new(), requires: requires.iter().map(|s| s.to_string()).collect(), levels }
+    }
+
+    fn campaign(chapters: Vec<Chapter>) -> Campaign {
+        Campaign { id: "test".to_string(), dir: PathBuf::from(LEVEL_DIR), manifest: CampaignManifest { name: "Test".to_string(), chapters } }
+    }
+
+    fn cycles(campaign: &Campaign) -> Vec<CampaignError> {
+        campaign.validate().into_iter()
+            .filter(|e| matches!(e, CampaignError::ChapterCycle(_) | CampaignError::LevelCycle(_)))
+            .collect()
+    }
+
+    #[test]
+    fn levels_in_order_have_no_cycle() {
+        let campaign = campaign(vec![
+            chapter("A", &[], vec![level("1", None), level("2", None)]),
+            chapter("B", &["A"], vec![level("3", None), level("4", Some(&["2"]))]),
+        ]);
+        assert!(cycles(&campaign).is_empty());
+    }
+
+    #[test]
+    fn levels_requiring_each_other() {
+        let campaign = campaign(vec![chapter("A", &[], vec![level("1", Some(&["2"])), level("2", Some(&["1"]))])]);
+        match cycles(&campaign).as_slice() {
+            [CampaignError::LevelCycle(cycle)] => assert_eq!(cycle, &["1", "2", "1"]),
+            other => panic!("Expected a level cycle, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn chapters_requiring_each_other() {
+        let campaign = campaign(vec![
+            chapter("A", &["B"], vec![level("1", Some(&[]))]),
+            chapter("B", &["A"], vec![level("2", Some(&[]))]),
+        ]);
+        match cycles(&campaign).as_slice() {
+            [CampaignError::ChapterCycle(cycle)] => assert_eq!(cycle, &["A", "B", "A"]),
+            other => panic!("Expected a chapter cycle, got {:?}", other),
+        }
+    }
+
+    /// The first level of an earlier chapter defaults to requiring nothing, but requiring a later chapter locks it
+    #[test]
+    fn chapter_requiring_a_later_one() {
+        let campaign = campaign(vec![
+            chapter("A", &["B"], vec![level("1", None)]),
+            chapter("B", &[], vec![level("2", None)]),
+        ]);
+        assert!(matches!(cycles(&campaign).as_slice(), [CampaignError::LevelCycle(_)]));
+    }
+}
@@ -1,8 +1,11 @@
 use crate::game::{GridSize, PlacementGridEntity};
 use crate::sim::components::*;
-use crate::sim::helpers::Side;
+use crate::sim::helpers::{Orientation, Side};
+use crate::sim::packages::PackageLibrary;
+use crate::sim::definitions::ComponentRegistry;
+use crate::sim::levels::LevelData;
 use crate::sim::model::{
-    CellState, Component as SimComponent, DummyComponent as DummySimComponent, SimulationData,
+    CellState, Component as SimComponent, DummyComponent as DummySimComponent, GridComponent, SimulationData,
 };
 use crate::{GameState, MainTextureAtlas};
 use bevy::prelude::*;
@@ -12,10 +15,13 @@ impl Plugin for ComponentSetupPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<PlaceComponentEvent>()
             .init_resource::<SimulationData>()
+            .init_resource::<ComponentRegistry>()
+            .init_resource::<PlacementOrientation>()
             .init_resource::<GridSize>()
             .register_type::<SimulationData>()
             .add_system(clear_grid.in_schedule(OnExit(GameState::InGame)))
-            .add_system(placement_event.run_if(in_state(GameState::InGame)));
+            .add_system(placement_event.run_if(in_state(GameState::InGame)))
+            .add_system(placement_orientation_keys.run_if(in_state(GameState::InGame)));
     }
 }
 
@@ -40,8 +46,63 @@ impl SimComponent {
             SimComponent::Counter(_) => DummySimComponent::Counter,
             SimComponent::Observer(_) => DummySimComponent::Observer,
             SimComponent::Provider(_) => DummySimComponent::Provider,
+            SimComponent::Constant(_) => DummySimComponent::Constant,
+            SimComponent::ToggleSwitch(_) => DummySimComponent::ToggleSwitch,
+            SimComponent::PushButton(_) => DummySimComponent::PushButton,
+            SimComponent::WireCrossover(_) => DummySimComponent::WireCrossover,
+            SimComponent::Delay(_) => DummySimComponent::Delay,
+            SimComponent::Led(_) => DummySimComponent::Led,
+            SimComponent::SevenSegment(_) => DummySimComponent::SevenSegment,
+            SimComponent::PixelMatrix(_) => DummySimComponent::PixelMatrix,
+            SimComponent::SchmittTrigger(_) => DummySimComponent::SchmittTrigger,
+            SimComponent::Scaler(_) => DummySimComponent::Scaler,
+            SimComponent::Mixer(_) => DummySimComponent::Mixer,
+            SimComponent::MinMax(_) => DummySimComponent::MinMax,
+            SimComponent::AbsDifference(_) => DummySimComponent::AbsDifference,
+            SimComponent::RandomSource(_) => DummySimComponent::RandomSource,
+            SimComponent::Package(_) => DummySimComponent::Package,
+            SimComponent::Scripted(_) => DummySimComponent::Scripted,
+            SimComponent::BitShift(_) => DummySimComponent::BitShift,
+            SimComponent::BitMask(_) => DummySimComponent::BitMask,
+            SimComponent::BitExtract(_) => DummySimComponent::BitExtract,
+            SimComponent::BitPack(_) => DummySimComponent::BitPack,
+            SimComponent::FifoQueue(_) => DummySimComponent::FifoQueue,
+            SimComponent::LifoStack(_) => DummySimComponent::LifoStack,
+            SimComponent::StateMachine(_) => DummySimComponent::StateMachine,
+            SimComponent::TunnelSend(_) => DummySimComponent::TunnelSend,
+            SimComponent::TunnelReceive(_) => DummySimComponent::TunnelReceive,
+            SimComponent::PullUp(_) => DummySimComponent::PullUp,
+            SimComponent::PullDown(_) => DummySimComponent::PullDown,
         }
     }
+
+    /// Size in grid cells, packages and custom components pick their own size so use this over [DummySimComponent::get_grid_size] when there is a component
+    pub fn grid_size(&self) -> [usize; 2] {
+        match self {
+            SimComponent::Package(p) => p.grid_size(),
+            SimComponent::Scripted(s) => s.grid_size(),
+            _ => self.dummy().get_grid_size(),
+        }
+    }
+
+    /// Size in pixels, see [Self::grid_size]
+    pub fn size(&self) -> Vec2 {
+        match self {
+            SimComponent::Package(p) => p.size(),
+            SimComponent::Scripted(s) => s.size(),
+            _ => self.dummy().get_size(),
+        }
+    }
+
+    pub fn oriented_grid_size(&self, orientation: Orientation) -> [usize; 2] {
+        orientation.grid_size(self.grid_size())
+    }
+
+    /// The component's ports after turning it, offsets are from the bottom left of the turned component
+    pub fn oriented_ports(&self, orientation: Orientation) -> Vec<([usize; 2], Side)> {
+        let size = self.grid_size();
+        self.ports().into_iter().map(|(offset, side)| orientation.apply(*offset, *side, size)).collect()
+    }
 }
 
 impl DummySimComponent {
@@ -55,6 +116,33 @@ impl DummySimComponent {
             Self::Counter => SimComponent::Counter(Counter::default()),
             Self::Observer => SimComponent::Observer(Observer::default()),
             Self::Provider => SimComponent::Provider(Provider::default()),
+            Self::Constant => SimComponent::Constant(Constant::default()),
+            Self::ToggleSwitch => SimComponent::ToggleSwitch(ToggleSwitch::default()),
+            Self::PushButton => SimComponent::PushButton(PushButton::default()),
+            Self::WireCrossover => SimComponent::WireCrossover(WireCrossover::default()),
+            Self::Delay => SimComponent::Delay(Delay::default()),
+            Self::Led => SimComponent::Led(Led::default()),
+            Self::SevenSegment => SimComponent::SevenSegment(SevenSegment::default()),
+            Self::PixelMatrix => SimComponent::PixelMatrix(PixelMatrix::default()),
+            Self::SchmittTrigger => SimComponent::SchmittTrigger(SchmittTrigger::default()),
+            Self::Scaler => SimComponent::Scaler(Scaler::default()),
+            Self::Mixer => SimComponent::Mixer(Mixer::default()),
+            Self::MinMax => SimComponent::MinMax(MinMax::default()),
+            Self::AbsDifference => SimComponent::AbsDifference(AbsDifference::default()),
+            Self::RandomSource => SimComponent::RandomSource(RandomSource::default()),
+            Self::Package => SimComponent::Package(Package::default()),
+            Self::Scripted => SimComponent::Scripted(Scripted::default()),
+            Self::BitShift => SimComponent::BitShift(BitShift::default()),
+            Self::BitMask => SimComponent::BitMask(BitMask::default()),
+            Self::BitExtract => SimComponent::BitExtract(BitExtract::default()),
+            Self::BitPack => SimComponent::BitPack(BitPack::default()),
+            Self::FifoQueue => SimComponent::FifoQueue(FifoQueue::default()),
+            Self::LifoStack => SimComponent::LifoStack(LifoStack::default()),
+            Self::StateMachine => SimComponent::StateMachine(StateMachine::default()),
+            Self::TunnelSend => SimComponent::TunnelSend(TunnelSend::default()),
+            Self::TunnelReceive => SimComponent::TunnelReceive(TunnelReceive::default()),
+            Self::PullUp => SimComponent::PullUp(PullUp::default()),
+            Self::PullDown => SimComponent::PullDown(PullDown::default()),
         }
     }
 
@@ -75,9 +163,36 @@ impl DummySimComponent {
             Self::GateAnd => "gate_and",
             Self::SignalCopy => "signal_copy",
             Self::SignalPassthrough => "signal_passthrough",
-            Self::Counter => "signal_copy", // TODO: Make sprite for counter
+            Self::Counter => "counter",
             Self::Observer => "observer",
             Self::Provider => "provider",
+            Self::Constant => "constant",
+            Self::ToggleSwitch => "switch_off",
+            Self::PushButton => "button",
+            Self::WireCrossover => "crossover_up_down_left_right",
+            Self::Delay => "delay",
+            Self::Led => "led_0",
+            Self::SevenSegment => "seven_segment",
+            Self::PixelMatrix => "pixel_matrix",
+            Self::SchmittTrigger => "schmitt_trigger",
+            Self::Scaler => "scaler",
+            Self::Mixer => "mixer",
+            Self::MinMax => "min_max_min",
+            Self::AbsDifference => "abs_difference",
+            Self::RandomSource => "random",
+            Self::Package => "package_1x1",
+            Self::Scripted => "custom_1x1",
+            Self::BitShift => "bit_shift_left",
+            Self::BitMask => "bit_mask",
+            Self::BitExtract => "bit_extract",
+            Self::BitPack => "bit_pack",
+            Self::FifoQueue => "fifo_queue",
+            Self::LifoStack => "lifo_stack",
+            Self::StateMachine => "state_machine",
+            Self::TunnelSend => "tunnel_send",
+            Self::TunnelReceive => "tunnel_receive",
+            Self::PullUp => "pull_up",
+            Self::PullDown => "pull_down",
         };
         s
     }
@@ -92,6 +207,33 @@ impl DummySimComponent {
             Self::Counter => Vec2::new(32.0, 64.0),
             Self::Observer => Vec2::splat(32.0),
             Self::Provider => Vec2::splat(32.0),
+            Self::Constant => Vec2::splat(32.0),
+            Self::ToggleSwitch => Vec2::splat(32.0),
+            Self::PushButton => Vec2::splat(32.0),
+            Self::WireCrossover => Vec2::splat(32.0),
+            Self::Delay => Vec2::splat(32.0),
+            Self::Led => Vec2::splat(32.0),
+            Self::SevenSegment => Vec2::new(32.0, 64.0),
+            Self::PixelMatrix => Vec2::splat(64.0),
+            Self::SchmittTrigger => Vec2::splat(32.0),
+            Self::Scaler => Vec2::splat(32.0),
+            Self::Mixer => Vec2::new(32.0, 64.0),
+            Self::MinMax => Vec2::new(32.0, 64.0),
+            Self::AbsDifference => Vec2::new(32.0, 64.0),
+            Self::RandomSource => Vec2::splat(32.0),
+            Self::Package => Vec2::splat(32.0),
+            Self::Scripted => Vec2::splat(32.0),
+            Self::BitShift => Vec2::splat(32.0),
+            Self::BitMask => Vec2::splat(32.0),
+            Self::BitExtract => Vec2::splat(32.0),
+            Self::BitPack => Vec2::new(128.0, 64.0),
+            Self::FifoQueue => Vec2::new(32.0, 96.0),
+            Self::LifoStack => Vec2::new(32.0, 96.0),
+            Self::StateMachine => Vec2::new(64.0, 64.0),
+            Self::TunnelSend => Vec2::new(32.0, 32.0),
+            Self::TunnelReceive => Vec2::new(32.0, 32.0),
+            Self::PullUp => Vec2::new(32.0, 32.0),
+            Self::PullDown => Vec2::new(32.0, 32.0),
         }
     }
 
@@ -105,6 +247,33 @@ impl DummySimComponent {
             Self::Counter => [1, 2],
             Self::Observer => [1, 1],
             Self::Provider => [1, 1],
+            Self::Constant => [1, 1],
+            Self::ToggleSwitch => [1, 1],
+            Self::PushButton => [1, 1],
+            Self::WireCrossover => [1, 1],
+            Self::Delay => [1, 1],
+            Self::Led => [1, 1],
+            Self::SevenSegment => [1, 2],
+            Self::PixelMatrix => [2, 2],
+            Self::SchmittTrigger => [1, 1],
+            Self::Scaler => [1, 1],
+            Self::Mixer => [1, 2],
+            Self::MinMax => [1, 2],
+            Self::AbsDifference => [1, 2],
+            Self::RandomSource => [1, 1],
+            Self::Package => [1, 1],
+            Self::Scripted => [1, 1],
+            Self::BitShift => [1, 1],
+            Self::BitMask => [1, 1],
+            Self::BitExtract => [1, 1],
+            Self::BitPack => [4, 2],
+            Self::FifoQueue => [1, 3],
+            Self::LifoStack => [1, 3],
+            Self::StateMachine => [2, 2],
+            Self::TunnelSend => [1, 1],
+            Self::TunnelReceive => [1, 1],
+            Self::PullUp => [1, 1],
+            Self::PullDown => [1, 1],
         }
     }
 
@@ -118,8 +287,50 @@ impl DummySimComponent {
             DummySimComponent::Counter => crate::sim::components::Counter::CONST_PORTS.values(),
             DummySimComponent::Observer => crate::sim::components::Observer::CONST_PORTS.values(),
             DummySimComponent::Provider => crate::sim::components::Provider::CONST_PORTS.values(),
+            DummySimComponent::Constant => crate::sim::components::Constant::CONST_PORTS.values(),
+            DummySimComponent::ToggleSwitch => crate::sim::components::ToggleSwitch::CONST_PORTS.values(),
+            DummySimComponent::PushButton => crate::sim::components::PushButton::CONST_PORTS.values(),
+            DummySimComponent::WireCrossover => crate::sim::components::WireCrossover::CONST_PORTS.values(),
+            DummySimComponent::Delay => crate::sim::components::Delay::CONST_PORTS.values(),
+            DummySimComponent::Led => crate::sim::components::Led::CONST_PORTS.values(),
+            DummySimComponent::SevenSegment => crate::sim::components::SevenSegment::CONST_PORTS.values(),
+            DummySimComponent::PixelMatrix => crate::sim::components::PixelMatrix::CONST_PORTS.values(),
+            DummySimComponent::SchmittTrigger => crate::sim::components::SchmittTrigger::CONST_PORTS.values(),
+            DummySimComponent::Scaler => crate::sim::components::Scaler::CONST_PORTS.values(),
+            DummySimComponent::Mixer => crate::sim::components::Mixer::CONST_PORTS.values(),
+            DummySimComponent::MinMax => crate::sim::components::MinMax::CONST_PORTS.values(),
+            DummySimComponent::AbsDifference => crate::sim::components::AbsDifference::CONST_PORTS.values(),
+            DummySimComponent::RandomSource => crate::sim::components::RandomSource::CONST_PORTS.values(),
+            DummySimComponent::Package => crate::sim::components::Package::CONST_PORTS.values(),
+            DummySimComponent::Scripted => crate::sim::components::Scripted::CONST_PORTS.values(),
+            DummySimComponent::BitShift => crate::sim::components::BitShift::CONST_PORTS.values(),
+            DummySimComponent::BitMask => crate::sim::components::BitMask::CONST_PORTS.values(),
+            DummySimComponent::BitExtract => crate::sim::components::BitExtract::CONST_PORTS.values(),
+            DummySimComponent::BitPack => crate::sim::components::BitPack::CONST_PORTS.values(),
+            DummySimComponent::FifoQueue => crate::sim::components::FifoQueue::CONST_PORTS.values(),
+            DummySimComponent::LifoStack => crate::sim::components::LifoStack::CONST_PORTS.values(),
+            DummySimComponent::StateMachine => crate::sim::components::StateMachine::CONST_PORTS.values(),
+            DummySimComponent::TunnelSend => crate::sim::components::TunnelSend::CONST_PORTS.values(),
+            DummySimComponent::TunnelReceive => crate::sim::components::TunnelReceive::CONST_PORTS.values(),
+            DummySimComponent::PullUp => crate::sim::components::PullUp::CONST_PORTS.values(),
+            DummySimComponent::PullDown => crate::sim::components::PullDown::CONST_PORTS.values(),
         }.collect()
     }
+
+    /// [Self::ports] after turning the component, offsets are from the bottom left of the turned component
+    pub fn oriented_ports(&self, orientation: Orientation) -> Vec<([usize; 2], Side)> {
+        let size = self.get_grid_size();
+        self.ports().into_iter().map(|(offset, side)| orientation.apply(*offset, *side, size)).collect()
+    }
+
+    pub fn oriented_grid_size(&self, orientation: Orientation) -> [usize; 2] {
+        orientation.grid_size(self.get_grid_size())
+    }
+
+    /// Wires pick their sprite from their connections so turning them makes no sense
+    pub fn can_orient(&self) -> bool {
+        !matches!(self, DummySimComponent::WirePiece | DummySimComponent::WireCrossover)
+    }
     
     pub fn name(&self) -> &'static str {
         match self {
@@ -131,6 +342,33 @@ impl DummySimComponent {
             DummySimComponent::Counter => "Counter",
             DummySimComponent::Observer => "Observer",
             DummySimComponent::Provider => "Provider",
+            DummySimComponent::Constant => "Constant",
+            DummySimComponent::ToggleSwitch => "Toggle switch",
+            DummySimComponent::PushButton => "Push button",
+            DummySimComponent::WireCrossover => "Wire crossover",
+            DummySimComponent::Delay => "Delay line",
+            DummySimComponent::Led => "LED",
+            DummySimComponent::SevenSegment => "7-segment display",
+            DummySimComponent::PixelMatrix => "Pixel matrix",
+            DummySimComponent::SchmittTrigger => "Schmitt trigger",
+            DummySimComponent::Scaler => "Scaler",
+            DummySimComponent::Mixer => "Mixer",
+            DummySimComponent::MinMax => "Min / max",
+            DummySimComponent::AbsDifference => "Difference",
+            DummySimComponent::RandomSource => "Random source",
+            DummySimComponent::Package => "Package",
+            DummySimComponent::Scripted => "Custom component",
+            DummySimComponent::BitShift => "Bit shift",
+            DummySimComponent::BitMask => "Bit mask",
+            DummySimComponent::BitExtract => "Bit extract",
+            DummySimComponent::BitPack => "Bit pack",
+            DummySimComponent::FifoQueue => "Queue (FIFO)",
+            DummySimComponent::LifoStack => "Stack (LIFO)",
+            DummySimComponent::StateMachine => "State machine",
+            DummySimComponent::TunnelSend => "Tunnel send",
+            DummySimComponent::TunnelReceive => "Tunnel receive",
+            DummySimComponent::PullUp => "Pull-up",
+            DummySimComponent::PullDown => "Pull-down",
             
         }
     }
@@ -142,9 +380,36 @@ impl DummySimComponent {
             DummySimComponent::GateAnd => "Checks if two signals are equal to 255, if so outputs 255.",
             DummySimComponent::SignalCopy => "Duplicates a signal acrosss two wires.",
             DummySimComponent::SignalPassthrough => "Passthoughs a signal, can be used for delays.",
-            DummySimComponent::Counter => "Counts the amount of logic HIGHs on the input when there is also a HIGH on the clock signal.",
+            DummySimComponent::Counter => "Counts the amount of logic HIGHs on the input when there is also a HIGH on the clock signal.\nA HIGH on the bottom port resets it. Direction and wrapping can be changed in its menu.",
             DummySimComponent::Observer => "Watches the input and records the test data.",
             DummySimComponent::Provider => "Publishes test data into your circuit.",
+            DummySimComponent::Constant => "Outputs a fixed value which can be set in its menu.",
+            DummySimComponent::ToggleSwitch => "Click while the simulation is running to flip between 0 and 255.",
+            DummySimComponent::PushButton => "Click while the simulation is running to output 255 for a single tick.",
+            DummySimComponent::WireCrossover => "Lets two wires cross, up connects to down and left connects to right.",
+            DummySimComponent::Delay => "Outputs its input a set number of ticks later.",
+            DummySimComponent::Led => "Glows brighter the higher the input is.",
+            DummySimComponent::SevenSegment => "Shows the input as a single digit, the mode can be changed in its menu.",
+            DummySimComponent::PixelMatrix => "An 8x8 display, each tick the pixel at the address is set from the data input.",
+            DummySimComponent::SchmittTrigger => "Outputs 255 once the input reaches the upper threshold and 0 once it falls to the lower threshold.",
+            DummySimComponent::Scaler => "Multiplies the input by a fixed factor, limited to 0 - 255.",
+            DummySimComponent::Mixer => "Blends the two inputs together, by default outputting their average.",
+            DummySimComponent::MinMax => "Outputs the smallest or largest of the two inputs.",
+            DummySimComponent::AbsDifference => "Outputs how far apart the two inputs are.",
+            DummySimComponent::RandomSource => "Outputs a pseudo random value every tick or whenever the clock input goes HIGH. The same seed always gives the same values.",
+            DummySimComponent::Package => "A circuit from your package library placed as a single component.",
+            DummySimComponent::Scripted => "A component defined in a file in data/components.",
+            DummySimComponent::BitShift => "Moves the bits of the input left or right by a set amount. Rotating wraps the bits that fall off round to the other end, arithmetic right shifts keep the top bit.",
+            DummySimComponent::BitMask => "ANDs the input with a constant mask so only the chosen bits get through.",
+            DummySimComponent::BitExtract => "Outputs HIGH when the chosen bit of the input is set and LOW otherwise.",
+            DummySimComponent::BitPack => "Builds a byte from 8 inputs, each HIGH input sets its bit. The top row are bits 7 to 4, the bottom row bits 3 to 0.",
+            DummySimComponent::FifoQueue => "Stores values and gives them back in the order they went in. A HIGH on push stores data in, a HIGH on pop removes the oldest. Data out shows the next value to come out.",
+            DummySimComponent::LifoStack => "Stores values and gives back the most recent first. A HIGH on push stores data in, a HIGH on pop removes the newest. Data out shows the next value to come out.",
+            DummySimComponent::StateMachine => "Steps through states set by a table. Each row says which state and input range it applies to, the next state and the value to output. Outputs the current state number too.",
+            DummySimComponent::TunnelSend => "Sends its input to every tunnel receiver with the same label, as if a wire joined them.",
+            DummySimComponent::TunnelReceive => "Outputs whatever reaches the tunnel sender with the same label.",
+            DummySimComponent::PullUp => "Holds the net it is on at 255 when nothing else writes to it. Any other output overrides it.",
+            DummySimComponent::PullDown => "Holds the net it is on at 0 when nothing else writes to it. Any other output overrides it.",
         }
     }
 }
@@ -156,7 +421,25 @@ pub struct Size(pub UVec2);
 #[derive(Debug, Component)]
 pub struct GridLink(pub [usize; 2]);
 
-pub struct PlaceComponentEvent(pub [usize; 2], pub DummySimComponent);
+/// Position, what to place and which way it is turned.\
+/// Packages and data driven components also give their name in the [PackageLibrary] or [ComponentRegistry]
+pub struct PlaceComponentEvent(pub [usize; 2], pub DummySimComponent, pub Orientation, pub Option<String>);
+
+/// The way newly placed components are turned, changed with R (rotate) and F (flip) while dragging
+#[derive(Resource, Debug, Default, Clone, Copy)]
+pub struct PlacementOrientation(pub Orientation);
+
+fn placement_orientation_keys(
+    keys: Res<Input<KeyCode>>,
+    mut placement_orientation: ResMut<PlacementOrientation>,
+) {
+    if keys.just_pressed(KeyCode::R) {
+        placement_orientation.0 = placement_orientation.0.rotate_clockwise();
+    }
+    if keys.just_pressed(KeyCode::F) {
+        placement_orientation.0 = placement_orientation.0.mirror();
+    }
+}
 
 fn placement_event(
     mut commands: Commands,
@@ -166,20 +449,42 @@ fn placement_event(
     mut component_sprites: Query<&mut TextureAtlasSprite, With<GridLink>>,
     atlases: Res<Assets<TextureAtlas>>,
     main_atlas: Res<MainTextureAtlas>,
+    library: Res<PackageLibrary>,
+    registry: Res<ComponentRegistry>,
+    level_data: Option<Res<LevelData>>,
 ) {
     let atlas = atlases.get(&main_atlas.handle).unwrap();
     let grid = placement_grid.single();
     let size = grid.2;
     let grid_bottom_left = grid.1.translation.truncate() - (size.0.as_vec2() * 0.5);
     for event in place_ev.iter() {
+        if let Some(level_data) = &level_data {
+            if let Err(e) = level_data.can_place(event.1, &sim_data.grid) {
+                info!("Refused to place {:?}, {:?} by the level's palette", event.1, e);
+                continue;
+            }
+        }
+        let component = match (&event.3, event.1) {
+            (Some(name), DummySimComponent::Package) => library.build(name),
+            (Some(name), DummySimComponent::Scripted) => registry.build(name),
+            _ => Some(event.1.build_default()),
+        };
+        let component = match component {
+            Some(c) => c,
+            None => {
+                error!("Attempted to place {:?} {:?} which no longer exists", event.1, event.3);
+                continue;
+            }
+        };
         match sim_data.place_new_component(
             &mut commands,
             &grid_bottom_left,
             atlas,
             &main_atlas,
             &mut component_sprites,
-            event.1,
+            component,
             &event.0,
+            event.2,
         ) {
             Ok(_) => info!("Placed new component. {:?} at {:?}", event.1, event.0),
             Err(_) => info!("Attempted to place component in blocked position"),
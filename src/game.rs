@@ -1,9 +1,9 @@
-use std::path::PathBuf;
-
 use crate::components::placement::Size;
+use crate::campaign::Campaigns;
 use crate::level_select::CurrentLevel;
 use crate::sim::interactions::{SelectedComponentIndicator, GridComponentClick, GridClick, SelectedComponent};
 use crate::sim::levels::LoadLevelEvent;
+use crate::sim::level_editor::EditorBoardEvent;
 use crate::{ui, MainTextureAtlas};
 use bevy::prelude::*;
 use bevy_mod_picking::events::EventListener;
@@ -29,6 +29,10 @@ impl Default for GridSize {
 #[derive(Component)]
 pub struct GameRoot;
 
+/// Outlines the part of the grid which will be turned into a package
+#[derive(Component)]
+pub struct PackageRegionIndicator;
+
 #[derive(Component)]
 pub struct PlacementGridEntity;
 
@@ -37,11 +41,13 @@ pub fn setup_screen(
     mut commands: Commands,
     ass: Res<AssetServer>,
     level: Res<CurrentLevel>,
+    campaigns: Res<Campaigns>,
 
     atlases: Res<Assets<TextureAtlas>>,
     main_atlas: Res<MainTextureAtlas>,
     mut selected_component: ResMut<SelectedComponent>,
     mut load_level: EventWriter<LoadLevelEvent>,
+    mut editor_board: EventWriter<EditorBoardEvent>,
 ) {
     // At the moment `CurrentLevel` actually refers to the level to load
     commands.spawn((
@@ -91,8 +97,26 @@ pub fn setup_screen(
         SelectedComponentIndicator,
     ));
 
+    commands.spawn((
+        ShapeBundle {
+            path: GeometryBuilder::build_as(&shape),
+            transform: Transform::from_translation(Vec3::new(0.0, 0.0, 101.0)),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        Stroke::new(Color::CYAN, 2.0),
+        GameRoot,
+        PackageRegionIndicator,
+    ));
+
     selected_component.0 = None;
-    load_level.send(LoadLevelEvent(PathBuf::from(format!("data/levels/{}.json", level.0.unwrap()))));
+    match &level.0 {
+        Some(level) => match campaigns.path(level) {
+            Some(path) => load_level.send(LoadLevelEvent(path)),
+            None => error!("Level {} is not in any campaign", level),
+        },
+        None => editor_board.send(EditorBoardEvent::New),
+    }
 }
 
 /// Unit component to mark an entity as interactable for the click_system
@@ -1,4 +1,9 @@
+use std::{collections::BTreeSet, fs::File, path::Path};
+
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{GameState, campaign::{Campaign, Campaigns, LevelId}, sim::test_cases::TestResults};
 
 #[derive(Component)]
 pub struct LevelsMenu;
@@ -6,24 +11,140 @@ pub struct LevelsMenu;
 #[derive(Component)]
 struct Row;
 
-#[derive(Component)]
-pub struct LevelButton;
+/// Which level a button opens and whether it can be played yet
+#[derive(Component, Debug, Clone)]
+pub struct LevelButton {
+    pub level: LevelId,
+    pub state: LevelState,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LevelState {
+    Locked,
+    Open,
+    Completed,
+}
+
+impl LevelState {
+    /// Background colour when nothing is hovering over the button
+    fn colour(&self) -> Color {
+        match self {
+            LevelState::Locked => Color::rgba(0.25, 0.25, 0.25, 1.0),
+            LevelState::Open => Color::rgba(0.5, 0.5, 0.5, 1.0),
+            LevelState::Completed => Color::rgba(0.4, 0.7, 0.4, 1.0),
+        }
+    }
+}
 
 #[derive(Component)]
 pub struct ButtonText;
 
-#[derive(Resource)] // TODO:
-pub struct CurrentLevel(pub Option<u16>);
+/// The level to load when entering [GameState::InGame], `None` opens the level editor instead
+#[derive(Resource)]
+pub struct CurrentLevel(pub Option<LevelId>);
+
+impl CurrentLevel {
+    /// Where the player's saves for this level go
+    pub fn save_dir(&self) -> String {
+        match &self.0 {
+            Some(level) => format!("data/levels/user/{}", level),
+            None => "data/levels/user/editor".to_string(),
+        }
+    }
+}
+
+const MAX_ROW_LENGTH: usize = 10;
+/// Where finished levels are remembered between runs
+const PROGRESS_FILE: &str = "data/progress.json";
 
-const LEVEL_COUNT: u16 = 35;
-const MAX_ROW_LENGTH: u16 = 10;
-const ROW_COUNT: u16 = LEVEL_COUNT / MAX_ROW_LENGTH + (LEVEL_COUNT % MAX_ROW_LENGTH != 0) as u16;
+/// Which levels have been completed, saved to [PROGRESS_FILE] whenever one is
+#[derive(Resource, Debug, Default, Serialize, Deserialize)]
+pub struct Progress {
+    /// Levels shown as in [LevelId]'s `Display`
+    pub completed: BTreeSet<String>,
+}
+
+/// Progress from before campaigns, when every level was numbered
+#[derive(Deserialize)]
+struct NumberedProgress {
+    completed: BTreeSet<u16>,
+}
+
+/// Loads at startup, a missing or broken file just means nothing is completed yet
+impl FromWorld for Progress {
+    fn from_world(_: &mut World) -> Self {
+        let value: serde_json::Value = match File::open(PROGRESS_FILE).ok().and_then(|file| serde_json::from_reader(file).ok()) {
+            Some(value) => value,
+            None => return Progress::default(),
+        };
+        if let Ok(old) = serde_json::from_value::<NumberedProgress>(value.clone()) {
+            return Progress { completed: old.completed.iter().map(|l| l.to_string()).collect() };
+        }
+        serde_json::from_value(value).map_err(|e| error!("Could not read {}: {}", PROGRESS_FILE, e)).unwrap_or_default()
+    }
+}
+
+impl Progress {
+    pub fn is_completed(&self, level: &LevelId) -> bool {
+        self.completed.contains(&level.to_string())
+    }
+
+    /// A level opens once everything it requires in its campaign is completed
+    pub fn state(&self, campaign: &Campaign, level: &str) -> LevelState {
+        let completed = |id: &str| self.is_completed(&LevelId { campaign: campaign.id.clone(), level: id.to_string() });
+        if completed(level) {
+            return LevelState::Completed;
+        }
+        match campaign.requirements(level).iter().all(|r| completed(r)) {
+            true => LevelState::Open,
+            false => LevelState::Locked,
+        }
+    }
+
+    /// The first open level in the campaign which is not completed yet, or the last level if they all are
+    pub fn next_level(&self, campaign: &Campaign) -> Option<LevelId> {
+        campaign.levels()
+            .find(|(_, l)| self.state(campaign, &l.id) == LevelState::Open)
+            .or_else(|| campaign.levels().last())
+            .map(|(_, l)| campaign.level_id(l))
+    }
 
-/// Change button color on interaction
+    pub fn complete(&mut self, level: &LevelId) {
+        if self.completed.insert(level.to_string()) {
+            if let Err(e) = self.save(Path::new(PROGRESS_FILE)) {
+                error!("Could not save progress: {}", e);
+            }
+        }
+    }
+
+    fn save(&self, path: &Path) -> std::io::Result<()> {
+        let file = File::create(path)?;
+        serde_json::to_writer_pretty(file, self).map_err(std::io::Error::from)
+    }
+}
+
+/// Marks the level as completed once every test case passes
+pub fn record_completion(
+    results: Res<TestResults>,
+    current_level: Res<CurrentLevel>,
+    mut progress: ResMut<Progress>,
+) {
+    if !results.is_changed() || !results.all_passed() {return}
+    if let Some(level) = &current_level.0 {
+        progress.complete(level);
+    }
+}
+
+/// Change button color on interaction, locked levels stay dark
 pub fn butt_interact_visual(
-    mut query: Query<(&Interaction, &mut BackgroundColor), (Changed<Interaction>, With<Button>)>,
+    mut query: Query<(&Interaction, &mut BackgroundColor, Option<&LevelButton>), (Changed<Interaction>, With<Button>)>,
 ) {
-    for (interaction, mut color) in query.iter_mut() {
+    for (interaction, mut color, level_button) in query.iter_mut() {
+        let state = level_button.map(|b| b.state).unwrap_or(LevelState::Open);
+        if state == LevelState::Locked {
+            *color = BackgroundColor(state.colour());
+            continue;
+        }
         match interaction {
             Interaction::Clicked => {
                 *color = BackgroundColor(Color::rgba(0.2, 0.2, 0.2, 1.0));
@@ -32,36 +153,21 @@ pub fn butt_interact_visual(
                 *color = BackgroundColor(Color::rgba(0.4, 0.4, 0.4, 1.0));
             }
             Interaction::None => {
-                *color = BackgroundColor(Color::rgba(0.5, 0.5, 0.5, 1.0));
+                *color = BackgroundColor(state.colour());
             }
         }
     }
 }
 
-/// Condition to help with handling multiple buttons
-///
-/// We get which level it is working off from the text. We do this by queryi9ng the children of the buttons to get the text and then parsing it back to u16.
-pub fn on_butt_interact<B: Component>(
-    _commands: Commands,
-    q_parent: Query<(&Children, &Interaction), (Changed<Interaction>, With<Button>, With<B>)>, // The buttons
-    q_child: Query<&Text, With<ButtonText>>, // Should be the text
+/// Start the clicked level, unless it is still locked
+pub fn on_level_button(
+    mut commands: Commands,
+    buttons: Query<(&LevelButton, &Interaction), (Changed<Interaction>, With<Button>)>,
 ) {
-    for (children, interaction) in q_parent.iter() {
-        if *interaction == Interaction::Clicked {
-            let mut level_num: u16 = 0;
-            for &child in children.iter() {
-                let text = q_child.get(child);
-                match text {
-                    Err(e) => println!("{e}"),
-                    Ok(bevy_text) => {
-                        //println!("Found text!");
-                        let text = bevy_text.sections[0].value.clone();
-                        level_num = text.parse().unwrap();
-                    }
-                }
-            }
-
-            //println!("{level_num}");
+    for (button, interaction) in buttons.iter() {
+        if *interaction == Interaction::Clicked && button.state != LevelState::Locked {
+            commands.insert_resource(CurrentLevel(Some(button.level.clone())));
+            commands.insert_resource(NextState(Some(GameState::InGame)));
         }
     }
 }
@@ -71,7 +177,7 @@ pub fn on_butt_interact<B: Component>(
 //}
 
 /// Sets up level select screen using flexboxes and stuff
-pub fn setup(mut commands: Commands, ass: Res<AssetServer>) {
+pub fn setup(mut commands: Commands, ass: Res<AssetServer>, progress: Res<Progress>, campaigns: Res<Campaigns>) {
     let button_style = Style {
         align_items: AlignItems::Center,
         flex_direction: FlexDirection::Row,
@@ -88,6 +194,18 @@ pub fn setup(mut commands: Commands, ass: Res<AssetServer>) {
         font_size: 32.0,
         color: Color::BLACK,
     };
+    let heading_style = TextStyle {
+        font_size: 24.0,
+        ..text_style.clone()
+    };
+    let heading = |text: String| TextBundle {
+        text: Text::from_section(text, heading_style.clone()),
+        style: Style {
+            margin: UiRect::new(Val::Px(4.0), Val::Px(4.0), Val::Px(8.0), Val::Px(0.0)),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
 
     // Contains padding and button container, used to set flex direction into column, letting padding shift down instead of to the right
     let whole = commands
@@ -145,54 +263,66 @@ pub fn setup(mut commands: Commands, ass: Res<AssetServer>) {
         .id();
 
     //let mut rows: [Option<Entity>; ROW_COUNT as usize] = [None; ROW_COUNT as usize];
-    let mut rows = Vec::<Entity>::with_capacity(ROW_COUNT as usize);
-    for x in 0..ROW_COUNT as usize {
-        rows.push(
-            commands
-                .spawn((
-                    NodeBundle {
-                        background_color: BackgroundColor(Color::NONE),
-                        //background_color: BackgroundColor(Color::rgba (1.0*(x as f32/(ROW_COUNT as f32-1.0)),0.0,1.0,1.0)),
-                        style: Style {
-                            //flex_direction: FlexDirection::Row,
-                            //align_items: AlignItems::FlexStart,
-                            //justify_content: JustifyContent::FlexStart,
-                            margin: UiRect::all(Val::Px(2.0)),
-                            ..Default::default()
-                        },
-                        ..Default::default()
-                    },
-                    Row,
-                ))
-                .id(),
-        );
-
-        let row_length = std::cmp::min(LEVEL_COUNT as usize - (x * MAX_ROW_LENGTH as usize), 10);
-        let mut items = Vec::<Entity>::with_capacity(10);
-        for y in 0..row_length {
-            let string_number = format!("{:02}", (x * MAX_ROW_LENGTH as usize + y + 1)); // Change this +1 if you want to start from zero
-            items.push(
-                commands
+    // Each campaign and chapter gets a heading above its rows of buttons
+    let mut rows = Vec::<Entity>::new();
+    for campaign in &campaigns.0 {
+        rows.push(commands.spawn(heading(campaign.manifest.name.clone())).id());
+        for chapter in &campaign.manifest.chapters {
+            let mut title = chapter.name.clone();
+            if !chapter.introduces.is_empty() {
+                title = format!("{} - New: {}", title, chapter.introduces.join(", "));
+            }
+            rows.push(commands.spawn(heading(title)).id());
+
+            for row_levels in chapter.levels.chunks(MAX_ROW_LENGTH) {
+                let row = commands
                     .spawn((
-                        ButtonBundle {
-                            style: button_style.clone(),
+                        NodeBundle {
+                            background_color: BackgroundColor(Color::NONE),
+                            style: Style {
+                                margin: UiRect::all(Val::Px(2.0)),
+                                ..Default::default()
+                            },
                             ..Default::default()
                         },
-                        LevelButton,
+                        Row,
                     ))
-                    .with_children(|btn| {
-                        btn.spawn((
-                            TextBundle {
-                                text: Text::from_section(string_number, text_style.clone()),
-                                ..Default::default()
-                            },
-                            ButtonText,
-                        ));
-                    })
-                    .id(),
-            );
+                    .id();
+                rows.push(row);
+
+                let mut items = Vec::<Entity>::with_capacity(MAX_ROW_LENGTH);
+                for level in row_levels {
+                    // Numbered levels keep their leading zero so the buttons line up
+                    let label = match level.id.parse::<u16>() {
+                        Ok(number) => format!("{:02}", number),
+                        Err(_) => level.id.clone(),
+                    };
+                    let state = progress.state(campaign, &level.id);
+                    items.push(
+                        commands
+                            .spawn((
+                                ButtonBundle {
+                                    style: button_style.clone(),
+                                    background_color: BackgroundColor(state.colour()),
+                                    ..Default::default()
+                                },
+                                LevelButton { level: campaign.level_id(level), state },
+                            ))
+                            .with_children(|btn| {
+                                btn.spawn((
+                                    TextBundle {
+                                        text: Text::from_section(label, text_style.clone()),
+                                        ..Default::default()
+                                    },
+                                    ButtonText,
+                                ));
+                            })
+                            .id(),
+                    );
+                }
+                commands.entity(row).push_children(&items);
+            }
         }
-        commands.entity(rows[x]).push_children(&items);
     }
     commands.entity(whole).push_children(&[padding, container]);
     commands.entity(container).push_children(&rows);
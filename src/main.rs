@@ -3,6 +3,7 @@
 #![allow(clippy::too_many_lines)]
 extern crate glob;
 
+mod campaign;
 mod components;
 mod config;
 mod game;
@@ -43,6 +44,11 @@ pub enum GameState {
 }
 
 fn main() {
+    // Check every level file and exit without opening a window, see [sim::levels::check_levels]
+    if std::env::args().any(|arg| arg == "--check-levels") {
+        std::process::exit(sim::levels::check_levels());
+    }
+
     App::new()
         .add_plugins(
             DefaultPlugins
@@ -61,7 +67,9 @@ fn main() {
         // Resources
         .insert_resource(Msaa::Sample2)
         .insert_resource(ClearColor(Color::rgb_u8(30, 32, 48)))
-        .insert_resource(level_select::CurrentLevel(Some(1))) // TODO: Change to none + working level select
+        .insert_resource(level_select::CurrentLevel(None))
+        .init_resource::<level_select::Progress>()
+        .init_resource::<campaign::Campaigns>()
         // Plugins (foreign)
         .add_plugin(PixelCameraPlugin)
         .add_plugin(ShapePlugin)
@@ -90,6 +98,14 @@ fn main() {
         .add_plugin(crate::ui::dummy_component::ComponentTrayPlugin)
         .add_plugin(crate::components::placement::ComponentSetupPlugin)
         .add_plugin(crate::ui::egui::main::LeftPanelPlugin)
+        .add_plugin(crate::ui::egui::package::PackageMenuPlugin)
+        .add_plugin(crate::ui::egui::test_cases::TestCaseMenuPlugin)
+        .add_plugin(crate::sim::test_cases::TestCasePlugin)
+        .add_plugin(crate::ui::egui::scoring::ScoreMenuPlugin)
+        .add_plugin(crate::sim::scoring::ScoringPlugin)
+        .add_plugin(crate::ui::egui::level_editor::LevelEditorMenuPlugin)
+        .add_plugin(crate::sim::level_editor::LevelEditorPlugin)
+        .add_plugin(crate::sim::packages::PackagePlugin)
         .add_plugin(crate::ui::egui::theming::EguiThemingPlugin)
         .add_plugin(crate::config::SettingsPlugin)
         .add_plugin(crate::settings::SettingsMenuPlugin)
@@ -105,12 +121,12 @@ fn main() {
         .add_system(despawn_with::<game::GameRoot>.in_schedule(OnExit(GameState::InGame)))
         // menu stuff
         // in-game stuff
-        .add_systems((back_to_menu_on_esc,).distributive_run_if(in_state(GameState::InGame)))
+        .add_systems((back_to_menu_on_esc, level_select::record_completion).distributive_run_if(in_state(GameState::InGame)))
         // Levels menu
         .add_systems(
             (
                 level_select::butt_interact_visual,
-                level_select::on_butt_interact::<level_select::LevelButton>,
+                level_select::on_level_button,
                 back_to_menu_on_esc,
             )
                 .distributive_run_if(in_state(GameState::LevelsMenu)),
@@ -1,9 +1,9 @@
-use crate::GameState;
+use crate::{GameState, campaign::{Campaigns, MAIN_CAMPAIGN}, level_select::{CurrentLevel, Progress}, sim::level_editor::LevelDraft};
 use bevy::{
     app::AppExit,
     prelude::{
         in_state, App, AssetServer, Commands, EventWriter, FromWorld, Handle, Image,
-        IntoSystemConfigs, Local, NextState, Plugin, World,
+        IntoSystemConfigs, Local, NextState, Plugin, Res, World,
     },
     window::close_on_esc,
 };
@@ -27,6 +27,8 @@ fn main_menu(
     mut egui_ctx: EguiContexts,
     images: Local<MainMenuImages>,
     mut exit: EventWriter<AppExit>,
+    progress: Res<Progress>,
+    campaigns: Res<Campaigns>,
 ) {
     let background = egui_ctx.add_image(images.background.clone_weak());
     let ctx_mut = egui_ctx.ctx_mut();
@@ -57,8 +59,12 @@ fn main_menu(
 
                             let text = RichText::new("Play").font(egui::FontId { size: 46., family: egui::FontFamily::Monospace });
                             let button_play = ui.add_sized(Vec2::new(350., 75.), egui::Button::new(text));
+                            // Carries on from the first level of the main campaign not completed yet
                             if button_play.clicked() {
-                                commands.insert_resource(NextState(Some(GameState::InGame)));
+                                if let Some(level) = campaigns.get(MAIN_CAMPAIGN).and_then(|c| progress.next_level(c)) {
+                                    commands.insert_resource(CurrentLevel(Some(level)));
+                                    commands.insert_resource(NextState(Some(GameState::InGame)));
+                                }
                             }
 
                             ui.allocate_space(vec2(10., 10.));
@@ -68,6 +74,15 @@ fn main_menu(
                                 commands.insert_resource(NextState(Some(GameState::LevelsMenu)));
                             }
 
+                            ui.allocate_space(vec2(10., 10.));
+                            let text = RichText::new("Level Editor").font(egui::FontId { size: 46., family: egui::FontFamily::Monospace });
+                            let button_editor = ui.add_sized(Vec2::new(350., 75.), egui::Button::new(text));
+                            if button_editor.clicked() {
+                                commands.insert_resource(CurrentLevel(None));
+                                commands.insert_resource(LevelDraft::default());
+                                commands.insert_resource(NextState(Some(GameState::InGame)));
+                            }
+
                             ui.allocate_space(vec2(10., 10.));
                             let text = RichText::new("Settings").font(egui::FontId { size: 46., family: egui::FontFamily::Monospace });
                             let button_settings = ui.add_sized(Vec2::new(350., 75.), egui::Button::new(text));
@@ -0,0 +1,82 @@
+use crate::sim::{
+    helpers::{self, Side},
+    model::{AudioEvent, GridComponent, SimulationData, VisualEvent, ComponentPortData, DummyComponent}, interactions::UpdateComponentEvent,
+};
+use bevy::{
+    reflect::{FromReflect, Reflect},
+    sprite::{TextureAtlas, TextureAtlasSprite}, prelude::{World, EventWriter},
+};
+use enum_map::{Enum, EnumMap};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use crate::sim::net::Net;
+
+/// Outputs how far apart its two inputs are, |A - B|\
+/// Differences no bigger than the dead band are treated as 0, useful for ignoring small amounts of noise
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Reflect, FromReflect)]
+pub struct AbsDifference {
+    #[reflect(ignore)]
+    #[serde(skip)]
+    ports: EnumMap<AbsDifferencePorts, ComponentPortData>,
+    #[serde(default)]
+    dead_band: u8,
+}
+
+impl GridComponent for AbsDifference {
+    fn tick(&mut self, _: [usize; 2], _: usize, _: &mut World) -> (Vec<VisualEvent>, Vec<AudioEvent>) {
+        let input_a = self.ports[AbsDifferencePorts::InputA].get();
+        let input_b = self.ports[AbsDifferencePorts::InputB].get();
+        let difference = input_a.abs_diff(input_b);
+        self.ports[AbsDifferencePorts::Output].set(if difference <= self.dead_band {0} else {difference});
+        (Vec::new(), Vec::new())
+    }
+
+    fn build(&mut self) {}
+
+    fn on_place(
+        &mut self,
+        _own_pos: &[usize; 2],
+        _sim_data: &SimulationData,
+        _sprite: &mut TextureAtlasSprite,
+        _atlas: &TextureAtlas,
+    ) {
+    }
+
+    fn ports(&self) -> Vec<&([usize; 2], Side)> {
+        return Self::CONST_PORTS.values().collect();
+    }
+
+    fn set_port(&mut self, offset: [usize; 2], side: Side, set_to: Arc<Net>) -> Result<(),()> {
+        for (varient, (self_offset, self_side)) in Self::CONST_PORTS.iter() {
+            if offset == *self_offset && side == *self_side {
+                self.ports[varient].set_link(Some(set_to));
+                return Ok(());
+            }
+        }
+        Err(())
+    }
+
+    fn gui_options(&mut self, ui: &mut egui::Ui, sim_halted: bool, _: DummyComponent, _: &[usize; 2], _: &mut EventWriter<UpdateComponentEvent>) {
+        ui.add_enabled_ui(sim_halted, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Dead band: ");
+                ui.add(egui::Slider::new(&mut self.dead_band, 0..=255)).on_disabled_hover_text(helpers::UI_DISABLED_MSG);
+            });
+        });
+    }
+}
+
+impl AbsDifference {
+    pub const CONST_PORTS: EnumMap<AbsDifferencePorts, ([usize; 2], Side)> = EnumMap::from_array([
+        ([0, 1], Side::Left),
+        ([0, 0], Side::Left),
+        ([0, 0], Side::Right),
+    ]);
+}
+
+#[derive(Debug, Enum)]
+pub enum AbsDifferencePorts {
+    InputA,
+    InputB,
+    Output,
+}
@@ -8,7 +8,8 @@ use bevy::{
 };
 use enum_map::{Enum, EnumMap};
 use serde::{Deserialize, Serialize};
-use std::sync::{atomic::AtomicU8, Arc};
+use std::sync::Arc;
+use crate::sim::net::Net;
 
 /// A 'And' gate component which should do typical AND behaviour, consider 100 ON, anything else OFF\
 /// No connection defaults to 0 hence off
@@ -48,7 +49,7 @@ impl GridComponent for GateAnd {
         return Self::CONST_PORTS.values().collect();
     }
 
-    fn set_port(&mut self, offset: [usize; 2], side: Side, set_to: Arc<AtomicU8>) -> Result<(),()> {
+    fn set_port(&mut self, offset: [usize; 2], side: Side, set_to: Arc<Net>) -> Result<(),()> {
         for (varient, (self_offset, self_side)) in Self::CONST_PORTS.iter() {
             if offset == *self_offset && side == *self_side {
                 self.ports[varient].set_link(Some(set_to));
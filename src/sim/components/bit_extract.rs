@@ -0,0 +1,77 @@
+use crate::sim::{
+    helpers::{self, Side},
+    model::{AudioEvent, GridComponent, SimulationData, VisualEvent, ComponentPortData, DummyComponent}, interactions::UpdateComponentEvent,
+};
+use bevy::{
+    reflect::{FromReflect, Reflect},
+    sprite::{TextureAtlas, TextureAtlasSprite}, prelude::{World, EventWriter},
+};
+use enum_map::{Enum, EnumMap};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use crate::sim::net::Net;
+
+/// Outputs HIGH (255) when a single chosen bit of its input is set, otherwise LOW
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Reflect, FromReflect)]
+pub struct BitExtract {
+    #[reflect(ignore)]
+    #[serde(skip)]
+    ports: EnumMap<BitExtractPorts, ComponentPortData>,
+    /// Which bit to output, 0 is the least significant
+    #[serde(default)]
+    bit: u8,
+}
+
+impl GridComponent for BitExtract {
+    fn tick(&mut self, _: [usize; 2], _: usize, _: &mut World) -> (Vec<VisualEvent>, Vec<AudioEvent>) {
+        let input = self.ports[BitExtractPorts::Input].get();
+        let set = input & (1 << self.bit.min(7)) != 0;
+        self.ports[BitExtractPorts::Output].set(if set {255} else {0});
+        (Vec::new(), Vec::new())
+    }
+
+    fn build(&mut self) {}
+
+    fn on_place(
+        &mut self,
+        _own_pos: &[usize; 2],
+        _sim_data: &SimulationData,
+        _sprite: &mut TextureAtlasSprite,
+        _atlas: &TextureAtlas,
+    ) {
+    }
+
+    fn ports(&self) -> Vec<&([usize; 2], Side)> {
+        return Self::CONST_PORTS.values().collect();
+    }
+
+    fn set_port(&mut self, offset: [usize; 2], side: Side, set_to: Arc<Net>) -> Result<(),()> {
+        for (varient, (self_offset, self_side)) in Self::CONST_PORTS.iter() {
+            if offset == *self_offset && side == *self_side {
+                self.ports[varient].set_link(Some(set_to));
+                return Ok(());
+            }
+        }
+        Err(())
+    }
+
+    fn gui_options(&mut self, ui: &mut egui::Ui, sim_halted: bool, _: DummyComponent, _: &[usize; 2], _: &mut EventWriter<UpdateComponentEvent>) {
+        ui.add_enabled_ui(sim_halted, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Bit: ");
+                ui.add(egui::Slider::new(&mut self.bit, 0..=7)).on_disabled_hover_text(helpers::UI_DISABLED_MSG);
+            });
+        });
+    }
+}
+
+impl BitExtract {
+    pub const CONST_PORTS: EnumMap<BitExtractPorts, ([usize; 2], Side)> =
+        EnumMap::from_array([([0, 0], Side::Left), ([0, 0], Side::Right)]);
+}
+
+#[derive(Debug, Enum)]
+pub enum BitExtractPorts {
+    Input,
+    Output,
+}
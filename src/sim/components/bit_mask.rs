@@ -0,0 +1,93 @@
+use crate::sim::{
+    helpers::{self, Side},
+    model::{AudioEvent, GridComponent, SimulationData, VisualEvent, ComponentPortData, DummyComponent}, interactions::UpdateComponentEvent,
+};
+use bevy::{
+    reflect::{FromReflect, Reflect},
+    sprite::{TextureAtlas, TextureAtlasSprite}, prelude::{World, EventWriter},
+};
+use enum_map::{Enum, EnumMap};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use crate::sim::net::Net;
+
+/// ANDs its input with a constant, only the bits set in the mask get through
+#[derive(Debug, Serialize, Deserialize, Clone, Reflect, FromReflect)]
+#[serde(default)]
+pub struct BitMask {
+    #[reflect(ignore)]
+    #[serde(skip)]
+    ports: EnumMap<BitMaskPorts, ComponentPortData>,
+    mask: u8,
+}
+
+impl Default for BitMask {
+    fn default() -> Self {
+        Self {
+            ports: EnumMap::default(),
+            mask: 0x0F,
+        }
+    }
+}
+
+impl GridComponent for BitMask {
+    fn tick(&mut self, _: [usize; 2], _: usize, _: &mut World) -> (Vec<VisualEvent>, Vec<AudioEvent>) {
+        let input = self.ports[BitMaskPorts::Input].get();
+        self.ports[BitMaskPorts::Output].set(input & self.mask);
+        (Vec::new(), Vec::new())
+    }
+
+    fn build(&mut self) {}
+
+    fn on_place(
+        &mut self,
+        _own_pos: &[usize; 2],
+        _sim_data: &SimulationData,
+        _sprite: &mut TextureAtlasSprite,
+        _atlas: &TextureAtlas,
+    ) {
+    }
+
+    fn ports(&self) -> Vec<&([usize; 2], Side)> {
+        return Self::CONST_PORTS.values().collect();
+    }
+
+    fn set_port(&mut self, offset: [usize; 2], side: Side, set_to: Arc<Net>) -> Result<(),()> {
+        for (varient, (self_offset, self_side)) in Self::CONST_PORTS.iter() {
+            if offset == *self_offset && side == *self_side {
+                self.ports[varient].set_link(Some(set_to));
+                return Ok(());
+            }
+        }
+        Err(())
+    }
+
+    fn gui_options(&mut self, ui: &mut egui::Ui, sim_halted: bool, _: DummyComponent, _: &[usize; 2], _: &mut EventWriter<UpdateComponentEvent>) {
+        ui.add_enabled_ui(sim_halted, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Mask: ");
+                ui.add(egui::DragValue::new(&mut self.mask)).on_disabled_hover_text(helpers::UI_DISABLED_MSG);
+            });
+            // Most significant bit first, the same way the number is written
+            ui.horizontal(|ui| {
+                for bit in (0..8).rev() {
+                    let mut set = self.mask & (1 << bit) != 0;
+                    if ui.checkbox(&mut set, "").on_hover_text(format!("Bit {}", bit)).on_disabled_hover_text(helpers::UI_DISABLED_MSG).changed() {
+                        self.mask ^= 1 << bit;
+                    }
+                }
+            });
+        });
+    }
+}
+
+impl BitMask {
+    pub const CONST_PORTS: EnumMap<BitMaskPorts, ([usize; 2], Side)> =
+        EnumMap::from_array([([0, 0], Side::Left), ([0, 0], Side::Right)]);
+}
+
+#[derive(Debug, Enum)]
+pub enum BitMaskPorts {
+    Input,
+    Output,
+}
@@ -0,0 +1,104 @@
+use crate::sim::{
+    helpers::Side,
+    model::{AudioEvent, GridComponent, SimulationData, VisualEvent, ComponentPortData, DummyComponent}, interactions::UpdateComponentEvent,
+};
+use bevy::{
+    reflect::{FromReflect, Reflect},
+    sprite::{TextureAtlas, TextureAtlasSprite}, prelude::{World, EventWriter},
+};
+use enum_map::{Enum, EnumMap};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use crate::sim::net::Net;
+
+/// Builds a byte out of 8 inputs, each one HIGH (255) sets its bit.\
+/// The top row are bits 7 - 4 and the bottom row bits 3 - 0, most significant on the left.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Reflect, FromReflect)]
+pub struct BitPack {
+    #[reflect(ignore)]
+    #[serde(skip)]
+    ports: EnumMap<BitPackPorts, ComponentPortData>,
+    #[serde(skip)]
+    value: u8,
+}
+
+impl GridComponent for BitPack {
+    fn tick(&mut self, _: [usize; 2], _: usize, _: &mut World) -> (Vec<VisualEvent>, Vec<AudioEvent>) {
+        let bits = [
+            BitPackPorts::Bit7, BitPackPorts::Bit6, BitPackPorts::Bit5, BitPackPorts::Bit4,
+            BitPackPorts::Bit3, BitPackPorts::Bit2, BitPackPorts::Bit1, BitPackPorts::Bit0,
+        ];
+        self.value = 0;
+        for (i, bit) in bits.into_iter().enumerate() {
+            if self.ports[bit].get() >= 255 {
+                self.value |= 1 << (7 - i);
+            }
+        }
+        self.ports[BitPackPorts::Output].set(self.value);
+        (Vec::new(), Vec::new())
+    }
+
+    fn build(&mut self) {
+        self.value = 0;
+    }
+
+    fn on_place(
+        &mut self,
+        _own_pos: &[usize; 2],
+        _sim_data: &SimulationData,
+        _sprite: &mut TextureAtlasSprite,
+        _atlas: &TextureAtlas,
+    ) {
+    }
+
+    fn ports(&self) -> Vec<&([usize; 2], Side)> {
+        return Self::CONST_PORTS.values().collect();
+    }
+
+    fn set_port(&mut self, offset: [usize; 2], side: Side, set_to: Arc<Net>) -> Result<(),()> {
+        for (varient, (self_offset, self_side)) in Self::CONST_PORTS.iter() {
+            if offset == *self_offset && side == *self_side {
+                self.ports[varient].set_link(Some(set_to));
+                return Ok(());
+            }
+        }
+        Err(())
+    }
+
+    fn gui_options(&mut self, ui: &mut egui::Ui, sim_halted: bool, _: DummyComponent, _: &[usize; 2], _: &mut EventWriter<UpdateComponentEvent>) {
+        ui.label("Top row: bits 7 - 4, bottom row: bits 3 - 0");
+        if !sim_halted {
+            ui.horizontal(|ui| {
+                ui.label("Current value: ");
+                ui.label(egui::RichText::new(format!("{} ({:08b})", self.value, self.value)).code());
+            });
+        }
+    }
+}
+
+impl BitPack {
+    pub const CONST_PORTS: EnumMap<BitPackPorts, ([usize; 2], Side)> = EnumMap::from_array([
+        ([0, 1], Side::Up),
+        ([1, 1], Side::Up),
+        ([2, 1], Side::Up),
+        ([3, 1], Side::Up),
+        ([0, 0], Side::Down),
+        ([1, 0], Side::Down),
+        ([2, 0], Side::Down),
+        ([3, 0], Side::Down),
+        ([3, 0], Side::Right),
+    ]);
+}
+
+#[derive(Debug, Enum)]
+pub enum BitPackPorts {
+    Bit7,
+    Bit6,
+    Bit5,
+    Bit4,
+    Bit3,
+    Bit2,
+    Bit1,
+    Bit0,
+    Output,
+}
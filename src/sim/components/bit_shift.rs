@@ -0,0 +1,144 @@
+use crate::sim::{
+    helpers::{self, Side},
+    model::{AudioEvent, GridComponent, SimulationData, VisualEvent, ComponentPortData, DummyComponent}, interactions::UpdateComponentEvent,
+};
+use bevy::{
+    reflect::{FromReflect, Reflect},
+    sprite::{TextureAtlas, TextureAtlasSprite}, prelude::{World, EventWriter, Handle},
+};
+use enum_map::{Enum, EnumMap};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use crate::sim::net::Net;
+
+/// Shifts or rotates the bits of its input by a fixed amount
+#[derive(Debug, Serialize, Deserialize, Clone, Reflect, FromReflect)]
+#[serde(default)]
+pub struct BitShift {
+    #[reflect(ignore)]
+    #[serde(skip)]
+    ports: EnumMap<BitShiftPorts, ComponentPortData>,
+    mode: ShiftMode,
+    /// How many bits to move by, 0 - 7
+    amount: u8,
+}
+
+impl Default for BitShift {
+    fn default() -> Self {
+        Self {
+            ports: EnumMap::default(),
+            mode: ShiftMode::Left,
+            amount: 1,
+        }
+    }
+}
+
+impl GridComponent for BitShift {
+    fn tick(&mut self, _: [usize; 2], _: usize, _: &mut World) -> (Vec<VisualEvent>, Vec<AudioEvent>) {
+        let input = self.ports[BitShiftPorts::Input].get();
+        let amount = self.amount.min(7) as u32;
+        let output = match self.mode {
+            ShiftMode::Left => input << amount,
+            ShiftMode::RightLogical => input >> amount,
+            // Keep the top (sign) bit by treating the input as signed
+            ShiftMode::RightArithmetic => ((input as i8) >> amount) as u8,
+            ShiftMode::RotateLeft => input.rotate_left(amount),
+            ShiftMode::RotateRight => input.rotate_right(amount),
+        };
+        self.ports[BitShiftPorts::Output].set(output);
+        (Vec::new(), Vec::new())
+    }
+
+    fn build(&mut self) {}
+
+    fn on_place(
+        &mut self,
+        _own_pos: &[usize; 2],
+        _sim_data: &SimulationData,
+        sprite: &mut TextureAtlasSprite,
+        atlas: &TextureAtlas,
+    ) {
+        if let Some(index) = atlas.get_texture_index(&Handle::weak(self.sprite_name().into())) {
+            sprite.index = index;
+        }
+    }
+
+    fn ports(&self) -> Vec<&([usize; 2], Side)> {
+        return Self::CONST_PORTS.values().collect();
+    }
+
+    fn set_port(&mut self, offset: [usize; 2], side: Side, set_to: Arc<Net>) -> Result<(),()> {
+        for (varient, (self_offset, self_side)) in Self::CONST_PORTS.iter() {
+            if offset == *self_offset && side == *self_side {
+                self.ports[varient].set_link(Some(set_to));
+                return Ok(());
+            }
+        }
+        Err(())
+    }
+
+    fn gui_options(&mut self, ui: &mut egui::Ui, sim_halted: bool, _: DummyComponent, pos: &[usize; 2], updates: &mut EventWriter<UpdateComponentEvent>) {
+        let before = self.mode;
+        ui.add_enabled_ui(sim_halted, |ui| {
+            egui::ComboBox::from_label("Mode")
+                .selected_text(self.mode.label())
+                .show_ui(ui, |ui| {
+                    for mode in [ShiftMode::Left, ShiftMode::RightLogical, ShiftMode::RightArithmetic, ShiftMode::RotateLeft, ShiftMode::RotateRight] {
+                        ui.selectable_value(&mut self.mode, mode, mode.label());
+                    }
+                }).response.on_disabled_hover_text(helpers::UI_DISABLED_MSG);
+            ui.horizontal(|ui| {
+                ui.label("Bits: ");
+                ui.add(egui::Slider::new(&mut self.amount, 0..=7)).on_disabled_hover_text(helpers::UI_DISABLED_MSG);
+            });
+        });
+        if before != self.mode {
+            updates.send(UpdateComponentEvent{pos: *pos, surround: false});
+        }
+    }
+}
+
+impl BitShift {
+    pub const CONST_PORTS: EnumMap<BitShiftPorts, ([usize; 2], Side)> =
+        EnumMap::from_array([([0, 0], Side::Left), ([0, 0], Side::Right)]);
+
+    pub fn sprite_name(&self) -> &'static str {
+        match self.mode {
+            ShiftMode::Left => "bit_shift_left",
+            ShiftMode::RightLogical => "bit_shift_right",
+            ShiftMode::RightArithmetic => "bit_shift_right_arithmetic",
+            ShiftMode::RotateLeft => "bit_shift_rotate_left",
+            ShiftMode::RotateRight => "bit_shift_rotate_right",
+        }
+    }
+}
+
+#[derive(Debug, Enum)]
+pub enum BitShiftPorts {
+    Input,
+    Output,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Reflect, FromReflect)]
+pub enum ShiftMode {
+    #[default]
+    Left,
+    /// Fills the top bits with 0
+    RightLogical,
+    /// Fills the top bits with copies of the top bit
+    RightArithmetic,
+    RotateLeft,
+    RotateRight,
+}
+
+impl ShiftMode {
+    fn label(self) -> &'static str {
+        match self {
+            ShiftMode::Left => "Shift left",
+            ShiftMode::RightLogical => "Shift right (logical)",
+            ShiftMode::RightArithmetic => "Shift right (arithmetic)",
+            ShiftMode::RotateLeft => "Rotate left",
+            ShiftMode::RotateRight => "Rotate right",
+        }
+    }
+}
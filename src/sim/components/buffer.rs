@@ -0,0 +1,232 @@
+use crate::sim::{
+    helpers::{self, Side},
+    model::{AudioEvent, GridComponent, SimulationData, VisualEvent, ComponentPortData, DummyComponent}, interactions::UpdateComponentEvent,
+};
+use bevy::{
+    reflect::{FromReflect, Reflect},
+    sprite::{TextureAtlas, TextureAtlasSprite}, prelude::{World, EventWriter},
+};
+use egui::RichText;
+use enum_map::{Enum, EnumMap};
+use serde::{Deserialize, Serialize};
+use std::{collections::VecDeque, sync::Arc};
+use crate::sim::net::Net;
+
+pub const MAX_CAPACITY: usize = 64;
+
+/// A first in first out queue, values come out in the order they went in.\
+/// See [Buffer] for how the ports behave.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Reflect, FromReflect)]
+pub struct FifoQueue {
+    #[serde(flatten)]
+    buffer: Buffer,
+}
+
+/// A last in first out stack, the most recent value pushed comes out first.\
+/// See [Buffer] for how the ports behave.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Reflect, FromReflect)]
+pub struct LifoStack {
+    #[serde(flatten)]
+    buffer: Buffer,
+}
+
+/// Storage shared by [FifoQueue] and [LifoStack].\
+/// Push and pop happen when their input goes HIGH, if both happen on the same tick the pop goes first.\
+/// Data out always shows the value which would be popped next, or 0 when empty.
+#[derive(Debug, Serialize, Deserialize, Clone, Reflect, FromReflect)]
+#[serde(default)]
+pub struct Buffer {
+    #[reflect(ignore)]
+    #[serde(skip)]
+    ports: EnumMap<BufferPorts, ComponentPortData>,
+    /// Most values held at once, 1 - [MAX_CAPACITY]
+    capacity: usize,
+    /// Oldest value at the front
+    #[reflect(ignore)]
+    #[serde(skip)]
+    contents: VecDeque<u8>,
+    #[serde(skip)]
+    last_push: u8,
+    #[serde(skip)]
+    last_pop: u8,
+}
+
+impl Default for Buffer {
+    fn default() -> Self {
+        Self {
+            ports: EnumMap::default(),
+            capacity: 8,
+            contents: VecDeque::new(),
+            last_push: 0,
+            last_pop: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BufferOrder {
+    Fifo,
+    Lifo,
+}
+
+impl Buffer {
+    pub const CONST_PORTS: EnumMap<BufferPorts, ([usize; 2], Side)> = EnumMap::from_array([
+        ([0, 2], Side::Left),
+        ([0, 1], Side::Left),
+        ([0, 0], Side::Left),
+        ([0, 2], Side::Right),
+        ([0, 1], Side::Right),
+        ([0, 0], Side::Right),
+    ]);
+
+    fn tick(&mut self, order: BufferOrder) {
+        let push = self.ports[BufferPorts::Push].get();
+        let pop = self.ports[BufferPorts::Pop].get();
+        let push_edge = push >= 255 && self.last_push < 255;
+        let pop_edge = pop >= 255 && self.last_pop < 255;
+        self.last_push = push;
+        self.last_pop = pop;
+
+        if pop_edge {
+            match order {
+                BufferOrder::Fifo => self.contents.pop_front(),
+                BufferOrder::Lifo => self.contents.pop_back(),
+            };
+        }
+        if push_edge && self.contents.len() < self.capacity {
+            self.contents.push_back(self.ports[BufferPorts::DataIn].get());
+        }
+
+        let next = match order {
+            BufferOrder::Fifo => self.contents.front(),
+            BufferOrder::Lifo => self.contents.back(),
+        };
+        self.ports[BufferPorts::DataOut].set(next.copied().unwrap_or(0));
+        self.ports[BufferPorts::Empty].set(if self.contents.is_empty() {255} else {0});
+        self.ports[BufferPorts::Full].set(if self.contents.len() >= self.capacity {255} else {0});
+    }
+
+    fn build(&mut self) {
+        self.capacity = self.capacity.clamp(1, MAX_CAPACITY);
+        self.contents.clear();
+        self.last_push = 0;
+        self.last_pop = 0;
+    }
+
+    fn set_port(&mut self, offset: [usize; 2], side: Side, set_to: Arc<Net>) -> Result<(),()> {
+        for (varient, (self_offset, self_side)) in Self::CONST_PORTS.iter() {
+            if offset == *self_offset && side == *self_side {
+                self.ports[varient].set_link(Some(set_to));
+                return Ok(());
+            }
+        }
+        Err(())
+    }
+
+    fn gui_options(&mut self, ui: &mut egui::Ui, sim_halted: bool, order: BufferOrder) {
+        ui.add_enabled_ui(sim_halted, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Capacity: ");
+                ui.add(egui::Slider::new(&mut self.capacity, 1..=MAX_CAPACITY)).on_disabled_hover_text(helpers::UI_DISABLED_MSG);
+            });
+        });
+
+        if !sim_halted {
+            ui.separator();
+            ui.label(format!("Holding {} of {}", self.contents.len(), self.capacity));
+            // Listed in the order they will come out
+            let contents: Vec<String> = match order {
+                BufferOrder::Fifo => self.contents.iter().map(|v| v.to_string()).collect(),
+                BufferOrder::Lifo => self.contents.iter().rev().map(|v| v.to_string()).collect(),
+            };
+            ui.label("Next out first:");
+            ui.horizontal_wrapped(|ui| {
+                for value in contents {
+                    ui.label(RichText::new(value).code());
+                }
+            });
+        }
+    }
+}
+
+impl GridComponent for FifoQueue {
+    fn tick(&mut self, _: [usize; 2], _: usize, _: &mut World) -> (Vec<VisualEvent>, Vec<AudioEvent>) {
+        self.buffer.tick(BufferOrder::Fifo);
+        (Vec::new(), Vec::new())
+    }
+
+    fn build(&mut self) {
+        self.buffer.build();
+    }
+
+    fn on_place(
+        &mut self,
+        _own_pos: &[usize; 2],
+        _sim_data: &SimulationData,
+        _sprite: &mut TextureAtlasSprite,
+        _atlas: &TextureAtlas,
+    ) {
+    }
+
+    fn ports(&self) -> Vec<&([usize; 2], Side)> {
+        return Self::CONST_PORTS.values().collect();
+    }
+
+    fn set_port(&mut self, offset: [usize; 2], side: Side, set_to: Arc<Net>) -> Result<(),()> {
+        self.buffer.set_port(offset, side, set_to)
+    }
+
+    fn gui_options(&mut self, ui: &mut egui::Ui, sim_halted: bool, _: DummyComponent, _: &[usize; 2], _: &mut EventWriter<UpdateComponentEvent>) {
+        self.buffer.gui_options(ui, sim_halted, BufferOrder::Fifo);
+    }
+}
+
+impl FifoQueue {
+    pub const CONST_PORTS: EnumMap<BufferPorts, ([usize; 2], Side)> = Buffer::CONST_PORTS;
+}
+
+impl GridComponent for LifoStack {
+    fn tick(&mut self, _: [usize; 2], _: usize, _: &mut World) -> (Vec<VisualEvent>, Vec<AudioEvent>) {
+        self.buffer.tick(BufferOrder::Lifo);
+        (Vec::new(), Vec::new())
+    }
+
+    fn build(&mut self) {
+        self.buffer.build();
+    }
+
+    fn on_place(
+        &mut self,
+        _own_pos: &[usize; 2],
+        _sim_data: &SimulationData,
+        _sprite: &mut TextureAtlasSprite,
+        _atlas: &TextureAtlas,
+    ) {
+    }
+
+    fn ports(&self) -> Vec<&([usize; 2], Side)> {
+        return Self::CONST_PORTS.values().collect();
+    }
+
+    fn set_port(&mut self, offset: [usize; 2], side: Side, set_to: Arc<Net>) -> Result<(),()> {
+        self.buffer.set_port(offset, side, set_to)
+    }
+
+    fn gui_options(&mut self, ui: &mut egui::Ui, sim_halted: bool, _: DummyComponent, _: &[usize; 2], _: &mut EventWriter<UpdateComponentEvent>) {
+        self.buffer.gui_options(ui, sim_halted, BufferOrder::Lifo);
+    }
+}
+
+impl LifoStack {
+    pub const CONST_PORTS: EnumMap<BufferPorts, ([usize; 2], Side)> = Buffer::CONST_PORTS;
+}
+
+#[derive(Debug, Enum)]
+pub enum BufferPorts {
+    DataIn,
+    Push,
+    Pop,
+    DataOut,
+    Empty,
+    Full,
+}
@@ -0,0 +1,87 @@
+use crate::sim::{
+    helpers::{self, Side},
+    model::{AudioEvent, GridComponent, SimulationData, VisualEvent, ComponentPortData, DummyComponent}, interactions::UpdateComponentEvent,
+};
+use bevy::{
+    reflect::{FromReflect, Reflect},
+    sprite::{TextureAtlas, TextureAtlasSprite}, prelude::{World, EventWriter, Handle},
+};
+use egui::RichText;
+use enum_map::{Enum, EnumMap};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use crate::sim::net::Net;
+
+/// A momentary button, clicking it while the simulation is running outputs 255 for the next tick only.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Reflect, FromReflect)]
+pub struct PushButton {
+    #[serde(skip)]
+    pressed: bool,
+    #[reflect(ignore)]
+    #[serde(skip)]
+    ports: EnumMap<PushButtonPorts, ComponentPortData>,
+}
+
+impl GridComponent for PushButton {
+    fn tick(&mut self, own_pos: [usize; 2], _: usize, world: &mut World) -> (Vec<VisualEvent>, Vec<AudioEvent>) {
+        if self.pressed {
+            self.ports[PushButtonPorts::Output].set(255);
+            // Release the button now the press has been seen by the rest of the circuit
+            self.pressed = false;
+            helpers::set_sprite_from_world(world, &own_pos, "button");
+        } else {
+            self.ports[PushButtonPorts::Output].set(0);
+        }
+        (Vec::new(), Vec::new())
+    }
+
+    fn build(&mut self) {
+        self.pressed = false;
+    }
+
+    fn on_place(
+        &mut self,
+        _own_pos: &[usize; 2],
+        _sim_data: &SimulationData,
+        _sprite: &mut TextureAtlasSprite,
+        _atlas: &TextureAtlas,
+    ) {
+    }
+
+    fn ports(&self) -> Vec<&([usize; 2], Side)> {
+        return Self::CONST_PORTS.values().collect();
+    }
+
+    fn set_port(&mut self, offset: [usize; 2], side: Side, set_to: Arc<Net>) -> Result<(),()> {
+        for (varient, (self_offset, self_side)) in Self::CONST_PORTS.iter() {
+            if offset == *self_offset && side == *self_side {
+                self.ports[varient].set_link(Some(set_to));
+                return Ok(());
+            }
+        }
+        Err(())
+    }
+
+    fn gui_options(&mut self, ui: &mut egui::Ui, _: bool, _: DummyComponent, _: &[usize; 2], _: &mut EventWriter<UpdateComponentEvent>) {
+        ui.horizontal(|ui| {
+            ui.label("Pressed: ");
+            ui.label(RichText::new(if self.pressed {"Yes"} else {"No"}).code());
+        });
+    }
+
+    fn interact(&mut self, sprite: &mut TextureAtlasSprite, atlas: &TextureAtlas) {
+        self.pressed = true;
+        sprite.index = atlas
+            .get_texture_index(&Handle::weak("button_pressed".into()))
+            .expect("Could not find button sprite");
+    }
+}
+
+impl PushButton {
+    pub const CONST_PORTS: EnumMap<PushButtonPorts, ([usize; 2], Side)> = EnumMap::from_array([([0, 0], Side::Right)]);
+}
+
+#[derive(Debug, Enum)]
+pub enum PushButtonPorts {
+    Output,
+}
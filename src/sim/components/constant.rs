@@ -0,0 +1,71 @@
+use crate::sim::{
+    helpers::{self, Side},
+    model::{AudioEvent, GridComponent, SimulationData, VisualEvent, ComponentPortData, DummyComponent}, interactions::UpdateComponentEvent,
+};
+use bevy::{
+    reflect::{FromReflect, Reflect},
+    sprite::{TextureAtlas, TextureAtlasSprite}, prelude::{World, EventWriter},
+};
+use enum_map::{Enum, EnumMap};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use crate::sim::net::Net;
+
+/// Constantly outputs a value set by the player in the components menu
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Reflect, FromReflect)]
+pub struct Constant {
+    value: u8,
+    #[reflect(ignore)]
+    #[serde(skip)]
+    ports: EnumMap<ConstantPorts, ComponentPortData>,
+}
+
+impl GridComponent for Constant {
+    fn tick(&mut self, _: [usize; 2], _: usize, _: &mut World) -> (Vec<VisualEvent>, Vec<AudioEvent>) {
+        self.ports[ConstantPorts::Output].set(self.value);
+        (Vec::new(), Vec::new())
+    }
+
+    fn build(&mut self) {}
+
+    fn on_place(
+        &mut self,
+        _own_pos: &[usize; 2],
+        _sim_data: &SimulationData,
+        _sprite: &mut TextureAtlasSprite,
+        _atlas: &TextureAtlas,
+    ) {
+    }
+
+    fn ports(&self) -> Vec<&([usize; 2], Side)> {
+        return Self::CONST_PORTS.values().collect();
+    }
+
+    fn set_port(&mut self, offset: [usize; 2], side: Side, set_to: Arc<Net>) -> Result<(),()> {
+        for (varient, (self_offset, self_side)) in Self::CONST_PORTS.iter() {
+            if offset == *self_offset && side == *self_side {
+                self.ports[varient].set_link(Some(set_to));
+                return Ok(());
+            }
+        }
+        Err(())
+    }
+
+    fn gui_options(&mut self, ui: &mut egui::Ui, sim_halted: bool, _: DummyComponent, _: &[usize; 2], _: &mut EventWriter<UpdateComponentEvent>) {
+        ui.add_enabled_ui(sim_halted, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Value: ");
+                ui.add(egui::Slider::new(&mut self.value, 0..=255)).on_disabled_hover_text(helpers::UI_DISABLED_MSG);
+            });
+        });
+    }
+}
+
+impl Constant {
+    pub const CONST_PORTS: EnumMap<ConstantPorts, ([usize; 2], Side)> = EnumMap::from_array([([0, 0], Side::Right)]);
+}
+
+#[derive(Debug, Enum)]
+pub enum ConstantPorts {
+    Output,
+}
@@ -8,7 +8,8 @@ use bevy::{
 };
 use enum_map::{Enum, EnumMap};
 use serde::{Deserialize, Serialize};
-use std::sync::{atomic::AtomicU8, Arc};
+use std::sync::Arc;
+use crate::sim::net::Net;
 
 /// More of a debug component, not sure if it will really be need in final program\
 /// Copy input into both outputs - Very similar to passthrough
@@ -42,7 +43,7 @@ impl GridComponent for SignalCopy {
         return Self::CONST_PORTS.values().collect();
     }
 
-    fn set_port(&mut self, offset: [usize; 2], side: Side, set_to: Arc<AtomicU8>) -> Result<(),()> {
+    fn set_port(&mut self, offset: [usize; 2], side: Side, set_to: Arc<Net>) -> Result<(),()> {
         for (varient, (self_offset, self_side)) in Self::CONST_PORTS.iter() {
             if offset == *self_offset && side == *self_side {
                 self.ports[varient].set_link(Some(set_to));
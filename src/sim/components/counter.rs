@@ -1,40 +1,87 @@
 use crate::sim::{
-    helpers::Side,
+    helpers::{self, Side},
     model::{AudioEvent, GridComponent, SimulationData, VisualEvent, ComponentPortData, DummyComponent}, interactions::UpdateComponentEvent,
 };
 use bevy::{
     reflect::{FromReflect, Reflect},
-    sprite::{TextureAtlas, TextureAtlasSprite}, prelude::{World, EventWriter},
+    sprite::{TextureAtlas, TextureAtlasSprite}, prelude::{World, EventWriter, Vec2},
 };
 use egui::RichText;
 use enum_map::{Enum, EnumMap};
 use serde::{Deserialize, Serialize};
-use std::sync::{atomic::AtomicU8, Arc};
+use std::sync::Arc;
+use crate::sim::net::Net;
 
-/// A counter which whenever it reads 100 on input A and 100 on input CLK (Clock) it will increment an internal counter and post that on the output
-#[derive(Debug, Default, Serialize, Deserialize, Clone, Reflect, FromReflect)]
+const DIGIT_SPRITES: [&str; 10] = [
+    "digit_0", "digit_1", "digit_2", "digit_3", "digit_4",
+    "digit_5", "digit_6", "digit_7", "digit_8", "digit_9",
+];
+/// Offset from the bottom left of the component for the hundreds, tens and ones digit shown on the sprite
+const DIGIT_OFFSETS: [Vec2; 3] = [Vec2::new(5.0, 27.0), Vec2::new(13.0, 27.0), Vec2::new(21.0, 27.0)];
+
+/// A counter which whenever it reads 255 on input A and 255 on input CLK (Clock) it will step an internal counter and post that on the output\
+/// A HIGH on the reset input sets the count back to 0. Counts wrap around at `modulo`.
+#[derive(Debug, Serialize, Deserialize, Clone, Reflect, FromReflect)]
+#[serde(default)]
 pub struct Counter {
     #[reflect(ignore)]
     #[serde(skip)]
     ports: EnumMap<CounterPorts, ComponentPortData>,
+    count: u8, // Only kept between runs if `persist_count` is set
+    direction: CountDirection,
+    /// The count wraps to 0 once it reaches this, 2 - 256
+    modulo: u16,
+    /// Keep the count between runs (and in saves) instead of starting from 0
+    persist_count: bool,
+    /// The count currently shown on the sprite, [None] if it needs redrawing
+    #[reflect(ignore)]
     #[serde(skip)]
-    count: u8, // Defaults 0
+    shown_count: Option<u8>,
+}
+
+impl Default for Counter {
+    fn default() -> Self {
+        Self {
+            ports: EnumMap::default(),
+            count: 0,
+            direction: CountDirection::Up,
+            modulo: 256,
+            persist_count: false,
+            shown_count: None,
+        }
+    }
 }
 
 impl GridComponent for Counter {
-    fn tick(&mut self, _: [usize; 2], _: usize, _: &mut World) -> (Vec<VisualEvent>, Vec<AudioEvent>) {
+    fn tick(&mut self, own_pos: [usize; 2], _: usize, world: &mut World) -> (Vec<VisualEvent>, Vec<AudioEvent>) {
         let input = self.ports[CounterPorts::Input].get();
         let input_clk = self.ports[CounterPorts::InputClk].get();
-        if input_clk >= 255u8 && input >= 255u8 {
-            self.count = self.count.wrapping_add(1);
-            self.ports[CounterPorts::Output].set(self.count);
-            // TODO: I would like to add an event to display the counter number ontop of the sprite.
+        let reset = self.ports[CounterPorts::Reset].get();
+        if reset >= 255u8 {
+            self.count = 0;
+        } else if input_clk >= 255u8 && input >= 255u8 {
+            self.step();
+        }
+        self.ports[CounterPorts::Output].set(self.count);
+
+        if self.shown_count != Some(self.count) {
+            self.shown_count = Some(self.count);
+            let digits = [self.count / 100, self.count / 10 % 10, self.count % 10];
+            let overlays: Vec<(Vec2, &str)> = DIGIT_OFFSETS.iter().zip(digits).map(|(offset, digit)| {
+                (*offset, DIGIT_SPRITES[digit as usize])
+            }).collect();
+            helpers::set_overlay_sprites(world, &own_pos, &overlays);
         }
         (Vec::new(), Vec::new())
     }
 
     fn build(&mut self) {
-        self.count = 0;
+        if !self.persist_count {
+            self.count = 0;
+        }
+        self.modulo = self.modulo.clamp(2, 256);
+        self.count = (self.count as u16 % self.modulo) as u8;
+        self.shown_count = None;
     }
 
     fn on_place(
@@ -50,7 +97,7 @@ impl GridComponent for Counter {
         return Self::CONST_PORTS.values().collect();
     }
 
-    fn set_port(&mut self, offset: [usize; 2], side: Side, set_to: Arc<AtomicU8>) -> Result<(),()> {
+    fn set_port(&mut self, offset: [usize; 2], side: Side, set_to: Arc<Net>) -> Result<(),()> {
         for (varient, (self_offset, self_side)) in Self::CONST_PORTS.iter() {
             if offset == *self_offset && side == *self_side {
                 self.ports[varient].set_link(Some(set_to));
@@ -60,11 +107,24 @@ impl GridComponent for Counter {
         Err(())
     }
 
-    fn gui_options(&mut self, ui: &mut egui::Ui, _: bool, _: DummyComponent, _: &[usize; 2], _: &mut EventWriter<UpdateComponentEvent>) {
+    fn gui_options(&mut self, ui: &mut egui::Ui, sim_halted: bool, _: DummyComponent, _: &[usize; 2], _: &mut EventWriter<UpdateComponentEvent>) {
         ui.horizontal(|ui| {
             ui.label("Current Count: ");
             ui.label(RichText::new(self.count.to_string()).code());
         });
+
+        ui.add_enabled_ui(sim_halted, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Direction: ");
+                ui.selectable_value(&mut self.direction, CountDirection::Up, "Up");
+                ui.selectable_value(&mut self.direction, CountDirection::Down, "Down");
+            });
+            ui.horizontal(|ui| {
+                ui.label("Wrap at: ");
+                ui.add(egui::DragValue::new(&mut self.modulo).clamp_range(2..=256)).on_disabled_hover_text(helpers::UI_DISABLED_MSG);
+            });
+            ui.checkbox(&mut self.persist_count, "Keep count between runs").on_disabled_hover_text(helpers::UI_DISABLED_MSG);
+        });
     }
 }
 
@@ -73,7 +133,17 @@ impl Counter {
         ([0, 1], Side::Left),
         ([0, 0], Side::Left),
         ([0, 1], Side::Right),
+        ([0, 0], Side::Down),
     ]);
+
+    /// Move the count one step in the current direction, wrapping at the modulo
+    fn step(&mut self) {
+        let count = self.count as u16;
+        self.count = match self.direction {
+            CountDirection::Up => ((count + 1) % self.modulo) as u8,
+            CountDirection::Down => ((count + self.modulo - 1) % self.modulo) as u8,
+        };
+    }
 }
 
 #[derive(Debug, Enum)]
@@ -81,4 +151,12 @@ pub enum CounterPorts {
     Input,
     InputClk,
     Output,
+    Reset,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Reflect, FromReflect)]
+pub enum CountDirection {
+    #[default]
+    Up,
+    Down,
 }
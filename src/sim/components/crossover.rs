@@ -0,0 +1,93 @@
+use std::sync::Arc;
+use crate::sim::net::Net;
+
+use crate::sim::helpers;
+use crate::sim::{
+    helpers::Side,
+    model::{
+        AudioEvent, GridComponent, SimulationData, VisualEvent, DummyComponent
+    },
+    interactions::UpdateComponentEvent,
+};
+use bevy::prelude::{World, EventWriter};
+use bevy::{
+    prelude::Handle,
+    reflect::{FromReflect, Reflect},
+    sprite::{TextureAtlas, TextureAtlasSprite},
+};
+use egui::RichText;
+use enum_map::{Enum, EnumMap};
+use serde::{Deserialize, Serialize};
+
+use super::wire::{check_for_wire_link, sides_to_sprite_name};
+
+/// Lets two wires cross without connecting.\
+/// Up is only connected to down and left is only connected to right, see [crate::sim::run::flood_fill]
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Reflect, FromReflect)]
+pub struct WireCrossover {
+    #[reflect(ignore)]
+    pub connected_sides: EnumMap<helpers::Side, bool>,
+}
+
+impl GridComponent for WireCrossover {
+    // Like wires, crossovers are handled entirely when building the simulation
+    fn tick(&mut self, _: [usize; 2], _: usize, _: &mut World) -> (Vec<VisualEvent>, Vec<AudioEvent>) {
+        (Vec::new(), Vec::new())
+    }
+
+    fn build(&mut self) {}
+
+    fn on_place(
+        &mut self,
+        own_pos: &[usize; 2],
+        sim_data: &SimulationData,
+        sprite: &mut TextureAtlasSprite,
+        atlas: &TextureAtlas,
+    ) {
+        let mut sides = sim_data.port_grid.get_sides(own_pos);
+        for (side, state) in sides.iter_mut() {
+            if let Some(pos) = helpers::combine_offset(own_pos, &side.as_offset()) {
+                if check_for_wire_link(&pos, &sim_data.grid, side.reverse()) {
+                    *state = true;
+                }
+            }
+        }
+        let idx = atlas
+            .get_texture_index(&Handle::weak(Self::sprite_name(&sides).into()))
+            .expect("Could not find correct crossover varient");
+        sprite.index = idx;
+        self.connected_sides = sides;
+    }
+
+    fn ports(&self) -> Vec<&([usize; 2], Side)> {
+        Vec::new()
+    }
+
+    fn set_port(&mut self, _: [usize; 2], _: Side, _: Arc<Net>) -> Result<(),()> {
+        Err(())
+    }
+
+    fn gui_options(&mut self, ui: &mut egui::Ui, _: bool, _: DummyComponent, _: &[usize; 2], _: &mut EventWriter<UpdateComponentEvent>) {
+        let mut connected_sides = sides_to_sprite_name(&self.connected_sides, "", ", ");
+        if connected_sides.is_empty() {
+            connected_sides = String::from("None");
+        }
+
+        ui.horizontal(|ui| {
+            ui.label("Connected sides: ");
+            ui.label(RichText::new(connected_sides).code());
+        });
+    }
+}
+
+impl WireCrossover {
+    pub const CONST_PORTS: EnumMap<WireCrossoverPorts, ([usize; 2], Side)> = EnumMap::from_array([]);
+
+    pub fn sprite_name(sides: &EnumMap<Side, bool>) -> String {
+        sides_to_sprite_name(sides, "crossover_", "_")
+    }
+}
+
+/// Crossover connections are managed alongside wires
+#[derive(Debug, Enum)]
+pub enum WireCrossoverPorts {}
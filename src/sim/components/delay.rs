@@ -0,0 +1,110 @@
+use crate::sim::{
+    helpers::{self, Side},
+    model::{AudioEvent, GridComponent, SimulationData, VisualEvent, ComponentPortData, DummyComponent}, interactions::UpdateComponentEvent,
+};
+use bevy::{
+    reflect::{FromReflect, Reflect},
+    sprite::{TextureAtlas, TextureAtlasSprite}, prelude::{World, EventWriter},
+};
+use egui::RichText;
+use enum_map::{Enum, EnumMap};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use crate::sim::net::Net;
+
+/// The longest delay which can be set in the menu
+pub const MAX_DELAY: usize = 64;
+
+/// Outputs its input `delay` ticks later.\
+/// Backed by a ring buffer which is cleared to 0 whenever the simulation is built.
+#[derive(Debug, Serialize, Deserialize, Clone, Reflect, FromReflect)]
+#[serde(default)]
+pub struct Delay {
+    delay: usize,
+    #[serde(skip)]
+    buffer: Vec<u8>,
+    #[serde(skip)]
+    head: usize,
+    #[reflect(ignore)]
+    #[serde(skip)]
+    ports: EnumMap<DelayPorts, ComponentPortData>,
+}
+
+impl Default for Delay {
+    fn default() -> Self {
+        Self {
+            delay: 1,
+            buffer: Vec::new(),
+            head: 0,
+            ports: EnumMap::default(),
+        }
+    }
+}
+
+impl GridComponent for Delay {
+    fn tick(&mut self, _: [usize; 2], _: usize, _: &mut World) -> (Vec<VisualEvent>, Vec<AudioEvent>) {
+        let input = self.ports[DelayPorts::Input].get();
+        // The oldest value is at the head, swap it out for the newest
+        let output = std::mem::replace(&mut self.buffer[self.head], input);
+        self.head = (self.head + 1) % self.buffer.len();
+        self.ports[DelayPorts::Output].set(output);
+        (Vec::new(), Vec::new())
+    }
+
+    fn build(&mut self) {
+        self.buffer = vec![0; self.delay.clamp(1, MAX_DELAY)];
+        self.head = 0;
+    }
+
+    fn on_place(
+        &mut self,
+        _own_pos: &[usize; 2],
+        _sim_data: &SimulationData,
+        _sprite: &mut TextureAtlasSprite,
+        _atlas: &TextureAtlas,
+    ) {
+    }
+
+    fn ports(&self) -> Vec<&([usize; 2], Side)> {
+        return Self::CONST_PORTS.values().collect();
+    }
+
+    fn set_port(&mut self, offset: [usize; 2], side: Side, set_to: Arc<Net>) -> Result<(),()> {
+        for (varient, (self_offset, self_side)) in Self::CONST_PORTS.iter() {
+            if offset == *self_offset && side == *self_side {
+                self.ports[varient].set_link(Some(set_to));
+                return Ok(());
+            }
+        }
+        Err(())
+    }
+
+    fn gui_options(&mut self, ui: &mut egui::Ui, sim_halted: bool, _: DummyComponent, _: &[usize; 2], _: &mut EventWriter<UpdateComponentEvent>) {
+        ui.add_enabled_ui(sim_halted, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Delay (ticks): ");
+                ui.add(egui::DragValue::new(&mut self.delay).clamp_range(1..=MAX_DELAY)).on_disabled_hover_text(helpers::UI_DISABLED_MSG);
+            });
+        });
+
+        if !sim_halted && !self.buffer.is_empty() {
+            // Oldest first, the same order they will be output in
+            let buffered: Vec<String> = (0..self.buffer.len())
+                .map(|i| self.buffer[(self.head + i) % self.buffer.len()].to_string())
+                .collect();
+            ui.label("Buffered values:");
+            ui.label(RichText::new(buffered.join(", ")).code());
+        }
+    }
+}
+
+impl Delay {
+    pub const CONST_PORTS: EnumMap<DelayPorts, ([usize; 2], Side)> =
+        EnumMap::from_array([([0, 0], Side::Left), ([0, 0], Side::Right)]);
+}
+
+#[derive(Debug, Enum)]
+pub enum DelayPorts {
+    Input,
+    Output,
+}
@@ -0,0 +1,87 @@
+use crate::sim::{
+    helpers::{self, Side},
+    model::{AudioEvent, GridComponent, SimulationData, VisualEvent, ComponentPortData, DummyComponent}, interactions::UpdateComponentEvent,
+};
+use bevy::{
+    reflect::{FromReflect, Reflect},
+    sprite::{TextureAtlas, TextureAtlasSprite}, prelude::{World, EventWriter},
+};
+use egui::RichText;
+use enum_map::{Enum, EnumMap};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use crate::sim::net::Net;
+
+/// The amount of brightness sprites, named `led_0` to `led_7`
+const LED_LEVELS: u8 = 8;
+
+/// A light which glows brighter the higher its input is
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Reflect, FromReflect)]
+pub struct Led {
+    /// The brightness level currently shown by the sprite, [None] if it needs redrawing
+    #[reflect(ignore)]
+    #[serde(skip)]
+    shown_level: Option<u8>,
+    #[reflect(ignore)]
+    #[serde(skip)]
+    ports: EnumMap<LedPorts, ComponentPortData>,
+}
+
+impl GridComponent for Led {
+    fn tick(&mut self, own_pos: [usize; 2], _: usize, world: &mut World) -> (Vec<VisualEvent>, Vec<AudioEvent>) {
+        let input = self.ports[LedPorts::Input].get();
+        let level = input / (u8::MAX / LED_LEVELS + 1);
+        if self.shown_level != Some(level) {
+            self.shown_level = Some(level);
+            helpers::set_sprite_from_world(world, &own_pos, &format!("led_{}", level));
+        }
+        (Vec::new(), Vec::new())
+    }
+
+    fn build(&mut self) {
+        self.shown_level = None;
+    }
+
+    fn on_place(
+        &mut self,
+        _own_pos: &[usize; 2],
+        _sim_data: &SimulationData,
+        _sprite: &mut TextureAtlasSprite,
+        _atlas: &TextureAtlas,
+    ) {
+    }
+
+    fn ports(&self) -> Vec<&([usize; 2], Side)> {
+        return Self::CONST_PORTS.values().collect();
+    }
+
+    fn set_port(&mut self, offset: [usize; 2], side: Side, set_to: Arc<Net>) -> Result<(),()> {
+        for (varient, (self_offset, self_side)) in Self::CONST_PORTS.iter() {
+            if offset == *self_offset && side == *self_side {
+                self.ports[varient].set_link(Some(set_to));
+                return Ok(());
+            }
+        }
+        Err(())
+    }
+
+    fn gui_options(&mut self, ui: &mut egui::Ui, _: bool, _: DummyComponent, _: &[usize; 2], _: &mut EventWriter<UpdateComponentEvent>) {
+        let brightness = match self.shown_level {
+            Some(level) => format!("{}/{}", level, LED_LEVELS - 1),
+            None => String::from("-"),
+        };
+        ui.horizontal(|ui| {
+            ui.label("Brightness: ");
+            ui.label(RichText::new(brightness).code());
+        });
+    }
+}
+
+impl Led {
+    pub const CONST_PORTS: EnumMap<LedPorts, ([usize; 2], Side)> = EnumMap::from_array([([0, 0], Side::Left)]);
+}
+
+#[derive(Debug, Enum)]
+pub enum LedPorts {
+    Input,
+}
@@ -0,0 +1,106 @@
+use crate::sim::{
+    helpers::{self, Side},
+    model::{AudioEvent, GridComponent, SimulationData, VisualEvent, ComponentPortData, DummyComponent}, interactions::UpdateComponentEvent,
+};
+use bevy::{
+    reflect::{FromReflect, Reflect},
+    sprite::{TextureAtlas, TextureAtlasSprite}, prelude::{World, EventWriter, Handle},
+};
+use enum_map::{Enum, EnumMap};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use crate::sim::net::Net;
+
+/// Outputs either the smaller or larger of its two inputs
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Reflect, FromReflect)]
+pub struct MinMax {
+    #[reflect(ignore)]
+    #[serde(skip)]
+    ports: EnumMap<MinMaxPorts, ComponentPortData>,
+    #[serde(default)]
+    mode: MinMaxMode,
+}
+
+impl GridComponent for MinMax {
+    fn tick(&mut self, _: [usize; 2], _: usize, _: &mut World) -> (Vec<VisualEvent>, Vec<AudioEvent>) {
+        let input_a = self.ports[MinMaxPorts::InputA].get();
+        let input_b = self.ports[MinMaxPorts::InputB].get();
+        let output = match self.mode {
+            MinMaxMode::Min => input_a.min(input_b),
+            MinMaxMode::Max => input_a.max(input_b),
+        };
+        self.ports[MinMaxPorts::Output].set(output);
+        (Vec::new(), Vec::new())
+    }
+
+    fn build(&mut self) {}
+
+    fn on_place(
+        &mut self,
+        _own_pos: &[usize; 2],
+        _sim_data: &SimulationData,
+        sprite: &mut TextureAtlasSprite,
+        atlas: &TextureAtlas,
+    ) {
+        if let Some(index) = atlas.get_texture_index(&Handle::weak(self.sprite_name().into())) {
+            sprite.index = index;
+        }
+    }
+
+    fn ports(&self) -> Vec<&([usize; 2], Side)> {
+        return Self::CONST_PORTS.values().collect();
+    }
+
+    fn set_port(&mut self, offset: [usize; 2], side: Side, set_to: Arc<Net>) -> Result<(),()> {
+        for (varient, (self_offset, self_side)) in Self::CONST_PORTS.iter() {
+            if offset == *self_offset && side == *self_side {
+                self.ports[varient].set_link(Some(set_to));
+                return Ok(());
+            }
+        }
+        Err(())
+    }
+
+    fn gui_options(&mut self, ui: &mut egui::Ui, sim_halted: bool, _: DummyComponent, pos: &[usize; 2], updates: &mut EventWriter<UpdateComponentEvent>) {
+        let before = self.mode;
+        ui.add_enabled_ui(sim_halted, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Output the: ");
+                ui.selectable_value(&mut self.mode, MinMaxMode::Min, "Smallest").on_disabled_hover_text(helpers::UI_DISABLED_MSG);
+                ui.selectable_value(&mut self.mode, MinMaxMode::Max, "Largest").on_disabled_hover_text(helpers::UI_DISABLED_MSG);
+            });
+        });
+        if before != self.mode {
+            updates.send(UpdateComponentEvent{pos: *pos, surround: false});
+        }
+    }
+}
+
+impl MinMax {
+    pub const CONST_PORTS: EnumMap<MinMaxPorts, ([usize; 2], Side)> = EnumMap::from_array([
+        ([0, 1], Side::Left),
+        ([0, 0], Side::Left),
+        ([0, 0], Side::Right),
+    ]);
+
+    pub fn sprite_name(&self) -> &'static str {
+        match self.mode {
+            MinMaxMode::Min => "min_max_min",
+            MinMaxMode::Max => "min_max_max",
+        }
+    }
+}
+
+#[derive(Debug, Enum)]
+pub enum MinMaxPorts {
+    InputA,
+    InputB,
+    Output,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Reflect, FromReflect)]
+pub enum MinMaxMode {
+    #[default]
+    Min,
+    Max,
+}
@@ -0,0 +1,93 @@
+use crate::sim::{
+    helpers::{self, Side},
+    model::{AudioEvent, GridComponent, SimulationData, VisualEvent, ComponentPortData, DummyComponent}, interactions::UpdateComponentEvent,
+};
+use bevy::{
+    reflect::{FromReflect, Reflect},
+    sprite::{TextureAtlas, TextureAtlasSprite}, prelude::{World, EventWriter},
+};
+use enum_map::{Enum, EnumMap};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use crate::sim::net::Net;
+
+/// Blends two inputs together, with the default weighting of 50% this is the average of the two
+#[derive(Debug, Serialize, Deserialize, Clone, Reflect, FromReflect)]
+#[serde(default)]
+pub struct Mixer {
+    #[reflect(ignore)]
+    #[serde(skip)]
+    ports: EnumMap<MixerPorts, ComponentPortData>,
+    /// How much of input A makes up the output, 0 - 100%. The rest is input B.
+    weight_a: u8,
+}
+
+impl Default for Mixer {
+    fn default() -> Self {
+        Self {
+            ports: EnumMap::default(),
+            weight_a: 50,
+        }
+    }
+}
+
+impl GridComponent for Mixer {
+    fn tick(&mut self, _: [usize; 2], _: usize, _: &mut World) -> (Vec<VisualEvent>, Vec<AudioEvent>) {
+        let input_a = self.ports[MixerPorts::InputA].get() as u32;
+        let input_b = self.ports[MixerPorts::InputB].get() as u32;
+        let weight_a = self.weight_a.min(100) as u32;
+        // Rounded to the nearest, can never go above 255 as the weights add to 100
+        let mixed = (input_a * weight_a + input_b * (100 - weight_a) + 50) / 100;
+        self.ports[MixerPorts::Output].set(mixed as u8);
+        (Vec::new(), Vec::new())
+    }
+
+    fn build(&mut self) {}
+
+    fn on_place(
+        &mut self,
+        _own_pos: &[usize; 2],
+        _sim_data: &SimulationData,
+        _sprite: &mut TextureAtlasSprite,
+        _atlas: &TextureAtlas,
+    ) {
+    }
+
+    fn ports(&self) -> Vec<&([usize; 2], Side)> {
+        return Self::CONST_PORTS.values().collect();
+    }
+
+    fn set_port(&mut self, offset: [usize; 2], side: Side, set_to: Arc<Net>) -> Result<(),()> {
+        for (varient, (self_offset, self_side)) in Self::CONST_PORTS.iter() {
+            if offset == *self_offset && side == *self_side {
+                self.ports[varient].set_link(Some(set_to));
+                return Ok(());
+            }
+        }
+        Err(())
+    }
+
+    fn gui_options(&mut self, ui: &mut egui::Ui, sim_halted: bool, _: DummyComponent, _: &[usize; 2], _: &mut EventWriter<UpdateComponentEvent>) {
+        ui.add_enabled_ui(sim_halted, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Amount of A: ");
+                ui.add(egui::Slider::new(&mut self.weight_a, 0..=100).suffix("%")).on_disabled_hover_text(helpers::UI_DISABLED_MSG);
+            });
+        });
+    }
+}
+
+impl Mixer {
+    pub const CONST_PORTS: EnumMap<MixerPorts, ([usize; 2], Side)> = EnumMap::from_array([
+        ([0, 1], Side::Left),
+        ([0, 0], Side::Left),
+        ([0, 0], Side::Right),
+    ]);
+}
+
+#[derive(Debug, Enum)]
+pub enum MixerPorts {
+    InputA,
+    InputB,
+    Output,
+}
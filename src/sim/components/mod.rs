@@ -6,6 +6,30 @@ pub mod passthrough;
 pub mod wire;
 pub mod observer;
 pub mod provider;
+pub mod constant;
+pub mod switch;
+pub mod button;
+pub mod crossover;
+pub mod delay;
+pub mod led;
+pub mod seven_segment;
+pub mod pixel_matrix;
+pub mod schmitt_trigger;
+pub mod scaler;
+pub mod mixer;
+pub mod min_max;
+pub mod abs_difference;
+pub mod random;
+pub mod package;
+pub mod scripted;
+pub mod bit_shift;
+pub mod bit_mask;
+pub mod bit_extract;
+pub mod bit_pack;
+pub mod buffer;
+pub mod state_machine;
+pub mod tunnel;
+pub mod pull;
 
 pub use and::GateAnd;
 pub use copy::SignalCopy;
@@ -14,4 +38,28 @@ pub use not::GateNot;
 pub use passthrough::SignalPassthrough;
 pub use wire::Wire;
 pub use observer::Observer;
-pub use provider::Provider;
\ No newline at end of file
+pub use provider::Provider;
+pub use constant::Constant;
+pub use switch::ToggleSwitch;
+pub use button::PushButton;
+pub use crossover::WireCrossover;
+pub use delay::Delay;
+pub use led::Led;
+pub use seven_segment::SevenSegment;
+pub use pixel_matrix::PixelMatrix;
+pub use schmitt_trigger::SchmittTrigger;
+pub use scaler::Scaler;
+pub use mixer::Mixer;
+pub use min_max::MinMax;
+pub use abs_difference::AbsDifference;
+pub use random::RandomSource;
+pub use package::Package;
+pub use scripted::Scripted;
+pub use bit_shift::BitShift;
+pub use bit_mask::BitMask;
+pub use bit_extract::BitExtract;
+pub use bit_pack::BitPack;
+pub use buffer::{FifoQueue, LifoStack};
+pub use state_machine::StateMachine;
+pub use tunnel::{TunnelSend, TunnelReceive};
+pub use pull::{PullUp, PullDown};
\ No newline at end of file
@@ -8,7 +8,8 @@ use bevy::{
 };
 use enum_map::{Enum, EnumMap};
 use serde::{Deserialize, Serialize};
-use std::sync::{atomic::AtomicU8, Arc};
+use std::sync::Arc;
+use crate::sim::net::Net;
 
 /// Observes the inputted value and records it to the data.
 #[derive(Debug, Default, Serialize, Deserialize, Clone, Reflect, FromReflect)]
@@ -50,7 +51,7 @@ impl GridComponent for Observer {
         return Self::CONST_PORTS.values().collect();
     }
 
-    fn set_port(&mut self, offset: [usize; 2], side: Side, set_to: Arc<AtomicU8>) -> Result<(),()> {
+    fn set_port(&mut self, offset: [usize; 2], side: Side, set_to: Arc<Net>) -> Result<(),()> {
         for (varient, (self_offset, self_side)) in Self::CONST_PORTS.iter() {
             if offset == *self_offset && side == *self_side {
                 self.ports[varient].set_link(Some(set_to));
@@ -65,6 +66,14 @@ impl GridComponent for Observer {
 
 impl Observer {
     pub const CONST_PORTS: EnumMap<ObserverPorts, ([usize; 2], Side)> = EnumMap::from_array([([0, 0], Side::Left)]);
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn set_id(&mut self, id: String) {
+        self.id = id;
+    }
 }
 
 #[derive(Debug, Enum)]
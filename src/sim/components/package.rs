@@ -0,0 +1,251 @@
+use crate::sim::{
+    helpers::Side,
+    model::{AudioEvent, CellState, GridComponent, SimulationData, VisualEvent, DummyComponent},
+    interactions::UpdateComponentEvent,
+    packages::PackageDefinition,
+    port_grid::PortGrid,
+    net::{Net, NetResolution},
+    run::{self, NetConflict},
+};
+use bevy::{
+    math::Vec2,
+    reflect::{FromReflect, Reflect},
+    sprite::{TextureAtlas, TextureAtlasSprite}, prelude::{World, EventWriter, Handle},
+};
+use egui::RichText;
+use enum_map::{Enum, EnumMap};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// A circuit from the [crate::sim::packages::PackageLibrary] placed as a single component.\
+/// Only the name is saved, the circuit inside is looked up again when loading.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Reflect, FromReflect)]
+pub struct Package {
+    name: String,
+    #[reflect(ignore)]
+    #[serde(skip)]
+    definition: Option<Arc<PackageDefinition>>,
+    #[reflect(ignore)]
+    #[serde(skip)]
+    pin_ports: Vec<([usize; 2], Side)>,
+    /// Made fresh from the definition every build
+    #[reflect(ignore)]
+    #[serde(skip)]
+    inner: Option<PackageCircuit>,
+    /// Set by [run::build_nets] so the nets inside resolve the same way as the circuit around the package
+    #[reflect(ignore)]
+    #[serde(skip)]
+    resolution: NetResolution,
+}
+
+#[derive(Debug, Default, Clone)]
+struct PackageCircuit {
+    grid: Vec<Vec<CellState>>,
+    port_grid: PortGrid,
+    /// Only the nets entirely inside, those reaching a pin belong to the circuit outside
+    nets: Vec<Arc<Net>>,
+}
+
+impl GridComponent for Package {
+    fn tick(&mut self, own_pos: [usize; 2], tick_num: usize, world: &mut World) -> (Vec<VisualEvent>, Vec<AudioEvent>) {
+        let circuit = match self.inner.as_mut() {
+            Some(c) => c,
+            None => return (Vec::new(), Vec::new()),
+        };
+        for net in &circuit.nets {
+            net.start_tick();
+        }
+        for x in 0..circuit.grid.len() {
+            for y in 0..circuit.grid[x].len() {
+                if let CellState::Real(_, component, _) = &mut circuit.grid[x][y] {
+                    // Components inside have no sprite of their own, give them a position nothing on the board can have
+                    component.tick([usize::MAX - x, usize::MAX - y], tick_num, world);
+                }
+            }
+        }
+        // Positions inside mean nothing on the board so point at the package instead
+        if circuit.nets.iter().filter_map(|net| net.take_conflict()).count() > 0 {
+            if let Some(mut conflict) = world.get_resource_mut::<NetConflict>() {
+                conflict.0.get_or_insert(own_pos);
+            }
+        }
+        (Vec::new(), Vec::new())
+    }
+
+    fn build(&mut self) {
+        self.inner = self.definition.as_ref().map(|definition| {
+            let (mut grid, mut port_grid) = definition.instantiate();
+            let nets = run::build_nets(&mut grid, &mut port_grid, self.resolution);
+            PackageCircuit { grid, port_grid, nets }
+        });
+    }
+
+    fn on_place(
+        &mut self,
+        _own_pos: &[usize; 2],
+        _sim_data: &SimulationData,
+        sprite: &mut TextureAtlasSprite,
+        atlas: &TextureAtlas,
+    ) {
+        self.set_sprite(sprite, atlas);
+    }
+
+    fn ports(&self) -> Vec<&([usize; 2], Side)> {
+        return self.pin_ports.iter().collect();
+    }
+
+    fn set_port(&mut self, offset: [usize; 2], side: Side, set_to: Arc<Net>) -> Result<(),()> {
+        let definition = self.definition.as_ref().ok_or(())?;
+        let pin = definition.pins.iter().find(|p| p.offset == offset && p.side == side).ok_or(())?;
+        if let Some(circuit) = self.inner.as_mut() {
+            // Join the net outside onto whatever the pin leads to inside
+            let mut has_propagated = vec![vec![[false; 2]; circuit.grid[0].len()]; circuit.grid.len()];
+            run::flood_fill(&mut circuit.grid, &mut circuit.port_grid, set_to, pin.inner_pos, pin.inner_side, &mut has_propagated);
+        }
+        Ok(())
+    }
+
+    fn gui_options(&mut self, ui: &mut egui::Ui, _: bool, _: DummyComponent, _: &[usize; 2], _: &mut EventWriter<UpdateComponentEvent>) {
+        ui.label(RichText::new(&self.name).strong());
+        match &self.definition {
+            Some(definition) => {
+                if !definition.desc.is_empty() {
+                    ui.label(&definition.desc);
+                }
+                ui.label("Pins:");
+                for pin in &definition.pins {
+                    ui.label(format!("{} ({})", pin.name, pin.side.as_str()));
+                }
+            },
+            None => {
+                ui.label(RichText::new("This package is missing from the library so does nothing").color(egui::Color32::RED));
+            },
+        }
+    }
+}
+
+impl Package {
+    /// Pins depend on which package it is so come from [GridComponent::ports] instead
+    pub const CONST_PORTS: EnumMap<PackagePorts, ([usize; 2], Side)> = EnumMap::from_array([]);
+
+    pub fn new(definition: Arc<PackageDefinition>) -> Self {
+        let mut package = Package {
+            name: definition.name.clone(),
+            ..Default::default()
+        };
+        package.set_definition(Some(definition));
+        package
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn set_definition(&mut self, definition: Option<Arc<PackageDefinition>>) {
+        self.pin_ports = match &definition {
+            Some(d) => d.pins.iter().map(|p| (p.offset, p.side)).collect(),
+            None => Vec::new(),
+        };
+        self.definition = definition;
+        self.inner = None;
+    }
+
+    pub fn set_resolution(&mut self, resolution: NetResolution) {
+        self.resolution = resolution;
+    }
+
+    /// Unlike other components the size depends on which package it is
+    pub fn grid_size(&self) -> [usize; 2] {
+        self.definition.as_ref().map(|d| d.size).unwrap_or([1, 1])
+    }
+
+    pub fn size(&self) -> Vec2 {
+        let size = self.grid_size();
+        Vec2::new(size[0] as f32 * 32.0, size[1] as f32 * 32.0)
+    }
+
+    pub fn set_sprite(&self, sprite: &mut TextureAtlasSprite, atlas: &TextureAtlas) {
+        let size = self.grid_size();
+        let sprite_name = format!("package_{}x{}", size[0], size[1]);
+        if let Some(index) = atlas.get_texture_index(&Handle::weak(sprite_name.as_str().into())) {
+            sprite.index = index;
+        }
+    }
+}
+
+#[derive(Debug, Enum)]
+pub enum PackagePorts {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::{
+        components::{Constant, Wire},
+        helpers::Orientation,
+        model::{Component, ComponentGrid},
+    };
+    use bevy::prelude::Entity;
+
+    fn constant(value: u8) -> CellState {
+        let constant: Constant = serde_json::from_value(serde_json::json!({"value": value})).expect("Constants can be loaded");
+        CellState::Real(Entity::PLACEHOLDER, Component::Constant(constant), Orientation::default())
+    }
+
+    fn wire(sides: [Side; 2]) -> CellState {
+        let mut wire = Wire::default();
+        for side in sides {
+            wire.connected_sides[side] = true;
+        }
+        CellState::Real(Entity::PLACEHOLDER, Component::WirePiece(wire), Orientation::default())
+    }
+
+    /// Two constants inside a package driving 10 and 200 onto the same wire
+    fn two_drivers() -> Package {
+        let definition = PackageDefinition {
+            name: "Two drivers".to_string(),
+            desc: String::new(),
+            size: [1, 1],
+            pins: Vec::new(),
+            grid: ComponentGrid {
+                grid: vec![
+                    vec![constant(10), constant(200)],
+                    vec![wire([Side::Left, Side::Up]), wire([Side::Left, Side::Down])],
+                ],
+                locked: Vec::new(),
+            },
+        };
+        Package::new(Arc::new(definition))
+    }
+
+    /// Build a board holding just the package with `resolution` then tick it once
+    fn tick_on_board(resolution: NetResolution) -> (Package, World) {
+        let mut grid = vec![vec![CellState::Real(Entity::PLACEHOLDER, Component::Package(two_drivers()), Orientation::default())]];
+        let mut port_grid = PortGrid::new_with_size(1, 1);
+        run::build_nets(&mut grid, &mut port_grid, resolution);
+        let mut package = match grid.remove(0).remove(0) {
+            CellState::Real(_, Component::Package(package), _) => package,
+            _ => unreachable!(),
+        };
+
+        let mut world = World::new();
+        world.init_resource::<NetConflict>();
+        package.tick([0, 0], 0, &mut world);
+        (package, world)
+    }
+
+    #[test]
+    fn inner_nets_use_the_board_resolution() {
+        let (package, world) = tick_on_board(NetResolution::WiredAnd);
+        let nets = &package.inner.as_ref().expect("Package was built").nets;
+        assert_eq!(nets.len(), 1);
+        assert_eq!(nets[0].resolution(), NetResolution::WiredAnd);
+        assert_eq!(nets[0].load(), 10);
+        assert_eq!(world.resource::<NetConflict>().0, None);
+    }
+
+    #[test]
+    fn conflict_inside_points_at_the_package() {
+        let (_, world) = tick_on_board(NetResolution::Error);
+        assert_eq!(world.resource::<NetConflict>().0, Some([0, 0]));
+    }
+}
@@ -0,0 +1,103 @@
+use crate::sim::{
+    helpers::{self, Side},
+    model::{AudioEvent, GridComponent, SimulationData, VisualEvent, ComponentPortData, DummyComponent}, interactions::UpdateComponentEvent,
+};
+use bevy::{
+    reflect::{FromReflect, Reflect},
+    sprite::{TextureAtlas, TextureAtlasSprite}, prelude::{World, EventWriter, Vec2},
+};
+use enum_map::{Enum, EnumMap};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use crate::sim::net::Net;
+
+const MATRIX_SIZE: usize = 8;
+/// Offset of the top left pixel from the bottom left of the component
+const MATRIX_TOP_LEFT: Vec2 = Vec2::new(9.0, 51.0);
+/// Distance between the bottom left of each pixel
+const PIXEL_SPACING: f32 = 6.0;
+
+/// An 8x8 display, each tick the pixel at `address` is turned on if `data` is above 0, otherwise off.\
+/// Addresses count left to right then top to bottom, only the lowest 6 bits are used.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Reflect, FromReflect)]
+pub struct PixelMatrix {
+    /// One bit per pixel, bit 0 is the top left
+    #[reflect(ignore)]
+    #[serde(skip)]
+    pixels: u64,
+    /// The pixels currently shown, [None] if they need redrawing
+    #[reflect(ignore)]
+    #[serde(skip)]
+    shown: Option<u64>,
+    #[reflect(ignore)]
+    #[serde(skip)]
+    ports: EnumMap<PixelMatrixPorts, ComponentPortData>,
+}
+
+impl GridComponent for PixelMatrix {
+    fn tick(&mut self, own_pos: [usize; 2], _: usize, world: &mut World) -> (Vec<VisualEvent>, Vec<AudioEvent>) {
+        let address = self.ports[PixelMatrixPorts::Address].get() as usize % (MATRIX_SIZE * MATRIX_SIZE);
+        let data = self.ports[PixelMatrixPorts::Data].get();
+        if data > 0 {
+            self.pixels |= 1 << address;
+        } else {
+            self.pixels &= !(1 << address);
+        }
+
+        if self.shown != Some(self.pixels) {
+            self.shown = Some(self.pixels);
+            let overlays: Vec<(Vec2, &str)> = (0..MATRIX_SIZE * MATRIX_SIZE).map(|i| {
+                let offset = MATRIX_TOP_LEFT + Vec2::new((i % MATRIX_SIZE) as f32, -((i / MATRIX_SIZE) as f32)) * PIXEL_SPACING;
+                (offset, if self.pixels & (1 << i) != 0 {"matrix_pixel_on"} else {"matrix_pixel_off"})
+            }).collect();
+            helpers::set_overlay_sprites(world, &own_pos, &overlays);
+        }
+        (Vec::new(), Vec::new())
+    }
+
+    fn build(&mut self) {
+        self.pixels = 0;
+        self.shown = None;
+    }
+
+    fn on_place(
+        &mut self,
+        _own_pos: &[usize; 2],
+        _sim_data: &SimulationData,
+        _sprite: &mut TextureAtlasSprite,
+        _atlas: &TextureAtlas,
+    ) {
+    }
+
+    fn ports(&self) -> Vec<&([usize; 2], Side)> {
+        return Self::CONST_PORTS.values().collect();
+    }
+
+    fn set_port(&mut self, offset: [usize; 2], side: Side, set_to: Arc<Net>) -> Result<(),()> {
+        for (varient, (self_offset, self_side)) in Self::CONST_PORTS.iter() {
+            if offset == *self_offset && side == *self_side {
+                self.ports[varient].set_link(Some(set_to));
+                return Ok(());
+            }
+        }
+        Err(())
+    }
+
+    fn gui_options(&mut self, ui: &mut egui::Ui, _: bool, _: DummyComponent, _: &[usize; 2], _: &mut EventWriter<UpdateComponentEvent>) {
+        ui.label("Top port: address (y * 8 + x)");
+        ui.label("Bottom port: data, above 0 turns the pixel on");
+    }
+}
+
+impl PixelMatrix {
+    pub const CONST_PORTS: EnumMap<PixelMatrixPorts, ([usize; 2], Side)> = EnumMap::from_array([
+        ([0, 1], Side::Left),
+        ([0, 0], Side::Left),
+    ]);
+}
+
+#[derive(Debug, Enum)]
+pub enum PixelMatrixPorts {
+    Address,
+    Data,
+}
@@ -8,7 +8,8 @@ use bevy::{
 };
 use enum_map::{Enum, EnumMap};
 use serde::{Deserialize, Serialize};
-use std::sync::{atomic::AtomicU8, Arc};
+use std::sync::Arc;
+use crate::sim::net::Net;
 
 /// Observes the inputted value and records it to the data.
 #[derive(Debug, Default, Serialize, Deserialize, Clone, Reflect, FromReflect)]
@@ -24,7 +25,8 @@ impl GridComponent for Provider {
         let input = self.ports[ProviderPorts::Output].get();
         // Use SystemState instead of world.get_resource_mut() due to needing two mutable
         let level_data = world.get_resource::<LevelData>().unwrap();
-        let value = level_data.provided_inputs.get(self.id.as_str()).unwrap().get(tick);
+        // A level being made in the editor may not have inputs for this id yet
+        let value = level_data.provided_inputs.get(self.id.as_str()).and_then(|inputs| inputs.get(tick));
         if let Some(num) = value {
             self.ports[ProviderPorts::Output].set(*num);
         }
@@ -47,7 +49,7 @@ impl GridComponent for Provider {
         return Self::CONST_PORTS.values().collect();
     }
 
-    fn set_port(&mut self, offset: [usize; 2], side: Side, set_to: Arc<AtomicU8>) -> Result<(),()> {
+    fn set_port(&mut self, offset: [usize; 2], side: Side, set_to: Arc<Net>) -> Result<(),()> {
         for (varient, (self_offset, self_side)) in Self::CONST_PORTS.iter() {
             if offset == *self_offset && side == *self_side {
                 self.ports[varient].set_link(Some(set_to));
@@ -62,6 +64,14 @@ impl GridComponent for Provider {
 
 impl Provider {
     pub const CONST_PORTS: EnumMap<ProviderPorts, ([usize; 2], Side)> = EnumMap::from_array([([0, 0], Side::Right)]);
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn set_id(&mut self, id: String) {
+        self.id = id;
+    }
 }
 
 #[derive(Debug, Enum)]
@@ -0,0 +1,125 @@
+use crate::sim::{
+    helpers::Side,
+    model::{AudioEvent, GridComponent, SimulationData, VisualEvent, ComponentPortData, DummyComponent}, interactions::UpdateComponentEvent,
+    net::Net,
+};
+use bevy::{
+    reflect::{FromReflect, Reflect},
+    sprite::{TextureAtlas, TextureAtlasSprite}, prelude::{World, EventWriter},
+};
+use egui::RichText;
+use enum_map::{Enum, EnumMap};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Weakly holds its net HIGH, any component writing to the net overrides it.\
+/// Does nothing when ticked, the weak value is given to the net when it is built, see [Net::pull].
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Reflect, FromReflect)]
+pub struct PullUp {
+    #[reflect(ignore)]
+    #[serde(skip)]
+    ports: EnumMap<PullPorts, ComponentPortData>,
+}
+
+/// Weakly holds its net LOW, any component writing to the net or a [PullUp] on it overrides it.\
+/// Does nothing when ticked, the weak value is given to the net when it is built, see [Net::pull].
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Reflect, FromReflect)]
+pub struct PullDown {
+    #[reflect(ignore)]
+    #[serde(skip)]
+    ports: EnumMap<PullPorts, ComponentPortData>,
+}
+
+impl GridComponent for PullUp {
+    fn tick(&mut self, _: [usize; 2], _: usize, _: &mut World) -> (Vec<VisualEvent>, Vec<AudioEvent>) {
+        (Vec::new(), Vec::new())
+    }
+
+    fn build(&mut self) {}
+
+    fn on_place(
+        &mut self,
+        _own_pos: &[usize; 2],
+        _sim_data: &SimulationData,
+        _sprite: &mut TextureAtlasSprite,
+        _atlas: &TextureAtlas,
+    ) {
+    }
+
+    fn ports(&self) -> Vec<&([usize; 2], Side)> {
+        return PULL_PORTS.values().collect();
+    }
+
+    fn set_port(&mut self, offset: [usize; 2], side: Side, set_to: Arc<Net>) -> Result<(),()> {
+        set_pull_port(&mut self.ports, offset, side, set_to, 255)
+    }
+
+    fn gui_options(&mut self, ui: &mut egui::Ui, sim_halted: bool, _: DummyComponent, _: &[usize; 2], _: &mut EventWriter<UpdateComponentEvent>) {
+        net_value_options(ui, sim_halted, &self.ports);
+    }
+}
+
+impl GridComponent for PullDown {
+    fn tick(&mut self, _: [usize; 2], _: usize, _: &mut World) -> (Vec<VisualEvent>, Vec<AudioEvent>) {
+        (Vec::new(), Vec::new())
+    }
+
+    fn build(&mut self) {}
+
+    fn on_place(
+        &mut self,
+        _own_pos: &[usize; 2],
+        _sim_data: &SimulationData,
+        _sprite: &mut TextureAtlasSprite,
+        _atlas: &TextureAtlas,
+    ) {
+    }
+
+    fn ports(&self) -> Vec<&([usize; 2], Side)> {
+        return PULL_PORTS.values().collect();
+    }
+
+    fn set_port(&mut self, offset: [usize; 2], side: Side, set_to: Arc<Net>) -> Result<(),()> {
+        set_pull_port(&mut self.ports, offset, side, set_to, 0)
+    }
+
+    fn gui_options(&mut self, ui: &mut egui::Ui, sim_halted: bool, _: DummyComponent, _: &[usize; 2], _: &mut EventWriter<UpdateComponentEvent>) {
+        net_value_options(ui, sim_halted, &self.ports);
+    }
+}
+
+const PULL_PORTS: EnumMap<PullPorts, ([usize; 2], Side)> = EnumMap::from_array([([0, 0], Side::Right)]);
+
+impl PullUp {
+    pub const CONST_PORTS: EnumMap<PullPorts, ([usize; 2], Side)> = PULL_PORTS;
+}
+
+impl PullDown {
+    pub const CONST_PORTS: EnumMap<PullPorts, ([usize; 2], Side)> = PULL_PORTS;
+}
+
+fn set_pull_port(ports: &mut EnumMap<PullPorts, ComponentPortData>, offset: [usize; 2], side: Side, set_to: Arc<Net>, weak: u8) -> Result<(),()> {
+    for (varient, (self_offset, self_side)) in PULL_PORTS.iter() {
+        if offset == *self_offset && side == *self_side {
+            set_to.pull(weak);
+            ports[varient].set_link(Some(set_to));
+            return Ok(());
+        }
+    }
+    Err(())
+}
+
+fn net_value_options(ui: &mut egui::Ui, sim_halted: bool, ports: &EnumMap<PullPorts, ComponentPortData>) {
+    ui.label("Only sets the net when nothing else writes to it");
+    if !sim_halted {
+        ui.horizontal(|ui| {
+            ui.label("Net value: ");
+            ui.label(RichText::new(ports[PullPorts::Net].get().to_string()).code());
+        });
+    }
+}
+
+#[derive(Debug, Enum)]
+pub enum PullPorts {
+    Net,
+}
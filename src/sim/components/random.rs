@@ -0,0 +1,169 @@
+use crate::sim::{
+    generators::{random_between, seeded_rng},
+    helpers::{self, Side},
+    model::{AudioEvent, GridComponent, SimulationData, VisualEvent, ComponentPortData, DummyComponent}, interactions::UpdateComponentEvent, levels::LevelData,
+};
+use bevy::{
+    reflect::{FromReflect, Reflect},
+    sprite::{TextureAtlas, TextureAtlasSprite}, prelude::{World, EventWriter},
+};
+use egui::RichText;
+use enum_map::{Enum, EnumMap};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use crate::sim::net::Net;
+
+/// Outputs pseudo random values between `min` and `max` (inclusive), either every tick or each time the clock goes HIGH.\
+/// The generator is restarted from its seed every run so results are always the same.\
+/// The values come from [seeded_rng] so runs match across machines and versions of `rand`.
+#[derive(Debug, Serialize, Deserialize, Clone, Reflect, FromReflect)]
+#[serde(default)]
+pub struct RandomSource {
+    #[reflect(ignore)]
+    #[serde(skip)]
+    ports: EnumMap<RandomSourcePorts, ComponentPortData>,
+    seed: u64,
+    /// Use a seed made from the level name and position instead of `seed`
+    seed_from_level: bool,
+    mode: RandomMode,
+    min: u8,
+    max: u8,
+    /// Created on the first tick of a run, as the level seed needs access to the world
+    #[reflect(ignore)]
+    #[serde(skip)]
+    rng: Option<ChaCha8Rng>,
+    #[serde(skip)]
+    last_clock: u8,
+    #[serde(skip)]
+    value: u8,
+}
+
+impl Default for RandomSource {
+    fn default() -> Self {
+        Self {
+            ports: EnumMap::default(),
+            seed: 0,
+            seed_from_level: false,
+            mode: RandomMode::EveryTick,
+            min: 0,
+            max: 255,
+            rng: None,
+            last_clock: 0,
+            value: 0,
+        }
+    }
+}
+
+impl GridComponent for RandomSource {
+    fn tick(&mut self, own_pos: [usize; 2], _: usize, world: &mut World) -> (Vec<VisualEvent>, Vec<AudioEvent>) {
+        if self.rng.is_none() {
+            let seed = match self.seed_from_level {
+                true => level_seed(world.get_resource::<LevelData>().map(|l| l.name.as_str()).unwrap_or(""), own_pos),
+                false => self.seed,
+            };
+            self.rng = Some(seeded_rng(seed));
+        }
+
+        let clock = self.ports[RandomSourcePorts::Clock].get();
+        let step = match self.mode {
+            RandomMode::EveryTick => true,
+            RandomMode::ClockEdge => clock >= 255 && self.last_clock < 255,
+        };
+        self.last_clock = clock;
+
+        if step {
+            self.value = random_between(self.rng.as_mut().unwrap(), self.min, self.max);
+        }
+        self.ports[RandomSourcePorts::Output].set(self.value);
+        (Vec::new(), Vec::new())
+    }
+
+    fn build(&mut self) {
+        self.rng = None;
+        self.last_clock = 0;
+        self.value = 0;
+    }
+
+    fn on_place(
+        &mut self,
+        _own_pos: &[usize; 2],
+        _sim_data: &SimulationData,
+        _sprite: &mut TextureAtlasSprite,
+        _atlas: &TextureAtlas,
+    ) {
+    }
+
+    fn ports(&self) -> Vec<&([usize; 2], Side)> {
+        return Self::CONST_PORTS.values().collect();
+    }
+
+    fn set_port(&mut self, offset: [usize; 2], side: Side, set_to: Arc<Net>) -> Result<(),()> {
+        for (varient, (self_offset, self_side)) in Self::CONST_PORTS.iter() {
+            if offset == *self_offset && side == *self_side {
+                self.ports[varient].set_link(Some(set_to));
+                return Ok(());
+            }
+        }
+        Err(())
+    }
+
+    fn gui_options(&mut self, ui: &mut egui::Ui, sim_halted: bool, _: DummyComponent, _: &[usize; 2], _: &mut EventWriter<UpdateComponentEvent>) {
+        ui.add_enabled_ui(sim_halted, |ui| {
+            ui.checkbox(&mut self.seed_from_level, "Seed from level").on_disabled_hover_text(helpers::UI_DISABLED_MSG);
+            ui.add_enabled_ui(!self.seed_from_level, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Seed: ");
+                    ui.add(egui::DragValue::new(&mut self.seed)).on_disabled_hover_text(helpers::UI_DISABLED_MSG);
+                });
+            });
+            ui.horizontal(|ui| {
+                ui.label("New value: ");
+                ui.selectable_value(&mut self.mode, RandomMode::EveryTick, "Every tick");
+                ui.selectable_value(&mut self.mode, RandomMode::ClockEdge, "On clock");
+            });
+            ui.horizontal(|ui| {
+                ui.label("Min: ");
+                ui.add(egui::DragValue::new(&mut self.min)).on_disabled_hover_text(helpers::UI_DISABLED_MSG);
+                ui.label("Max: ");
+                ui.add(egui::DragValue::new(&mut self.max)).on_disabled_hover_text(helpers::UI_DISABLED_MSG);
+            });
+        });
+
+        if !sim_halted {
+            ui.horizontal(|ui| {
+                ui.label("Current value: ");
+                ui.label(RichText::new(self.value.to_string()).code());
+            });
+        }
+    }
+}
+
+impl RandomSource {
+    pub const CONST_PORTS: EnumMap<RandomSourcePorts, ([usize; 2], Side)> =
+        EnumMap::from_array([([0, 0], Side::Left), ([0, 0], Side::Right)]);
+}
+
+/// FNV-1a of the level name and position. Written out by hand as the std hashers are not promised to stay the same between versions.
+fn level_seed(level_name: &str, pos: [usize; 2]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    let pos_bytes = [(pos[0] as u64).to_le_bytes(), (pos[1] as u64).to_le_bytes()].concat();
+    for byte in level_name.bytes().chain(pos_bytes) {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+#[derive(Debug, Enum)]
+pub enum RandomSourcePorts {
+    Clock,
+    Output,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Reflect, FromReflect)]
+pub enum RandomMode {
+    #[default]
+    EveryTick,
+    ClockEdge,
+}
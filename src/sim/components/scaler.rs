@@ -0,0 +1,89 @@
+use crate::sim::{
+    helpers::{self, Side},
+    model::{AudioEvent, GridComponent, SimulationData, VisualEvent, ComponentPortData, DummyComponent}, interactions::UpdateComponentEvent,
+};
+use bevy::{
+    reflect::{FromReflect, Reflect},
+    sprite::{TextureAtlas, TextureAtlasSprite}, prelude::{World, EventWriter},
+};
+use enum_map::{Enum, EnumMap};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use crate::sim::net::Net;
+
+const MAX_FACTOR: f32 = 8.0;
+
+/// Multiplies the input by a fixed factor, rounding to the nearest whole value and clamping to 0 - 255
+#[derive(Debug, Serialize, Deserialize, Clone, Reflect, FromReflect)]
+#[serde(default)]
+pub struct Scaler {
+    #[reflect(ignore)]
+    #[serde(skip)]
+    ports: EnumMap<ScalerPorts, ComponentPortData>,
+    factor: f32,
+}
+
+impl Default for Scaler {
+    fn default() -> Self {
+        Self {
+            ports: EnumMap::default(),
+            factor: 1.0,
+        }
+    }
+}
+
+impl GridComponent for Scaler {
+    fn tick(&mut self, _: [usize; 2], _: usize, _: &mut World) -> (Vec<VisualEvent>, Vec<AudioEvent>) {
+        let input = self.ports[ScalerPorts::Input].get();
+        // `as` saturates when going from float to int
+        self.ports[ScalerPorts::Output].set((input as f32 * self.factor).round() as u8);
+        (Vec::new(), Vec::new())
+    }
+
+    fn build(&mut self) {
+        self.factor = self.factor.clamp(0.0, MAX_FACTOR);
+    }
+
+    fn on_place(
+        &mut self,
+        _own_pos: &[usize; 2],
+        _sim_data: &SimulationData,
+        _sprite: &mut TextureAtlasSprite,
+        _atlas: &TextureAtlas,
+    ) {
+    }
+
+    fn ports(&self) -> Vec<&([usize; 2], Side)> {
+        return Self::CONST_PORTS.values().collect();
+    }
+
+    fn set_port(&mut self, offset: [usize; 2], side: Side, set_to: Arc<Net>) -> Result<(),()> {
+        for (varient, (self_offset, self_side)) in Self::CONST_PORTS.iter() {
+            if offset == *self_offset && side == *self_side {
+                self.ports[varient].set_link(Some(set_to));
+                return Ok(());
+            }
+        }
+        Err(())
+    }
+
+    fn gui_options(&mut self, ui: &mut egui::Ui, sim_halted: bool, _: DummyComponent, _: &[usize; 2], _: &mut EventWriter<UpdateComponentEvent>) {
+        ui.add_enabled_ui(sim_halted, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Factor: ");
+                ui.add(egui::DragValue::new(&mut self.factor).speed(0.05).clamp_range(0.0..=MAX_FACTOR)).on_disabled_hover_text(helpers::UI_DISABLED_MSG);
+            });
+        });
+    }
+}
+
+impl Scaler {
+    pub const CONST_PORTS: EnumMap<ScalerPorts, ([usize; 2], Side)> =
+        EnumMap::from_array([([0, 0], Side::Left), ([0, 0], Side::Right)]);
+}
+
+#[derive(Debug, Enum)]
+pub enum ScalerPorts {
+    Input,
+    Output,
+}
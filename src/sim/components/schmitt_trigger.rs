@@ -0,0 +1,112 @@
+use crate::sim::{
+    helpers::{self, Side},
+    model::{AudioEvent, GridComponent, SimulationData, VisualEvent, ComponentPortData, DummyComponent}, interactions::UpdateComponentEvent,
+};
+use bevy::{
+    reflect::{FromReflect, Reflect},
+    sprite::{TextureAtlas, TextureAtlasSprite}, prelude::{World, EventWriter},
+};
+use egui::RichText;
+use enum_map::{Enum, EnumMap};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use crate::sim::net::Net;
+
+/// Turns an analogue signal into a clean 0 or 255.\
+/// Switches on once the input reaches the upper threshold and only switches off again once it drops to the lower one.\
+/// With both thresholds equal it acts as a plain threshold.
+#[derive(Debug, Serialize, Deserialize, Clone, Reflect, FromReflect)]
+#[serde(default)]
+pub struct SchmittTrigger {
+    #[reflect(ignore)]
+    #[serde(skip)]
+    ports: EnumMap<SchmittTriggerPorts, ComponentPortData>,
+    low: u8,
+    high: u8,
+    #[serde(skip)]
+    on: bool,
+}
+
+impl Default for SchmittTrigger {
+    fn default() -> Self {
+        Self {
+            ports: EnumMap::default(),
+            low: 85,
+            high: 170,
+            on: false,
+        }
+    }
+}
+
+impl GridComponent for SchmittTrigger {
+    fn tick(&mut self, _: [usize; 2], _: usize, _: &mut World) -> (Vec<VisualEvent>, Vec<AudioEvent>) {
+        let input = self.ports[SchmittTriggerPorts::Input].get();
+        if input >= self.high {
+            self.on = true;
+        } else if input <= self.low {
+            self.on = false;
+        }
+        self.ports[SchmittTriggerPorts::Output].set(if self.on {255} else {0});
+        (Vec::new(), Vec::new())
+    }
+
+    fn build(&mut self) {
+        self.on = false;
+    }
+
+    fn on_place(
+        &mut self,
+        _own_pos: &[usize; 2],
+        _sim_data: &SimulationData,
+        _sprite: &mut TextureAtlasSprite,
+        _atlas: &TextureAtlas,
+    ) {
+    }
+
+    fn ports(&self) -> Vec<&([usize; 2], Side)> {
+        return Self::CONST_PORTS.values().collect();
+    }
+
+    fn set_port(&mut self, offset: [usize; 2], side: Side, set_to: Arc<Net>) -> Result<(),()> {
+        for (varient, (self_offset, self_side)) in Self::CONST_PORTS.iter() {
+            if offset == *self_offset && side == *self_side {
+                self.ports[varient].set_link(Some(set_to));
+                return Ok(());
+            }
+        }
+        Err(())
+    }
+
+    fn gui_options(&mut self, ui: &mut egui::Ui, sim_halted: bool, _: DummyComponent, _: &[usize; 2], _: &mut EventWriter<UpdateComponentEvent>) {
+        ui.add_enabled_ui(sim_halted, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Switch on at: ");
+                ui.add(egui::Slider::new(&mut self.high, 0..=255)).on_disabled_hover_text(helpers::UI_DISABLED_MSG);
+            });
+            ui.horizontal(|ui| {
+                ui.label("Switch off at: ");
+                ui.add(egui::Slider::new(&mut self.low, 0..=255)).on_disabled_hover_text(helpers::UI_DISABLED_MSG);
+            });
+        });
+        // Keep the thresholds the right way round
+        self.low = self.low.min(self.high);
+
+        if !sim_halted {
+            ui.horizontal(|ui| {
+                ui.label("State: ");
+                ui.label(RichText::new(if self.on {"On"} else {"Off"}).code());
+            });
+        }
+    }
+}
+
+impl SchmittTrigger {
+    pub const CONST_PORTS: EnumMap<SchmittTriggerPorts, ([usize; 2], Side)> =
+        EnumMap::from_array([([0, 0], Side::Left), ([0, 0], Side::Right)]);
+}
+
+#[derive(Debug, Enum)]
+pub enum SchmittTriggerPorts {
+    Input,
+    Output,
+}
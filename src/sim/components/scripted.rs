@@ -0,0 +1,161 @@
+use crate::sim::{
+    helpers::Side,
+    model::{AudioEvent, GridComponent, SimulationData, VisualEvent, ComponentPortData, DummyComponent},
+    interactions::UpdateComponentEvent,
+    definitions::ComponentDefinition,
+};
+use bevy::{
+    math::Vec2,
+    reflect::{FromReflect, Reflect},
+    sprite::{TextureAtlas, TextureAtlasSprite}, prelude::{World, EventWriter, Handle},
+};
+use egui::RichText;
+use enum_map::{Enum, EnumMap};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use crate::sim::net::Net;
+
+/// A component defined in a file under [crate::sim::definitions::COMPONENT_DIR] instead of in code.\
+/// Only the name is saved, the definition is looked up again when loading.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Reflect, FromReflect)]
+pub struct Scripted {
+    name: String,
+    #[reflect(ignore)]
+    #[serde(skip)]
+    definition: Option<Arc<ComponentDefinition>>,
+    /// Inputs then outputs, in the same order as the definition
+    #[reflect(ignore)]
+    #[serde(skip)]
+    port_places: Vec<([usize; 2], Side)>,
+    #[reflect(ignore)]
+    #[serde(skip)]
+    ports: Vec<ComponentPortData>,
+    /// Variables of the behaviour, kept between ticks
+    #[reflect(ignore)]
+    #[serde(skip)]
+    slots: Vec<i64>,
+}
+
+impl GridComponent for Scripted {
+    fn tick(&mut self, _: [usize; 2], _: usize, _: &mut World) -> (Vec<VisualEvent>, Vec<AudioEvent>) {
+        let definition = match &self.definition {
+            Some(d) => d,
+            None => return (Vec::new(), Vec::new()),
+        };
+        let input_count = definition.inputs.len();
+        for i in 0..input_count {
+            self.slots[i] = self.ports[i].get() as i64;
+        }
+        definition.program.run(&mut self.slots);
+        for i in input_count..self.ports.len() {
+            self.ports[i].set(self.slots[i].clamp(0, 255) as u8);
+        }
+        (Vec::new(), Vec::new())
+    }
+
+    fn build(&mut self) {
+        let slot_count = self.definition.as_ref().map(|d| d.program.slot_count()).unwrap_or(0);
+        self.slots = vec![0; slot_count];
+    }
+
+    fn on_place(
+        &mut self,
+        _own_pos: &[usize; 2],
+        _sim_data: &SimulationData,
+        sprite: &mut TextureAtlasSprite,
+        atlas: &TextureAtlas,
+    ) {
+        self.set_sprite(sprite, atlas);
+    }
+
+    fn ports(&self) -> Vec<&([usize; 2], Side)> {
+        return self.port_places.iter().collect();
+    }
+
+    fn set_port(&mut self, offset: [usize; 2], side: Side, set_to: Arc<Net>) -> Result<(),()> {
+        for (i, (self_offset, self_side)) in self.port_places.iter().enumerate() {
+            if offset == *self_offset && side == *self_side {
+                self.ports[i].set_link(Some(set_to));
+                return Ok(());
+            }
+        }
+        Err(())
+    }
+
+    fn gui_options(&mut self, ui: &mut egui::Ui, sim_halted: bool, _: DummyComponent, _: &[usize; 2], _: &mut EventWriter<UpdateComponentEvent>) {
+        ui.label(RichText::new(&self.name).strong());
+        let definition = match &self.definition {
+            Some(d) => d,
+            None => {
+                ui.label(RichText::new("This component's definition is missing so it does nothing").color(egui::Color32::RED));
+                return;
+            },
+        };
+        if !definition.desc.is_empty() {
+            ui.label(&definition.desc);
+        }
+        ui.separator();
+        ui.label("Behaviour:");
+        ui.label(RichText::new(&definition.behaviour).code());
+
+        if !sim_halted {
+            ui.separator();
+            for (i, port) in definition.inputs.iter().chain(definition.outputs.iter()).enumerate() {
+                ui.horizontal(|ui| {
+                    ui.label(format!("{}: ", port.name));
+                    ui.label(RichText::new(self.ports[i].get().to_string()).code());
+                });
+            }
+        }
+    }
+}
+
+impl Scripted {
+    /// Ports depend on the definition so come from [GridComponent::ports] instead
+    pub const CONST_PORTS: EnumMap<ScriptedPorts, ([usize; 2], Side)> = EnumMap::from_array([]);
+
+    pub fn new(definition: Arc<ComponentDefinition>) -> Self {
+        let mut scripted = Scripted {
+            name: definition.name.clone(),
+            ..Default::default()
+        };
+        scripted.set_definition(Some(definition));
+        scripted
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn set_definition(&mut self, definition: Option<Arc<ComponentDefinition>>) {
+        self.port_places = match &definition {
+            Some(d) => d.inputs.iter().chain(d.outputs.iter()).map(|p| (p.offset, p.side)).collect(),
+            None => Vec::new(),
+        };
+        self.ports = vec![ComponentPortData::default(); self.port_places.len()];
+        self.slots = vec![0; definition.as_ref().map(|d| d.program.slot_count()).unwrap_or(0)];
+        self.definition = definition;
+    }
+
+    pub fn grid_size(&self) -> [usize; 2] {
+        self.definition.as_ref().map(|d| d.size).unwrap_or([1, 1])
+    }
+
+    pub fn size(&self) -> Vec2 {
+        let size = self.grid_size();
+        Vec2::new(size[0] as f32 * 32.0, size[1] as f32 * 32.0)
+    }
+
+    pub fn set_sprite(&self, sprite: &mut TextureAtlasSprite, atlas: &TextureAtlas) {
+        let sprite_name = match &self.definition {
+            Some(d) => d.sprite_name(),
+            None => "custom_1x1".to_string(),
+        };
+        if let Some(index) = atlas.get_texture_index(&Handle::weak(sprite_name.as_str().into())) {
+            sprite.index = index;
+        }
+    }
+}
+
+#[derive(Debug, Enum)]
+pub enum ScriptedPorts {}
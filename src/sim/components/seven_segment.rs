@@ -0,0 +1,151 @@
+use crate::sim::{
+    helpers::{self, Side},
+    model::{AudioEvent, GridComponent, SimulationData, VisualEvent, ComponentPortData, DummyComponent}, interactions::UpdateComponentEvent,
+};
+use bevy::{
+    reflect::{FromReflect, Reflect},
+    sprite::{TextureAtlas, TextureAtlasSprite}, prelude::{World, EventWriter, Vec2},
+};
+use egui::RichText;
+use enum_map::{Enum, EnumMap};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use crate::sim::net::Net;
+
+/// Offset from the bottom left of the component and the (on, off) sprites of each segment.\
+/// In bit order, segments a-g followed by the decimal point
+const SEGMENTS: [(Vec2, (&str, &str)); 8] = [
+    (Vec2::new(12.0, 46.0), HORIZONTAL), // a
+    (Vec2::new(22.0, 33.0), VERTICAL), // b
+    (Vec2::new(22.0, 17.0), VERTICAL), // c
+    (Vec2::new(12.0, 14.0), HORIZONTAL), // d
+    (Vec2::new(9.0, 17.0), VERTICAL), // e
+    (Vec2::new(9.0, 33.0), VERTICAL), // f
+    (Vec2::new(12.0, 30.0), HORIZONTAL), // g
+    (Vec2::new(26.0, 14.0), POINT), // dp
+];
+const HORIZONTAL: (&str, &str) = ("segment_horizontal_on", "segment_horizontal_off");
+const VERTICAL: (&str, &str) = ("segment_vertical_on", "segment_vertical_off");
+const POINT: (&str, &str) = ("segment_point_on", "segment_point_off");
+
+/// Segments to light for each hex digit, bit 0 is segment a
+const DIGITS: [u8; 16] = [
+    0x3F, 0x06, 0x5B, 0x4F, 0x66, 0x6D, 0x7D, 0x07,
+    0x7F, 0x6F, 0x77, 0x7C, 0x39, 0x5E, 0x79, 0x71,
+];
+
+/// A single 7-segment digit which decodes its input.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Reflect, FromReflect)]
+pub struct SevenSegment {
+    mode: SegmentMode,
+    /// The segments currently shown, [None] if they need redrawing
+    #[reflect(ignore)]
+    #[serde(skip)]
+    shown: Option<u8>,
+    #[reflect(ignore)]
+    #[serde(skip)]
+    ports: EnumMap<SevenSegmentPorts, ComponentPortData>,
+}
+
+impl GridComponent for SevenSegment {
+    fn tick(&mut self, own_pos: [usize; 2], _: usize, world: &mut World) -> (Vec<VisualEvent>, Vec<AudioEvent>) {
+        let input = self.ports[SevenSegmentPorts::Input].get();
+        let segments = self.mode.decode(input);
+        if self.shown != Some(segments) {
+            self.shown = Some(segments);
+            let overlays: Vec<(Vec2, &str)> = SEGMENTS.iter().enumerate().map(|(bit, (offset, (on, off)))| {
+                (*offset, if segments & (1 << bit) != 0 {*on} else {*off})
+            }).collect();
+            helpers::set_overlay_sprites(world, &own_pos, &overlays);
+        }
+        (Vec::new(), Vec::new())
+    }
+
+    fn build(&mut self) {
+        self.shown = None;
+    }
+
+    fn on_place(
+        &mut self,
+        _own_pos: &[usize; 2],
+        _sim_data: &SimulationData,
+        _sprite: &mut TextureAtlasSprite,
+        _atlas: &TextureAtlas,
+    ) {
+    }
+
+    fn ports(&self) -> Vec<&([usize; 2], Side)> {
+        return Self::CONST_PORTS.values().collect();
+    }
+
+    fn set_port(&mut self, offset: [usize; 2], side: Side, set_to: Arc<Net>) -> Result<(),()> {
+        for (varient, (self_offset, self_side)) in Self::CONST_PORTS.iter() {
+            if offset == *self_offset && side == *self_side {
+                self.ports[varient].set_link(Some(set_to));
+                return Ok(());
+            }
+        }
+        Err(())
+    }
+
+    fn gui_options(&mut self, ui: &mut egui::Ui, sim_halted: bool, _: DummyComponent, _: &[usize; 2], _: &mut EventWriter<UpdateComponentEvent>) {
+        ui.add_enabled_ui(sim_halted, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Mode: ");
+                egui::ComboBox::from_id_source("seven_segment_mode")
+                    .selected_text(self.mode.as_str())
+                    .show_ui(ui, |ui| {
+                        for mode in [SegmentMode::Decimal, SegmentMode::Hex, SegmentMode::Raw] {
+                            ui.selectable_value(&mut self.mode, mode, mode.as_str());
+                        }
+                    }).response.on_disabled_hover_text(helpers::UI_DISABLED_MSG);
+            });
+        });
+        ui.label(RichText::new(self.mode.desc()).weak());
+    }
+}
+
+impl SevenSegment {
+    pub const CONST_PORTS: EnumMap<SevenSegmentPorts, ([usize; 2], Side)> = EnumMap::from_array([([0, 0], Side::Left)]);
+}
+
+#[derive(Debug, Enum)]
+pub enum SevenSegmentPorts {
+    Input,
+}
+
+/// How the input of a [SevenSegment] is turned into lit segments
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Reflect, FromReflect)]
+pub enum SegmentMode {
+    #[default]
+    Decimal,
+    Hex,
+    Raw,
+}
+
+impl SegmentMode {
+    /// Returns the segments to light, bit 0 is segment a and bit 7 is the decimal point
+    pub fn decode(self, input: u8) -> u8 {
+        match self {
+            SegmentMode::Decimal => DIGITS[(input % 10) as usize],
+            SegmentMode::Hex => DIGITS[(input & 0x0F) as usize],
+            SegmentMode::Raw => input,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SegmentMode::Decimal => "Decimal",
+            SegmentMode::Hex => "Hex",
+            SegmentMode::Raw => "Raw",
+        }
+    }
+
+    pub fn desc(self) -> &'static str {
+        match self {
+            SegmentMode::Decimal => "Shows the last decimal digit of the input.",
+            SegmentMode::Hex => "Shows the lowest 4 bits of the input as a hex digit.",
+            SegmentMode::Raw => "Each bit lights one segment, a-g then the decimal point.",
+        }
+    }
+}
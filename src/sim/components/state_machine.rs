@@ -0,0 +1,301 @@
+use crate::sim::{
+    helpers::{self, Side},
+    model::{AudioEvent, GridComponent, SimulationData, VisualEvent, ComponentPortData, DummyComponent}, interactions::UpdateComponentEvent,
+};
+use bevy::{
+    reflect::{FromReflect, Reflect},
+    sprite::{TextureAtlas, TextureAtlasSprite}, prelude::{World, EventWriter},
+};
+use egui::{Color32, RichText};
+use egui_extras::{Column, TableBuilder};
+use enum_map::{Enum, EnumMap};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use crate::sim::net::Net;
+
+/// Most states a single machine can have
+pub const MAX_STATES: usize = 16;
+
+/// A finite state machine driven by a transition table instead of wired up flip-flops.\
+/// Each step the first row matching the current state and input is taken, which sets the next state and output.
+/// If no row matches the machine stays where it is and the output is unchanged.\
+/// The first state is where every run starts.
+#[derive(Debug, Serialize, Deserialize, Clone, Reflect, FromReflect)]
+#[serde(default)]
+pub struct StateMachine {
+    #[reflect(ignore)]
+    #[serde(skip)]
+    ports: EnumMap<StateMachinePorts, ComponentPortData>,
+    states: Vec<String>,
+    #[reflect(ignore)]
+    transitions: Vec<Transition>,
+    mode: StepMode,
+    #[serde(skip)]
+    state: usize,
+    #[serde(skip)]
+    output: u8,
+    #[serde(skip)]
+    last_clock: u8,
+}
+
+/// One row of the table, taken when in `from` and the input is between `min` and `max` (inclusive)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transition {
+    pub from: usize,
+    pub min: u8,
+    pub max: u8,
+    pub to: usize,
+    pub output: u8,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Reflect, FromReflect)]
+pub enum StepMode {
+    #[default]
+    EveryTick,
+    ClockEdge,
+}
+
+impl Default for StateMachine {
+    fn default() -> Self {
+        Self {
+            ports: EnumMap::default(),
+            states: vec!["Start".to_string()],
+            transitions: vec![Transition { from: 0, min: 0, max: 255, to: 0, output: 0 }],
+            mode: StepMode::EveryTick,
+            state: 0,
+            output: 0,
+            last_clock: 0,
+        }
+    }
+}
+
+impl GridComponent for StateMachine {
+    fn tick(&mut self, _: [usize; 2], _: usize, _: &mut World) -> (Vec<VisualEvent>, Vec<AudioEvent>) {
+        let clock = self.ports[StateMachinePorts::Clock].get();
+        let step = match self.mode {
+            StepMode::EveryTick => true,
+            StepMode::ClockEdge => clock >= 255 && self.last_clock < 255,
+        };
+        self.last_clock = clock;
+
+        if step {
+            let input = self.ports[StateMachinePorts::Input].get();
+            let taken = self.transitions.iter().find(|t| {
+                t.from == self.state && input >= t.min && input <= t.max
+            });
+            if let Some(transition) = taken {
+                self.state = transition.to;
+                self.output = transition.output;
+            }
+        }
+        self.ports[StateMachinePorts::Output].set(self.output);
+        self.ports[StateMachinePorts::State].set(self.state as u8);
+        (Vec::new(), Vec::new())
+    }
+
+    fn build(&mut self) {
+        if self.states.is_empty() {
+            self.states.push("Start".to_string());
+        }
+        let state_count = self.states.len();
+        self.transitions.retain(|t| t.from < state_count && t.to < state_count);
+        self.state = 0;
+        self.output = 0;
+        self.last_clock = 0;
+    }
+
+    fn on_place(
+        &mut self,
+        _own_pos: &[usize; 2],
+        _sim_data: &SimulationData,
+        _sprite: &mut TextureAtlasSprite,
+        _atlas: &TextureAtlas,
+    ) {
+    }
+
+    fn ports(&self) -> Vec<&([usize; 2], Side)> {
+        return Self::CONST_PORTS.values().collect();
+    }
+
+    fn set_port(&mut self, offset: [usize; 2], side: Side, set_to: Arc<Net>) -> Result<(),()> {
+        for (varient, (self_offset, self_side)) in Self::CONST_PORTS.iter() {
+            if offset == *self_offset && side == *self_side {
+                self.ports[varient].set_link(Some(set_to));
+                return Ok(());
+            }
+        }
+        Err(())
+    }
+
+    fn gui_options(&mut self, ui: &mut egui::Ui, sim_halted: bool, _: DummyComponent, _: &[usize; 2], _: &mut EventWriter<UpdateComponentEvent>) {
+        ui.add_enabled_ui(sim_halted, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Step: ");
+                ui.selectable_value(&mut self.mode, StepMode::EveryTick, "Every tick");
+                ui.selectable_value(&mut self.mode, StepMode::ClockEdge, "On clock");
+            });
+            ui.separator();
+            self.states_editor(ui);
+            ui.separator();
+            self.table_editor(ui);
+        });
+
+        for problem in self.validate() {
+            ui.label(RichText::new(problem).color(Color32::RED));
+        }
+
+        if !sim_halted {
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label("Current state: ");
+                let name = self.states.get(self.state).map(|s| s.as_str()).unwrap_or("?");
+                ui.label(RichText::new(format!("{} ({})", name, self.state)).code());
+            });
+            ui.horizontal(|ui| {
+                ui.label("Output: ");
+                ui.label(RichText::new(self.output.to_string()).code());
+            });
+        }
+    }
+}
+
+impl StateMachine {
+    pub const CONST_PORTS: EnumMap<StateMachinePorts, ([usize; 2], Side)> = EnumMap::from_array([
+        ([0, 1], Side::Left),
+        ([0, 0], Side::Left),
+        ([1, 1], Side::Right),
+        ([1, 0], Side::Right),
+    ]);
+
+    /// Problems with the table, states which can never be reached and inputs a state has no row for
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        for (i, name) in self.states.iter().enumerate() {
+            let mut covered = [false; 256];
+            for transition in self.transitions.iter().filter(|t| t.from == i) {
+                for value in transition.min..=transition.max {
+                    covered[value as usize] = true;
+                }
+            }
+            if let Some(first) = covered.iter().position(|c| !c) {
+                let last = covered[first..].iter().position(|c| *c).map(|p| first + p - 1).unwrap_or(255);
+                problems.push(format!("{} has no row for inputs {} to {}", name, first, last));
+            }
+        }
+
+        let mut reachable = vec![false; self.states.len()];
+        let mut to_visit = vec![0];
+        while let Some(state) = to_visit.pop() {
+            if state >= reachable.len() || reachable[state] {continue}
+            reachable[state] = true;
+            to_visit.extend(self.transitions.iter().filter(|t| t.from == state).map(|t| t.to));
+        }
+        for (name, _) in self.states.iter().zip(reachable).filter(|(_, r)| !r) {
+            problems.push(format!("{} can never be reached", name));
+        }
+
+        problems
+    }
+
+    fn states_editor(&mut self, ui: &mut egui::Ui) {
+        ui.label(RichText::new("States").strong());
+        let mut remove = None;
+        for (i, name) in self.states.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label(format!("{}:", i));
+                ui.add(egui::TextEdit::singleline(name).desired_width(100.0));
+                // The first state is the start so always stays
+                if i != 0 && ui.small_button("x").on_disabled_hover_text(helpers::UI_DISABLED_MSG).clicked() {
+                    remove = Some(i);
+                }
+            });
+        }
+        if let Some(i) = remove {
+            self.remove_state(i);
+        }
+        if self.states.len() < MAX_STATES && ui.button("Add state").on_disabled_hover_text(helpers::UI_DISABLED_MSG).clicked() {
+            self.states.push(format!("State {}", self.states.len()));
+        }
+    }
+
+    fn table_editor(&mut self, ui: &mut egui::Ui) {
+        ui.label(RichText::new("Transitions").strong());
+        let states = &self.states;
+        let transitions = &mut self.transitions;
+        let mut remove = None;
+
+        TableBuilder::new(ui)
+            .striped(true)
+            .vscroll(false)
+            .columns(Column::auto(), 6)
+            .header(20.0, |mut header| {
+                for title in ["State", "In min", "In max", "Next", "Output", ""] {
+                    header.col(|ui| {
+                        ui.label(title);
+                    });
+                }
+            })
+            .body(|mut body| {
+                for (i, transition) in transitions.iter_mut().enumerate() {
+                    body.row(20.0, |mut row| {
+                        row.col(|ui| state_combo(ui, ("fsm_from", i), states, &mut transition.from));
+                        row.col(|ui| {
+                            ui.add(egui::DragValue::new(&mut transition.min));
+                        });
+                        row.col(|ui| {
+                            ui.add(egui::DragValue::new(&mut transition.max));
+                        });
+                        row.col(|ui| state_combo(ui, ("fsm_to", i), states, &mut transition.to));
+                        row.col(|ui| {
+                            ui.add(egui::DragValue::new(&mut transition.output));
+                        });
+                        row.col(|ui| {
+                            if ui.small_button("x").clicked() {
+                                remove = Some(i);
+                            }
+                        });
+                    });
+                    if transition.max < transition.min {
+                        transition.max = transition.min;
+                    }
+                }
+            });
+
+        if let Some(i) = remove {
+            transitions.remove(i);
+        }
+        if ui.button("Add row").on_disabled_hover_text(helpers::UI_DISABLED_MSG).clicked() {
+            transitions.push(Transition { from: 0, min: 0, max: 255, to: 0, output: 0 });
+        }
+    }
+
+    /// Drops every row using the state and moves later states down one
+    fn remove_state(&mut self, index: usize) {
+        self.states.remove(index);
+        self.transitions.retain(|t| t.from != index && t.to != index);
+        for transition in self.transitions.iter_mut() {
+            if transition.from > index {transition.from -= 1}
+            if transition.to > index {transition.to -= 1}
+        }
+    }
+}
+
+fn state_combo(ui: &mut egui::Ui, id: impl std::hash::Hash, states: &[String], selected: &mut usize) {
+    let current = states.get(*selected).map(|s| s.as_str()).unwrap_or("?");
+    egui::ComboBox::from_id_source(id)
+        .selected_text(current)
+        .show_ui(ui, |ui| {
+            for (i, name) in states.iter().enumerate() {
+                ui.selectable_value(selected, i, name.as_str());
+            }
+        });
+}
+
+#[derive(Debug, Enum)]
+pub enum StateMachinePorts {
+    Input,
+    Clock,
+    Output,
+    State,
+}
@@ -0,0 +1,90 @@
+use crate::sim::{
+    helpers::Side,
+    model::{AudioEvent, GridComponent, SimulationData, VisualEvent, ComponentPortData, DummyComponent}, interactions::UpdateComponentEvent,
+};
+use bevy::{
+    reflect::{FromReflect, Reflect},
+    sprite::{TextureAtlas, TextureAtlasSprite}, prelude::{World, EventWriter, Handle},
+};
+use egui::RichText;
+use enum_map::{Enum, EnumMap};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use crate::sim::net::Net;
+
+/// A switch the player can flip by clicking it while the simulation is running.\
+/// Outputs 255 when on and 0 when off, the state it is left in is kept in saves.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Reflect, FromReflect)]
+pub struct ToggleSwitch {
+    on: bool,
+    #[reflect(ignore)]
+    #[serde(skip)]
+    ports: EnumMap<ToggleSwitchPorts, ComponentPortData>,
+}
+
+impl GridComponent for ToggleSwitch {
+    fn tick(&mut self, _: [usize; 2], _: usize, _: &mut World) -> (Vec<VisualEvent>, Vec<AudioEvent>) {
+        self.ports[ToggleSwitchPorts::Output].set(if self.on {255} else {0});
+        (Vec::new(), Vec::new())
+    }
+
+    fn build(&mut self) {}
+
+    fn on_place(
+        &mut self,
+        _own_pos: &[usize; 2],
+        _sim_data: &SimulationData,
+        sprite: &mut TextureAtlasSprite,
+        atlas: &TextureAtlas,
+    ) {
+        self.update_sprite(sprite, atlas);
+    }
+
+    fn ports(&self) -> Vec<&([usize; 2], Side)> {
+        return Self::CONST_PORTS.values().collect();
+    }
+
+    fn set_port(&mut self, offset: [usize; 2], side: Side, set_to: Arc<Net>) -> Result<(),()> {
+        for (varient, (self_offset, self_side)) in Self::CONST_PORTS.iter() {
+            if offset == *self_offset && side == *self_side {
+                self.ports[varient].set_link(Some(set_to));
+                return Ok(());
+            }
+        }
+        Err(())
+    }
+
+    fn gui_options(&mut self, ui: &mut egui::Ui, _: bool, _: DummyComponent, pos: &[usize; 2], updates: &mut EventWriter<UpdateComponentEvent>) {
+        ui.horizontal(|ui| {
+            ui.label("State: ");
+            ui.label(RichText::new(if self.on {"On"} else {"Off"}).code());
+        });
+        if ui.checkbox(&mut self.on, "On").changed() {
+            updates.send(UpdateComponentEvent{pos: *pos, surround: false});
+        }
+    }
+
+    fn interact(&mut self, sprite: &mut TextureAtlasSprite, atlas: &TextureAtlas) {
+        self.on = !self.on;
+        self.update_sprite(sprite, atlas);
+    }
+}
+
+impl ToggleSwitch {
+    pub const CONST_PORTS: EnumMap<ToggleSwitchPorts, ([usize; 2], Side)> = EnumMap::from_array([([0, 0], Side::Right)]);
+
+    pub fn sprite_name(&self) -> &'static str {
+        if self.on {"switch_on"} else {"switch_off"}
+    }
+
+    fn update_sprite(&self, sprite: &mut TextureAtlasSprite, atlas: &TextureAtlas) {
+        sprite.index = atlas
+            .get_texture_index(&Handle::weak(self.sprite_name().into()))
+            .expect("Could not find switch sprite");
+    }
+}
+
+#[derive(Debug, Enum)]
+pub enum ToggleSwitchPorts {
+    Output,
+}
@@ -0,0 +1,174 @@
+use crate::sim::{
+    helpers::{self, Side},
+    model::{AudioEvent, GridComponent, SimulationData, VisualEvent, ComponentPortData, DummyComponent}, interactions::UpdateComponentEvent,
+};
+use bevy::{
+    reflect::{FromReflect, Reflect},
+    sprite::{TextureAtlas, TextureAtlasSprite}, prelude::{World, EventWriter},
+};
+use egui::RichText;
+use enum_map::{Enum, EnumMap};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use crate::sim::net::Net;
+
+/// Sends whatever reaches it to every [TunnelReceive] with the same label.\
+/// Nothing happens when ticked, the nets are joined in [crate::sim::run::build_nets] as if a wire ran between them.
+#[derive(Debug, Serialize, Deserialize, Clone, Reflect, FromReflect)]
+#[serde(default)]
+pub struct TunnelSend {
+    #[reflect(ignore)]
+    #[serde(skip)]
+    ports: EnumMap<TunnelSendPorts, ComponentPortData>,
+    label: String,
+}
+
+/// Outputs whatever reaches the [TunnelSend] with the same label
+#[derive(Debug, Serialize, Deserialize, Clone, Reflect, FromReflect)]
+#[serde(default)]
+pub struct TunnelReceive {
+    #[reflect(ignore)]
+    #[serde(skip)]
+    ports: EnumMap<TunnelReceivePorts, ComponentPortData>,
+    label: String,
+}
+
+impl Default for TunnelSend {
+    fn default() -> Self {
+        Self {
+            ports: EnumMap::default(),
+            label: "A".to_string(),
+        }
+    }
+}
+
+impl Default for TunnelReceive {
+    fn default() -> Self {
+        Self {
+            ports: EnumMap::default(),
+            label: "A".to_string(),
+        }
+    }
+}
+
+impl GridComponent for TunnelSend {
+    fn tick(&mut self, _: [usize; 2], _: usize, _: &mut World) -> (Vec<VisualEvent>, Vec<AudioEvent>) {
+        (Vec::new(), Vec::new())
+    }
+
+    fn build(&mut self) {}
+
+    fn on_place(
+        &mut self,
+        _own_pos: &[usize; 2],
+        _sim_data: &SimulationData,
+        _sprite: &mut TextureAtlasSprite,
+        _atlas: &TextureAtlas,
+    ) {
+    }
+
+    fn ports(&self) -> Vec<&([usize; 2], Side)> {
+        return Self::CONST_PORTS.values().collect();
+    }
+
+    fn set_port(&mut self, offset: [usize; 2], side: Side, set_to: Arc<Net>) -> Result<(),()> {
+        for (varient, (self_offset, self_side)) in Self::CONST_PORTS.iter() {
+            if offset == *self_offset && side == *self_side {
+                self.ports[varient].set_link(Some(set_to));
+                return Ok(());
+            }
+        }
+        Err(())
+    }
+
+    fn gui_options(&mut self, ui: &mut egui::Ui, sim_halted: bool, _: DummyComponent, _: &[usize; 2], _: &mut EventWriter<UpdateComponentEvent>) {
+        label_options(ui, sim_halted, &mut self.label);
+        if !sim_halted {
+            ui.horizontal(|ui| {
+                ui.label("Sending: ");
+                ui.label(RichText::new(self.ports[TunnelSendPorts::Input].get().to_string()).code());
+            });
+        }
+    }
+}
+
+impl GridComponent for TunnelReceive {
+    fn tick(&mut self, _: [usize; 2], _: usize, _: &mut World) -> (Vec<VisualEvent>, Vec<AudioEvent>) {
+        (Vec::new(), Vec::new())
+    }
+
+    fn build(&mut self) {}
+
+    fn on_place(
+        &mut self,
+        _own_pos: &[usize; 2],
+        _sim_data: &SimulationData,
+        _sprite: &mut TextureAtlasSprite,
+        _atlas: &TextureAtlas,
+    ) {
+    }
+
+    fn ports(&self) -> Vec<&([usize; 2], Side)> {
+        return Self::CONST_PORTS.values().collect();
+    }
+
+    fn set_port(&mut self, offset: [usize; 2], side: Side, set_to: Arc<Net>) -> Result<(),()> {
+        for (varient, (self_offset, self_side)) in Self::CONST_PORTS.iter() {
+            if offset == *self_offset && side == *self_side {
+                self.ports[varient].set_link(Some(set_to));
+                return Ok(());
+            }
+        }
+        Err(())
+    }
+
+    fn gui_options(&mut self, ui: &mut egui::Ui, sim_halted: bool, _: DummyComponent, _: &[usize; 2], _: &mut EventWriter<UpdateComponentEvent>) {
+        label_options(ui, sim_halted, &mut self.label);
+        if !sim_halted {
+            ui.horizontal(|ui| {
+                ui.label("Receiving: ");
+                ui.label(RichText::new(self.ports[TunnelReceivePorts::Output].get().to_string()).code());
+            });
+        }
+    }
+}
+
+fn label_options(ui: &mut egui::Ui, sim_halted: bool, label: &mut String) {
+    ui.add_enabled_ui(sim_halted, |ui| {
+        ui.horizontal(|ui| {
+            ui.label("Label: ");
+            ui.text_edit_singleline(label).on_disabled_hover_text(helpers::UI_DISABLED_MSG);
+        });
+    });
+    if label.trim().is_empty() {
+        ui.label(RichText::new("Tunnels without a label are not linked").weak());
+    }
+}
+
+impl TunnelSend {
+    pub const CONST_PORTS: EnumMap<TunnelSendPorts, ([usize; 2], Side)> =
+        EnumMap::from_array([([0, 0], Side::Left)]);
+
+    pub fn label(&self) -> &str {
+        self.label.trim()
+    }
+}
+
+impl TunnelReceive {
+    pub const CONST_PORTS: EnumMap<TunnelReceivePorts, ([usize; 2], Side)> =
+        EnumMap::from_array([([0, 0], Side::Right)]);
+
+    pub fn label(&self) -> &str {
+        self.label.trim()
+    }
+}
+
+#[derive(Debug, Enum)]
+pub enum TunnelSendPorts {
+    Input,
+}
+
+#[derive(Debug, Enum)]
+pub enum TunnelReceivePorts {
+    Output,
+}
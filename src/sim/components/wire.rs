@@ -1,5 +1,5 @@
 use std::sync::Arc;
-use std::sync::atomic::AtomicU8;
+use crate::sim::net::{Net, NetResolution};
 
 use crate::sim::helpers;
 use crate::sim::{
@@ -25,7 +25,11 @@ pub struct Wire {
     #[reflect(ignore)]
     pub connected_sides: EnumMap<helpers::Side, bool>,
     #[reflect(ignore)]
-    pub disabled_sides: EnumMap<helpers::Side, EnabledOrDisabled>
+    pub disabled_sides: EnumMap<helpers::Side, EnabledOrDisabled>,
+    /// Replaces the level's [NetResolution] for the whole net this wire is on, any other wire on it picking a different one is a conflict
+    #[reflect(ignore)]
+    #[serde(default)]
+    pub resolution: Option<NetResolution>,
 }
 
 impl GridComponent for Wire {
@@ -71,7 +75,7 @@ impl GridComponent for Wire {
         Vec::new()
     }
 
-    fn set_port(&mut self, _: [usize; 2], _: Side, _: Arc<AtomicU8>) -> Result<(),()> {
+    fn set_port(&mut self, _: [usize; 2], _: Side, _: Arc<Net>) -> Result<(),()> {
         Err(())
     }
 
@@ -103,6 +107,17 @@ impl GridComponent for Wire {
             if side_change {
                 updates.send(UpdateComponentEvent{pos: pos.clone(), surround: true});
             }
+
+            ui.heading("Multiple drivers:");
+            let selected = self.resolution.map(|r| r.as_str()).unwrap_or("Level default");
+            egui::ComboBox::from_id_source("wire_resolution")
+                .selected_text(selected)
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.resolution, None, "Level default");
+                    for resolution in NetResolution::ALL {
+                        ui.selectable_value(&mut self.resolution, Some(resolution), resolution.as_str());
+                    }
+                }).response.on_disabled_hover_text(helpers::UI_DISABLED_MSG);
         });
     }
 }
@@ -126,11 +141,15 @@ pub enum EnabledOrDisabled {
 /// I just did this so I could use ?, its kinda weird
 fn check_for_wire_option(pos: &[usize; 2], grid: &ComponentGrid, origin_side: Side) -> Option<()> {
     let cell = grid.grid.get(pos[0])?.get(pos[1])?;
-    if let CellState::Real(_, a) = cell {
-        if let Component::WirePiece(w) = a { // Check the side is enabled to prevent wire connection missmatch
-            if w.disabled_sides[origin_side] == EnabledOrDisabled::Enabled {
-                return Some(());
+    if let CellState::Real(_, a, _) = cell {
+        match a {
+            Component::WirePiece(w) => { // Check the side is enabled to prevent wire connection missmatch
+                if w.disabled_sides[origin_side] == EnabledOrDisabled::Enabled {
+                    return Some(());
+                }
             }
+            Component::WireCrossover(_) => return Some(()), // Crossovers accept wires from every side
+            _ => {}
         }
     }
     None
@@ -138,7 +157,7 @@ fn check_for_wire_option(pos: &[usize; 2], grid: &ComponentGrid, origin_side: Si
 
 /// Check for a wire link at this position.\
 /// Origin side is relative to the checked position.
-fn check_for_wire_link(pos: &[usize; 2], grid: &ComponentGrid, origin_side: Side) -> bool {
+pub fn check_for_wire_link(pos: &[usize; 2], grid: &ComponentGrid, origin_side: Side) -> bool {
     check_for_wire_option(pos, grid, origin_side).is_some()
 }
 
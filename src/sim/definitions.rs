@@ -0,0 +1,186 @@
+use std::{collections::BTreeMap, fmt, fs::File, path::Path, sync::Arc};
+use bevy::prelude::*;
+use glob::glob;
+use serde::{Deserialize, Serialize};
+use super::{
+    components::Scripted,
+    expression::{ExpressionError, Program},
+    helpers::Side,
+    model::{CellState, Component, ComponentGrid},
+};
+
+/// Simple components can be written as ron or json files in here instead of in code
+pub const COMPONENT_DIR: &str = "data/components";
+/// There are only fallback sprites up to this size
+pub const MAX_COMPONENT_SIZE: usize = 3;
+
+/// A component loaded from [COMPONENT_DIR], e.g.
+/// ```ron
+/// (
+///     name: "Adder",
+///     desc: "Adds A and B, wrapping past 255",
+///     size: (1, 2),
+///     inputs: [(name: "a", offset: (0, 1), side: Left), (name: "b", offset: (0, 0), side: Left)],
+///     outputs: [(name: "out", offset: (0, 0), side: Right)],
+///     behaviour: "out = (a + b) & 0xFF",
+/// )
+/// ```
+/// See [super::expression] for what can go in `behaviour`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComponentDefinition {
+    pub name: String,
+    #[serde(default)]
+    pub desc: String,
+    #[serde(default = "single_cell")]
+    pub size: [usize; 2],
+    /// Name in the sprite map, a plain body of the right size is used if this is missing
+    #[serde(default)]
+    pub sprite: Option<String>,
+    #[serde(default)]
+    pub inputs: Vec<PortDefinition>,
+    #[serde(default)]
+    pub outputs: Vec<PortDefinition>,
+    pub behaviour: String,
+    #[serde(skip)]
+    pub program: Program,
+}
+
+fn single_cell() -> [usize; 2] {
+    [1, 1]
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortDefinition {
+    pub name: String,
+    pub offset: [usize; 2],
+    pub side: Side,
+}
+
+#[derive(Debug)]
+pub enum DefinitionError {
+    Parse(String),
+    BadSize,
+    NoPorts,
+    /// The port is not on the edge of the component facing its side
+    PortNotOnEdge(String),
+    DuplicatePort(String),
+    Behaviour(ExpressionError),
+}
+
+impl fmt::Display for DefinitionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DefinitionError::Parse(e) => write!(f, "Could not parse: {}", e),
+            DefinitionError::BadSize => write!(f, "Components must be between 1 and {} cells in each direction", MAX_COMPONENT_SIZE),
+            DefinitionError::NoPorts => write!(f, "Components need at least one input or output"),
+            DefinitionError::PortNotOnEdge(name) => write!(f, "The port {} is not on the edge it faces", name),
+            DefinitionError::DuplicatePort(name) => write!(f, "The port {} shares its name or place with another port", name),
+            DefinitionError::Behaviour(e) => write!(f, "Behaviour: {}", e),
+        }
+    }
+}
+
+impl ComponentDefinition {
+    /// Read a definition from a ron or json file, checking it and compiling its behaviour
+    pub fn from_file(path: &Path) -> Result<Self, DefinitionError> {
+        let file = File::open(path).map_err(|e| DefinitionError::Parse(e.to_string()))?;
+        let mut definition: ComponentDefinition = match path.extension().and_then(|e| e.to_str()) {
+            Some("ron") => ron::de::from_reader(file).map_err(|e| DefinitionError::Parse(e.to_string()))?,
+            _ => serde_json::from_reader(file).map_err(|e| DefinitionError::Parse(e.to_string()))?,
+        };
+        definition.validate()?;
+        Ok(definition)
+    }
+
+    fn validate(&mut self) -> Result<(), DefinitionError> {
+        if self.size.iter().any(|s| *s == 0 || *s > MAX_COMPONENT_SIZE) {
+            return Err(DefinitionError::BadSize);
+        }
+        if self.inputs.is_empty() && self.outputs.is_empty() {
+            return Err(DefinitionError::NoPorts);
+        }
+
+        let all_ports: Vec<&PortDefinition> = self.inputs.iter().chain(self.outputs.iter()).collect();
+        for (i, port) in all_ports.iter().enumerate() {
+            let on_edge = port.offset[0] < self.size[0] && port.offset[1] < self.size[1] && match port.side {
+                Side::Left => port.offset[0] == 0,
+                Side::Right => port.offset[0] == self.size[0] - 1,
+                Side::Down => port.offset[1] == 0,
+                Side::Up => port.offset[1] == self.size[1] - 1,
+            };
+            if !on_edge {
+                return Err(DefinitionError::PortNotOnEdge(port.name.clone()));
+            }
+            let duplicate = all_ports[..i].iter().any(|other| {
+                other.name == port.name || (other.offset == port.offset && other.side == port.side)
+            });
+            if duplicate {
+                return Err(DefinitionError::DuplicatePort(port.name.clone()));
+            }
+        }
+
+        let inputs: Vec<String> = self.inputs.iter().map(|p| p.name.clone()).collect();
+        let outputs: Vec<String> = self.outputs.iter().map(|p| p.name.clone()).collect();
+        self.program = Program::compile(&self.behaviour, &inputs, &outputs).map_err(DefinitionError::Behaviour)?;
+        Ok(())
+    }
+
+    pub fn sprite_name(&self) -> String {
+        match &self.sprite {
+            Some(sprite) => sprite.clone(),
+            None => format!("custom_{}x{}", self.size[0], self.size[1]),
+        }
+    }
+}
+
+/// Every component loaded from [COMPONENT_DIR], saves refer to these by name
+#[derive(Resource, Debug, Default)]
+pub struct ComponentRegistry(pub BTreeMap<String, Arc<ComponentDefinition>>);
+
+/// Loads at startup so it is ready before any level or package
+impl FromWorld for ComponentRegistry {
+    fn from_world(_: &mut World) -> Self {
+        ComponentRegistry::load()
+    }
+}
+
+impl ComponentRegistry {
+    /// Read every definition in [COMPONENT_DIR], any which are invalid are logged and skipped
+    pub fn load() -> Self {
+        let mut registry = BTreeMap::new();
+        for extension in ["ron", "json"] {
+            for path in glob(&format!("{}/*.{}", COMPONENT_DIR, extension)).expect("Failed to read glob pattern").flatten() {
+                match ComponentDefinition::from_file(&path) {
+                    Ok(definition) => {
+                        if registry.contains_key(&definition.name) {
+                            error!("Component {} in {:?} has the same name as another, skipping", definition.name, path);
+                            continue;
+                        }
+                        registry.insert(definition.name.clone(), Arc::new(definition));
+                    },
+                    Err(e) => error!("Could not load component {:?}: {}", path, e),
+                }
+            }
+        }
+        ComponentRegistry(registry)
+    }
+
+    pub fn build(&self, name: &str) -> Option<Component> {
+        self.0.get(name).map(|definition| Component::Scripted(Scripted::new(definition.clone())))
+    }
+
+    /// Saves only store the name of a data driven component, this looks each one up again
+    pub fn attach(&self, grid: &mut ComponentGrid) {
+        for column in grid.grid.iter_mut() {
+            for cell in column.iter_mut() {
+                if let CellState::Real(_, Component::Scripted(scripted), _) = cell {
+                    let definition = self.0.get(scripted.name()).cloned();
+                    if definition.is_none() {
+                        error!("Could not find component {} in {}", scripted.name(), COMPONENT_DIR);
+                    }
+                    scripted.set_definition(definition);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,550 @@
+//! A small expression language used to give data driven components their behaviour.\
+//! A program is a list of assignments separated by `;` or new lines, e.g.
+//! ```text
+//! sum = a + b
+//! out = sum > 255 ? 255 : sum
+//! ```
+//! Outputs and any other names keep their value from the last tick until they are assigned again, other names must be assigned before they are read.\
+//! Values are whole numbers. Comparisons and logic give 255 for true and 0 for false to match the signals gates use,
+//! anything other than 0 counts as true. Dividing by zero gives 0.\
+//! Supported in order of precedence, loosest first: `?:`, `||`, `&&`, `|`, `^`, `&`, `== !=`, `< <= > >=`, `<< >>`, `+ -`, `* / %`,
+//! then the unary `- ! ~`. Also the functions `min(a, b)`, `max(a, b)`, `abs(a)` and `clamp(a, low, high)`.\
+//! Numbers can be written in decimal, hex (`0xFF`) or binary (`0b1010`) and `#` starts a comment.
+use std::fmt;
+
+/// Signals treat anything 255 as HIGH so true is 255 rather than 1
+const TRUE: i64 = 255;
+
+#[derive(Debug, Clone)]
+pub struct ExpressionError {
+    /// Character the error was found at
+    pub position: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ExpressionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (at character {})", self.message, self.position)
+    }
+}
+
+impl ExpressionError {
+    fn new(position: usize, message: impl Into<String>) -> Self {
+        Self { position, message: message.into() }
+    }
+}
+
+/// A compiled program. Variables are stored in slots, the inputs come first, then the outputs, then anything else assigned to.
+#[derive(Debug, Clone, Default)]
+pub struct Program {
+    statements: Vec<(usize, Expr)>,
+    slot_count: usize,
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Number(i64),
+    Slot(usize),
+    Unary(UnaryOp, Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Conditional(Box<Expr>, Box<Expr>, Box<Expr>),
+    Call(Function, Vec<Expr>),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum UnaryOp {
+    Negate,
+    Not,
+    BitNot,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum BinaryOp {
+    Or,
+    And,
+    BitOr,
+    BitXor,
+    BitAnd,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    ShiftLeft,
+    ShiftRight,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+}
+
+/// Binary operators grouped by precedence, loosest first
+const PRECEDENCE: &[&[(&str, BinaryOp)]] = &[
+    &[("||", BinaryOp::Or)],
+    &[("&&", BinaryOp::And)],
+    &[("|", BinaryOp::BitOr)],
+    &[("^", BinaryOp::BitXor)],
+    &[("&", BinaryOp::BitAnd)],
+    &[("==", BinaryOp::Equal), ("!=", BinaryOp::NotEqual)],
+    &[("<", BinaryOp::Less), ("<=", BinaryOp::LessEqual), (">", BinaryOp::Greater), (">=", BinaryOp::GreaterEqual)],
+    &[("<<", BinaryOp::ShiftLeft), (">>", BinaryOp::ShiftRight)],
+    &[("+", BinaryOp::Add), ("-", BinaryOp::Subtract)],
+    &[("*", BinaryOp::Multiply), ("/", BinaryOp::Divide), ("%", BinaryOp::Remainder)],
+];
+
+#[derive(Debug, Clone, Copy)]
+enum Function {
+    Min,
+    Max,
+    Abs,
+    Clamp,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "min" => Some(Function::Min),
+            "max" => Some(Function::Max),
+            "abs" => Some(Function::Abs),
+            "clamp" => Some(Function::Clamp),
+            _ => None,
+        }
+    }
+
+    fn argument_count(self) -> usize {
+        match self {
+            Function::Min | Function::Max => 2,
+            Function::Abs => 1,
+            Function::Clamp => 3,
+        }
+    }
+}
+
+impl Program {
+    /// Parse `source`, `inputs` and `outputs` are the names of the component's ports.\
+    /// Inputs cannot be assigned to and every output must be.
+    pub fn compile(source: &str, inputs: &[String], outputs: &[String]) -> Result<Self, ExpressionError> {
+        let tokens = tokenise(source)?;
+        let mut names: Vec<String> = inputs.iter().chain(outputs.iter()).cloned().collect();
+        let mut parser = Parser { tokens, index: 0, names: &mut names };
+        let mut statements = Vec::new();
+        let mut assigned = vec![false; outputs.len()];
+
+        while !parser.at_end() {
+            if parser.eat(";") {continue}
+            let (position, name) = match parser.next() {
+                Some((position, Token::Identifier(name))) => (position, name),
+                Some((position, _)) => return Err(ExpressionError::new(position, "Expected the name of an output to assign to")),
+                None => break,
+            };
+            if inputs.contains(&name) {
+                return Err(ExpressionError::new(position, format!("Cannot assign to the input {}", name)));
+            }
+            if !parser.eat("=") {
+                return Err(ExpressionError::new(parser.position(), "Expected ="));
+            }
+            let expr = parser.expression()?;
+            let slot = parser.slot_for(&name);
+            if let Some(output) = outputs.iter().position(|o| *o == name) {
+                assigned[output] = true;
+            }
+            statements.push((slot, expr));
+            if !parser.at_end() && !parser.eat(";") {
+                return Err(ExpressionError::new(parser.position(), "Expected a new line or ; after the expression"));
+            }
+        }
+
+        if let Some(missing) = assigned.iter().position(|a| !a) {
+            return Err(ExpressionError::new(source.chars().count(), format!("The output {} is never set", outputs[missing])));
+        }
+        let slot_count = names.len();
+        Ok(Program { statements, slot_count })
+    }
+
+    /// How many slots [Self::run] needs
+    pub fn slot_count(&self) -> usize {
+        self.slot_count
+    }
+
+    /// Run every statement in order, `slots` should already hold the inputs
+    pub fn run(&self, slots: &mut [i64]) {
+        for (slot, expr) in &self.statements {
+            let value = expr.evaluate(slots);
+            slots[*slot] = value;
+        }
+    }
+}
+
+impl Expr {
+    fn evaluate(&self, slots: &[i64]) -> i64 {
+        match self {
+            Expr::Number(n) => *n,
+            Expr::Slot(slot) => slots[*slot],
+            Expr::Unary(op, inner) => {
+                let value = inner.evaluate(slots);
+                match op {
+                    UnaryOp::Negate => value.wrapping_neg(),
+                    UnaryOp::Not => truth(value == 0),
+                    UnaryOp::BitNot => !value,
+                }
+            },
+            Expr::Binary(op, left, right) => {
+                let a = left.evaluate(slots);
+                // Short circuit the logic operators
+                match op {
+                    BinaryOp::Or if a != 0 => return TRUE,
+                    BinaryOp::And if a == 0 => return 0,
+                    _ => {},
+                }
+                let b = right.evaluate(slots);
+                match op {
+                    BinaryOp::Or | BinaryOp::And => truth(b != 0),
+                    BinaryOp::BitOr => a | b,
+                    BinaryOp::BitXor => a ^ b,
+                    BinaryOp::BitAnd => a & b,
+                    BinaryOp::Equal => truth(a == b),
+                    BinaryOp::NotEqual => truth(a != b),
+                    BinaryOp::Less => truth(a < b),
+                    BinaryOp::LessEqual => truth(a <= b),
+                    BinaryOp::Greater => truth(a > b),
+                    BinaryOp::GreaterEqual => truth(a >= b),
+                    BinaryOp::ShiftLeft => a.checked_shl(b.clamp(0, 63) as u32).unwrap_or(0),
+                    BinaryOp::ShiftRight => a.checked_shr(b.clamp(0, 63) as u32).unwrap_or(0),
+                    BinaryOp::Add => a.wrapping_add(b),
+                    BinaryOp::Subtract => a.wrapping_sub(b),
+                    BinaryOp::Multiply => a.wrapping_mul(b),
+                    BinaryOp::Divide => a.checked_div(b).unwrap_or(0),
+                    BinaryOp::Remainder => a.checked_rem(b).unwrap_or(0),
+                }
+            },
+            Expr::Conditional(condition, then, otherwise) => {
+                match condition.evaluate(slots) != 0 {
+                    true => then.evaluate(slots),
+                    false => otherwise.evaluate(slots),
+                }
+            },
+            Expr::Call(function, arguments) => {
+                let values: Vec<i64> = arguments.iter().map(|a| a.evaluate(slots)).collect();
+                match function {
+                    Function::Min => values[0].min(values[1]),
+                    Function::Max => values[0].max(values[1]),
+                    Function::Abs => values[0].wrapping_abs(),
+                    Function::Clamp => values[0].max(values[1]).min(values[2]),
+                }
+            },
+        }
+    }
+}
+
+fn truth(value: bool) -> i64 {
+    if value {TRUE} else {0}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(i64),
+    Identifier(String),
+    Symbol(&'static str),
+}
+
+/// Longer symbols first so `<<` is not read as two `<`
+const SYMBOLS: &[&str] = &[
+    "<<", ">>", "<=", ">=", "==", "!=", "&&", "||",
+    "+", "-", "*", "/", "%", "&", "|", "^", "~", "!", "<", ">", "(", ")", "?", ":", ",", "=", ";",
+];
+
+fn tokenise(source: &str) -> Result<Vec<(usize, Token)>, ExpressionError> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '\n' {
+            tokens.push((i, Token::Symbol(";")));
+            i += 1;
+        } else if c.is_whitespace() {
+            i += 1;
+        } else if c == '#' { // Comment until the end of the line
+            while i < chars.len() && chars[i] != '\n' {i += 1}
+        } else if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {i += 1}
+            let text: String = chars[start..i].iter().filter(|c| **c != '_').collect();
+            let parsed = match text.get(..2) {
+                Some("0x") | Some("0X") => i64::from_str_radix(&text[2..], 16),
+                Some("0b") | Some("0B") => i64::from_str_radix(&text[2..], 2),
+                _ => text.parse::<i64>(),
+            };
+            match parsed {
+                Ok(n) => tokens.push((start, Token::Number(n))),
+                Err(_) => return Err(ExpressionError::new(start, format!("{} is not a number", text))),
+            }
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {i += 1}
+            tokens.push((start, Token::Identifier(chars[start..i].iter().collect())));
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+            match SYMBOLS.iter().find(|s| rest.starts_with(**s)) {
+                Some(symbol) => {
+                    tokens.push((i, Token::Symbol(*symbol)));
+                    i += symbol.len();
+                },
+                None => return Err(ExpressionError::new(i, format!("Unexpected character {}", c))),
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: Vec<(usize, Token)>,
+    index: usize,
+    /// Names of every slot, locals are added as they are assigned to
+    names: &'a mut Vec<String>,
+}
+
+impl<'a> Parser<'a> {
+    fn at_end(&self) -> bool {
+        self.index >= self.tokens.len()
+    }
+
+    fn position(&self) -> usize {
+        self.tokens.get(self.index).or(self.tokens.last()).map(|(p, _)| *p).unwrap_or(0)
+    }
+
+    fn next(&mut self) -> Option<(usize, Token)> {
+        let token = self.tokens.get(self.index).cloned();
+        self.index += 1;
+        token
+    }
+
+    fn peek_symbol(&self) -> Option<&'static str> {
+        match self.tokens.get(self.index) {
+            Some((_, Token::Symbol(s))) => Some(s),
+            _ => None,
+        }
+    }
+
+    fn eat(&mut self, symbol: &str) -> bool {
+        if self.peek_symbol() == Some(symbol) {
+            self.index += 1;
+            return true;
+        }
+        false
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<(), ExpressionError> {
+        match self.eat(symbol) {
+            true => Ok(()),
+            false => Err(ExpressionError::new(self.position(), format!("Expected {}", symbol))),
+        }
+    }
+
+    fn slot_for(&mut self, name: &str) -> usize {
+        match self.names.iter().position(|n| n == name) {
+            Some(slot) => slot,
+            None => {
+                self.names.push(name.to_string());
+                self.names.len() - 1
+            },
+        }
+    }
+
+    fn expression(&mut self) -> Result<Expr, ExpressionError> {
+        let condition = self.binary(0)?;
+        if self.eat("?") {
+            let then = self.expression()?;
+            self.expect(":")?;
+            let otherwise = self.expression()?;
+            return Ok(Expr::Conditional(Box::new(condition), Box::new(then), Box::new(otherwise)));
+        }
+        Ok(condition)
+    }
+
+    fn binary(&mut self, level: usize) -> Result<Expr, ExpressionError> {
+        if level >= PRECEDENCE.len() {
+            return self.unary();
+        }
+        let mut left = self.binary(level + 1)?;
+        loop {
+            let op = match self.peek_symbol().and_then(|s| PRECEDENCE[level].iter().find(|(symbol, _)| *symbol == s)) {
+                Some((_, op)) => *op,
+                None => return Ok(left),
+            };
+            self.index += 1;
+            let right = self.binary(level + 1)?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, ExpressionError> {
+        let op = match self.peek_symbol() {
+            Some("-") => UnaryOp::Negate,
+            Some("!") => UnaryOp::Not,
+            Some("~") => UnaryOp::BitNot,
+            _ => return self.primary(),
+        };
+        self.index += 1;
+        Ok(Expr::Unary(op, Box::new(self.unary()?)))
+    }
+
+    fn primary(&mut self) -> Result<Expr, ExpressionError> {
+        let position = self.position();
+        match self.next() {
+            Some((_, Token::Number(n))) => Ok(Expr::Number(n)),
+            Some((_, Token::Symbol("("))) => {
+                let inner = self.expression()?;
+                self.expect(")")?;
+                Ok(inner)
+            },
+            Some((_, Token::Identifier(name))) => {
+                if let Some(function) = Function::from_name(&name) {
+                    self.expect("(")?;
+                    let mut arguments = Vec::new();
+                    if !self.eat(")") {
+                        loop {
+                            arguments.push(self.expression()?);
+                            if self.eat(")") {break}
+                            self.expect(",")?;
+                        }
+                    }
+                    if arguments.len() != function.argument_count() {
+                        return Err(ExpressionError::new(position, format!("{} takes {} values", name, function.argument_count())));
+                    }
+                    return Ok(Expr::Call(function, arguments));
+                }
+                match self.names.iter().position(|n| *n == name) {
+                    Some(slot) => Ok(Expr::Slot(slot)),
+                    None => Err(ExpressionError::new(position, format!("Unknown name {}", name))),
+                }
+            },
+            Some((_, token)) => Err(ExpressionError::new(position, format!("Unexpected {:?}", token))),
+            None => Err(ExpressionError::new(position, "Expected a value")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    /// Run `source` once with the inputs given and return `out`
+    fn run(source: &str, inputs: &[(&str, i64)]) -> i64 {
+        let input_names: Vec<&str> = inputs.iter().map(|(name, _)| *name).collect();
+        let program = Program::compile(source, &names(&input_names), &names(&["out"])).expect("The program compiles");
+        let mut slots = vec![0; program.slot_count()];
+        for (slot, (_, value)) in inputs.iter().enumerate() {
+            slots[slot] = *value;
+        }
+        program.run(&mut slots);
+        slots[inputs.len()]
+    }
+
+    fn compile_error(source: &str, inputs: &[&str]) -> ExpressionError {
+        Program::compile(source, &names(inputs), &names(&["out"])).expect_err("The program does not compile")
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(run("out = 1 + 2 * 3", &[]), 7);
+        assert_eq!(run("out = (1 + 2) * 3", &[]), 9);
+        assert_eq!(run("out = 1 << 2 < 5", &[]), TRUE);
+        assert_eq!(run("out = 1 < 2 << 3", &[]), TRUE);
+        assert_eq!(run("out = 6 & 3 == 3", &[]), 6 & TRUE);
+        assert_eq!(run("out = 1 | 2 ^ 3 & 4", &[]), 1 | (2 ^ (3 & 4)));
+        assert_eq!(run("out = 2 + 3 == 5 && 4 > 3", &[]), TRUE);
+        assert_eq!(run("out = -2 * -3", &[]), 6);
+        assert_eq!(run("out = !0 + 1", &[]), TRUE + 1);
+    }
+
+    #[test]
+    fn associativity() {
+        assert_eq!(run("out = 10 - 4 - 3", &[]), 3);
+        assert_eq!(run("out = 100 / 10 / 5", &[]), 2);
+        assert_eq!(run("out = 256 >> 2 >> 1", &[]), 32);
+        assert_eq!(run("out = 0 ? 1 : 2 ? 3 : 4", &[]), 3);
+        assert_eq!(run("out = 1 ? 2 ? 3 : 4 : 5", &[]), 3);
+    }
+
+    /// Nothing has side effects, so this checks each side of the logic operators gives the right value
+    #[test]
+    fn logic_short_circuits() {
+        assert_eq!(run("out = 5 || 0", &[]), TRUE);
+        assert_eq!(run("out = 0 || 7", &[]), TRUE);
+        assert_eq!(run("out = 0 || 0", &[]), 0);
+        assert_eq!(run("out = 0 && 1 / 0", &[]), 0);
+        assert_eq!(run("out = 3 && 7", &[]), TRUE);
+        assert_eq!(run("out = 3 && 0", &[]), 0);
+        assert_eq!(run("out = a != 0 && 100 / a > 10", &[("a", 0)]), 0);
+        assert_eq!(run("out = a != 0 && 100 / a > 10", &[("a", 5)]), TRUE);
+    }
+
+    #[test]
+    fn dividing_by_zero_gives_zero() {
+        assert_eq!(run("out = 7 / 0", &[]), 0);
+        assert_eq!(run("out = 7 % 0", &[]), 0);
+        assert_eq!(run("out = a / b", &[("a", i64::MIN), ("b", -1)]), 0);
+        assert_eq!(run("out = 7 % 3", &[]), 1);
+    }
+
+    #[test]
+    fn arithmetic_wraps() {
+        assert_eq!(run("out = a + 1", &[("a", i64::MAX)]), i64::MIN);
+        assert_eq!(run("out = a - 1", &[("a", i64::MIN)]), i64::MAX);
+        assert_eq!(run("out = a * 2", &[("a", i64::MAX)]), -2);
+        assert_eq!(run("out = -a", &[("a", i64::MIN)]), i64::MIN);
+        assert_eq!(run("out = abs(a)", &[("a", i64::MIN)]), i64::MIN);
+        assert_eq!(run("out = 1 << 64", &[]), 1 << 63);
+    }
+
+    #[test]
+    fn number_literals() {
+        assert_eq!(run("out = 0xFF", &[]), 255);
+        assert_eq!(run("out = 0Xff", &[]), 255);
+        assert_eq!(run("out = 0b1010", &[]), 10);
+        assert_eq!(run("out = 0b1111_0000", &[]), 240);
+        assert_eq!(run("out = 1_000", &[]), 1000);
+        assert_eq!(compile_error("out = 0xZZ", &[]).message, "0xZZ is not a number");
+    }
+
+    #[test]
+    fn locals_and_outputs_keep_their_value() {
+        let program = Program::compile("total = out + a\nout = total", &names(&["a"]), &names(&["out"])).unwrap();
+        let mut slots = vec![0; program.slot_count()];
+        for value in [1, 2, 3] {
+            slots[0] = value;
+            program.run(&mut slots);
+        }
+        assert_eq!(slots[1], 6);
+    }
+
+    #[test]
+    fn cannot_assign_to_an_input() {
+        let error = compile_error("a = 1\nout = a", &["a"]);
+        assert_eq!(error.message, "Cannot assign to the input a");
+        assert_eq!(error.position, 0);
+    }
+
+    #[test]
+    fn unknown_name() {
+        let error = compile_error("out = b + 1", &["a"]);
+        assert_eq!(error.message, "Unknown name b");
+        assert_eq!(error.position, 6);
+        assert_eq!(compile_error("x = x + 1\nout = x", &[]).message, "Unknown name x");
+    }
+
+    #[test]
+    fn output_never_set() {
+        let source = "x = 1 # é";
+        let error = compile_error(source, &[]);
+        assert_eq!(error.message, "The output out is never set");
+        assert_eq!(error.position, source.chars().count());
+    }
+}
@@ -0,0 +1,185 @@
+//! Level inputs and expected outputs can be written out in full or described and expanded when the level loads, e.g.
+//! ```json
+//! "provided_inputs": {"input": {"generator": "repeat", "pattern": [1, 254, 144, 5], "times": 25}},
+//! "expected_outputs": {"output": {"reference": "output = 255 - input"}}
+//! ```
+use bevy::utils::HashMap;
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{de::Error, Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use super::expression::{ExpressionError, Program};
+
+/// Name a reference can read to get how many values have come before, counting from 0
+pub const STEP_NAME: &str = "step";
+
+/// A generator which gives the same values for the same seed on every machine.\
+/// [ChaCha8Rng] is documented as value-stable, and the seed is used as it is rather than through `rand`'s helpers,
+/// so a new version of `rand` does not change level inputs or the scores measured on them.
+pub fn seeded_rng(seed: u64) -> ChaCha8Rng {
+    let mut bytes = [0; 32];
+    bytes[..8].copy_from_slice(&seed.to_le_bytes());
+    ChaCha8Rng::from_seed(bytes)
+}
+
+/// A value between `min` and `max` (inclusive, either way round), worked out here for the same reason as [seeded_rng]
+pub fn random_between(rng: &mut ChaCha8Rng, min: u8, max: u8) -> u8 {
+    let (min, max) = (min.min(max), max.max(min));
+    let span = (max - min) as u64 + 1;
+    min + ((rng.next_u32() as u64 * span) >> 32) as u8
+}
+
+/// Either the values written out in full or an [InputGenerator]
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum InputSource {
+    Values(Vec<u8>),
+    Generated(InputGenerator),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "generator", rename_all = "snake_case")]
+pub enum InputGenerator {
+    /// `count` values between `min` and `max` (inclusive), the same every time for the same seed
+    Random { seed: u64, min: u8, max: u8, count: usize },
+    /// `count` values going up by `step` from `start`, wrapping past 255 and below 0
+    Sequence { start: u8, step: i16, count: usize },
+    /// `pattern` repeated `times` times
+    Repeat { pattern: Vec<u8>, times: usize },
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum OutputSource {
+    Values(Vec<u8>),
+    /// A program, in the language from [super::expression], run once for each input value.
+    /// It reads the inputs by their ids and [STEP_NAME], and assigns to the output's id.
+    /// The output can be read too, before it is assigned it still holds the value from the step before.\
+    /// Observers only record values other than 0, so steps where the output is 0 are left out.
+    Reference { reference: String },
+}
+
+/// The object form of [OutputSource::Reference], kept seperate so a typo in it gets serde's own error
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ReferenceLoad {
+    reference: String,
+}
+
+// Not `#[serde(untagged)]` as that only says the data matched no variant.
+// An array is always values, anything else must be the other form so its errors are the ones worth showing.
+impl<'de> Deserialize<'de> for InputSource {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = Value::deserialize(deserializer)?;
+        if value.is_array() {
+            Vec::deserialize(value).map(InputSource::Values).map_err(D::Error::custom)
+        } else {
+            InputGenerator::deserialize(value).map(InputSource::Generated).map_err(D::Error::custom)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for OutputSource {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = Value::deserialize(deserializer)?;
+        if value.is_array() {
+            Vec::deserialize(value).map(OutputSource::Values).map_err(D::Error::custom)
+        } else {
+            ReferenceLoad::deserialize(value).map(|load| OutputSource::Reference { reference: load.reference }).map_err(D::Error::custom)
+        }
+    }
+}
+
+impl InputSource {
+    pub fn generate(&self) -> Vec<u8> {
+        match self {
+            InputSource::Values(values) => values.clone(),
+            InputSource::Generated(InputGenerator::Random { seed, min, max, count }) => {
+                let mut rng = seeded_rng(*seed);
+                (0..*count).map(|_| random_between(&mut rng, *min, *max)).collect()
+            },
+            InputSource::Generated(InputGenerator::Sequence { start, step, count }) => {
+                (0..*count).map(|i| (*start as i64 + *step as i64 * i as i64).rem_euclid(256) as u8).collect()
+            },
+            InputSource::Generated(InputGenerator::Repeat { pattern, times }) => pattern.repeat(*times),
+        }
+    }
+}
+
+impl OutputSource {
+    /// Work out the expected values for the output `id` from the already generated inputs
+    pub fn generate(&self, id: &str, inputs: &HashMap<String, Vec<u8>>) -> Result<Vec<u8>, ExpressionError> {
+        let source = match self {
+            OutputSource::Values(values) => return Ok(values.clone()),
+            OutputSource::Reference { reference } => reference,
+        };
+
+        // Sorted so the slots are the same every load
+        let mut input_ids: Vec<&String> = inputs.keys().collect();
+        input_ids.sort();
+        let mut input_names: Vec<String> = input_ids.iter().map(|id| id.to_string()).collect();
+        input_names.push(STEP_NAME.to_string());
+        let program = Program::compile(source, &input_names, &[id.to_string()])?;
+
+        let steps = inputs.values().map(|v| v.len()).max().unwrap_or(0);
+        let output_slot = input_names.len();
+        let mut slots = vec![0; program.slot_count()];
+        let mut expected = Vec::new();
+        for step in 0..steps {
+            for (slot, id) in input_ids.iter().enumerate() {
+                slots[slot] = inputs[*id].get(step).copied().unwrap_or(0) as i64;
+            }
+            slots[input_ids.len()] = step as i64;
+            program.run(&mut slots);
+            let value = slots[output_slot].clamp(0, 255) as u8;
+            if value != 0 {
+                expected.push(value);
+            }
+        }
+        Ok(expected)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_values() {
+        let generator = InputSource::Generated(InputGenerator::Random { seed: 42, min: 0, max: 255, count: 50 });
+        assert_eq!(generator.generate(), generator.generate());
+    }
+
+    #[test]
+    fn random_between_stays_in_range() {
+        let mut rng = seeded_rng(7);
+        for _ in 0..1000 {
+            let value = random_between(&mut rng, 200, 10);
+            assert!((10..=200).contains(&value));
+        }
+        assert_eq!(random_between(&mut rng, 5, 5), 5);
+        assert!((0..1000).any(|_| random_between(&mut rng, 0, 255) == 255));
+    }
+
+    #[test]
+    fn sources_load_both_forms() {
+        let values: InputSource = serde_json::from_str("[1, 2, 3]").unwrap();
+        assert_eq!(values.generate(), vec![1, 2, 3]);
+        let repeat: InputSource = serde_json::from_str(r#"{"generator": "repeat", "pattern": [4, 5], "times": 2}"#).unwrap();
+        assert_eq!(repeat.generate(), vec![4, 5, 4, 5]);
+        let reference: OutputSource = serde_json::from_str(r#"{"reference": "output = input"}"#).unwrap();
+        assert!(matches!(reference, OutputSource::Reference { .. }));
+    }
+
+    #[test]
+    fn source_errors_name_the_problem() {
+        let unknown = serde_json::from_str::<InputSource>(r#"{"generator": "shuffle", "count": 3}"#).unwrap_err().to_string();
+        assert!(unknown.contains("shuffle"), "{}", unknown);
+        let missing = serde_json::from_str::<InputSource>(r#"{"generator": "repeat", "pattern": [1]}"#).unwrap_err().to_string();
+        assert!(missing.contains("times"), "{}", missing);
+        let out_of_range = serde_json::from_str::<InputSource>("[1, 300]").unwrap_err().to_string();
+        assert!(out_of_range.contains("300"), "{}", out_of_range);
+        let typo = serde_json::from_str::<OutputSource>(r#"{"refrence": "output = input"}"#).unwrap_err().to_string();
+        assert!(typo.contains("refrence"), "{}", typo);
+    }
+}
@@ -1,10 +1,11 @@
-use bevy::{prelude::{UVec2, Vec2, Commands, Entity, Transform, Name}, reflect::{FromReflect, Reflect}, sprite::{SpriteSheetBundle, TextureAtlasSprite}};
+use bevy::{prelude::{UVec2, Vec2, Vec3, Quat, Color, Commands, Entity, Transform, Name, World, Assets, Handle, Component, Children, BuildWorldChildren, BuildChildren, With}, reflect::{FromReflect, Reflect}, sprite::{SpriteSheetBundle, TextureAtlasSprite, TextureAtlas, Anchor}, ui::FocusPolicy};
 use bevy_mod_picking::{events::EventListener, prelude::PointerClick};
+use bevy_prototype_lyon::{prelude::{GeometryBuilder, ShapeBundle, Stroke}, shapes::{self, RectangleOrigin}};
 use enum_map::Enum;
 use serde::{Serialize, Deserialize};
 use strum_macros::EnumIter;
 use crate::{game::{GRID_CELL_SIZE, GameRoot}, components::placement::GridLink, MainTextureAtlas};
-use super::{model::{DummyComponent}, interactions::GridComponentClick};
+use super::{model::Component, interactions::GridComponentClick};
 
 pub const UI_DISABLED_MSG: &'static str = "Disabled due to simulation";
 
@@ -35,6 +36,14 @@ impl Side {
         }
     }
 
+    /// Index of the axis this side lies on, 0 for up / down and 1 for left / right
+    pub fn axis(self) -> usize {
+        match self {
+            Side::Up | Side::Down => 0,
+            Side::Left | Side::Right => 1,
+        }
+    }
+
     pub fn reverse(self) -> Self {
         match self {
             Side::Up => Side::Down,
@@ -43,6 +52,120 @@ impl Side {
             Side::Right => Side::Left,
         }
     }
+
+    pub fn rotate_clockwise(self) -> Self {
+        match self {
+            Side::Up => Side::Right,
+            Side::Right => Side::Down,
+            Side::Down => Side::Left,
+            Side::Left => Side::Up,
+        }
+    }
+
+    pub fn rotate_anticlockwise(self) -> Self {
+        match self {
+            Side::Up => Side::Left,
+            Side::Left => Side::Down,
+            Side::Down => Side::Right,
+            Side::Right => Side::Up,
+        }
+    }
+
+    /// Flip left to right, up and down stay the same
+    pub fn mirror(self) -> Self {
+        match self {
+            Side::Left => Side::Right,
+            Side::Right => Side::Left,
+            other => other,
+        }
+    }
+}
+
+/// Which way a component has been turned on the grid.\
+/// Mirroring (left to right) happens first, then `rotation` clockwise quarter turns.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect, FromReflect, Serialize, Deserialize)]
+pub struct Orientation {
+    /// Clockwise quarter turns, 0 - 3
+    pub rotation: u8,
+    pub mirrored: bool,
+}
+
+impl Orientation {
+    pub fn rotate_clockwise(self) -> Self {
+        Self { rotation: (self.rotation + 1) % 4, ..self }
+    }
+
+    pub fn mirror(self) -> Self {
+        Self { mirrored: !self.mirrored, ..self }
+    }
+
+    /// If the width and height of the component are swapped
+    pub fn is_sideways(&self) -> bool {
+        self.rotation % 2 == 1
+    }
+
+    /// Size in grid cells after being turned, `size` is the unturned size
+    pub fn grid_size(&self, size: [usize; 2]) -> [usize; 2] {
+        match self.is_sideways() {
+            true => [size[1], size[0]],
+            false => size,
+        }
+    }
+
+    /// Size in pixels after being turned, `size` is the unturned size
+    pub fn pixel_size(&self, size: Vec2) -> Vec2 {
+        match self.is_sideways() {
+            true => Vec2::new(size.y, size.x),
+            false => size,
+        }
+    }
+
+    /// Move a port from the component's own layout onto the grid.\
+    /// `size` is the unturned grid size of the component.
+    pub fn apply(&self, offset: [usize; 2], side: Side, size: [usize; 2]) -> ([usize; 2], Side) {
+        let (mut offset, mut side, mut size) = (offset, side, size);
+        if self.mirrored {
+            offset[0] = size[0] - 1 - offset[0];
+            side = side.mirror();
+        }
+        for _ in 0..self.rotation % 4 {
+            offset = [offset[1], size[0] - 1 - offset[0]];
+            side = side.rotate_clockwise();
+            size = [size[1], size[0]];
+        }
+        (offset, side)
+    }
+
+    /// The reverse of [Self::apply], takes a port on the grid back to the component's own layout.\
+    /// `size` is the unturned grid size of the component.
+    pub fn invert(&self, offset: [usize; 2], side: Side, size: [usize; 2]) -> ([usize; 2], Side) {
+        let (mut offset, mut side, mut size) = (offset, side, self.grid_size(size));
+        for _ in 0..self.rotation % 4 {
+            offset = [size[1] - 1 - offset[1], offset[0]];
+            side = side.rotate_anticlockwise();
+            size = [size[1], size[0]];
+        }
+        if self.mirrored {
+            offset[0] = size[0] - 1 - offset[0];
+            side = side.mirror();
+        }
+        (offset, side)
+    }
+
+    /// Rotation for the sprite's [Transform], mirroring is done with [TextureAtlasSprite::flip_x]
+    pub fn sprite_rotation(&self) -> Quat {
+        Quat::from_rotation_z(-std::f32::consts::FRAC_PI_2 * (self.rotation % 4) as f32)
+    }
+
+    /// Turning a bottom left anchored sprite swings it around its corner.\
+    /// This is how far to move it so it covers its grid cells again, `size` is the unturned size in pixels.
+    pub fn sprite_offset(&self, size: Vec2) -> Vec2 {
+        let rotation = self.sprite_rotation();
+        [Vec2::ZERO, Vec2::new(size.x, 0.0), Vec2::new(0.0, size.y), size].iter()
+            .map(|corner| (rotation * corner.extend(0.0)).truncate())
+            .fold(Vec2::splat(f32::MAX), |min, corner| min.min(corner))
+            .round() * -1.0
+    }
 }
 
 pub fn combine_offset(pos: &[usize; 2], offset: &[isize; 2]) -> Option<[usize; 2]> {
@@ -108,19 +231,13 @@ pub fn calc_grid_pos(grid_bottom_left: &Vec2, pos_in_grid: &UVec2) -> Vec2 {
     pos
 }
 
-pub fn spawn_component_sprite(commands: &mut Commands, sprite: TextureAtlasSprite, grid_bottom_left: &Vec2, position: &[usize; 2], main_atlas: &MainTextureAtlas, dummy_component: DummyComponent) -> Entity {
+pub fn spawn_component_sprite(commands: &mut Commands, mut sprite: TextureAtlasSprite, grid_bottom_left: &Vec2, position: &[usize; 2], main_atlas: &MainTextureAtlas, component: &Component, orientation: Orientation) -> Entity {
+    sprite.flip_x = orientation.mirrored;
     commands
         .spawn((
             SpriteSheetBundle {
                 sprite,
-                transform: Transform {
-                    translation: calc_grid_pos(
-                        grid_bottom_left,
-                        &UVec2::new(position[0] as u32, position[1] as u32),
-                    )
-                    .extend(11.0),
-                    ..Default::default()
-                },
+                transform: component_transform(grid_bottom_left, position, component.size(), orientation),
                 texture_atlas: main_atlas.handle.clone(),
                 ..Default::default()
             },
@@ -128,9 +245,194 @@ pub fn spawn_component_sprite(commands: &mut Commands, sprite: TextureAtlasSprit
             GridLink(*position),
             Name::new(format!(
                 "Component - {}",
-                dummy_component.get_sprite_name()
+                component.dummy().get_sprite_name()
             )),
             EventListener::<PointerClick>::new_forward_event::<GridComponentClick>()
         ))
         .id()
-}
\ No newline at end of file
+}
+
+/// Outline a locked component so the player can tell it apart, a child of its sprite so it turns and despawns with it.\
+/// `size` is the unturned size in pixels
+pub fn spawn_locked_border(commands: &mut Commands, sprite: Entity, size: Vec2) {
+    let shape = shapes::Rectangle {
+        extents: size,
+        origin: RectangleOrigin::BottomLeft,
+    };
+    let border = commands.spawn((
+        ShapeBundle {
+            path: GeometryBuilder::build_as(&shape),
+            transform: Transform::from_translation(Vec3::new(0.0, 0.0, 1.0)),
+            ..Default::default()
+        },
+        Stroke::new(Color::ORANGE, 1.0),
+        Name::new("Locked border"),
+    )).id();
+    commands.entity(sprite).add_child(border);
+}
+
+/// Where the sprite of a component should be to cover its grid cells after being turned
+/// `size` is the unturned size in pixels
+pub fn component_transform(grid_bottom_left: &Vec2, position: &[usize; 2], size: Vec2, orientation: Orientation) -> Transform {
+    let bottom_left = calc_grid_pos(grid_bottom_left, &UVec2::new(position[0] as u32, position[1] as u32));
+    Transform {
+        translation: (bottom_left + orientation.sprite_offset(size)).extend(11.0),
+        rotation: orientation.sprite_rotation(),
+        ..Default::default()
+    }
+}
+
+/// Change the sprite of the component at `pos` by atlas name.\
+/// For use inside of [super::model::GridComponent::tick] where we only have access to the [World].
+/// Does nothing when the component has no sprite, e.g. while a test case runs in a world of its own
+pub fn set_sprite_from_world(world: &mut World, pos: &[usize; 2], sprite_name: &str) {
+    let mut links = world.query_filtered::<(Entity, &GridLink), With<TextureAtlasSprite>>();
+    let entities: Vec<Entity> = links.iter(world).filter(|(_, link)| link.0 == *pos).map(|(entity, _)| entity).collect();
+    if entities.is_empty() {return}
+
+    let index = {
+        let atlas = match atlas_from_world(world) {
+            Some(atlas) => atlas,
+            None => return,
+        };
+        match atlas.get_texture_index(&Handle::weak(sprite_name.into())) {
+            Some(idx) => idx,
+            None => panic!("Attempted to load none existent texture {}", sprite_name),
+        }
+    };
+
+    for entity in entities {
+        if let Some(mut sprite) = world.get_mut::<TextureAtlasSprite>(entity) {
+            sprite.index = index;
+        }
+    }
+}
+
+/// The main texture atlas, `None` if the world has no assets loaded
+fn atlas_from_world(world: &World) -> Option<&TextureAtlas> {
+    let main_atlas = world.get_resource::<MainTextureAtlas>()?;
+    world.get_resource::<Assets<TextureAtlas>>()?.get(&main_atlas.handle)
+}
+
+/// Marks a sprite drawn on top of a component, e.g. a single segment of a display.\
+/// Stores its index into the list given to [set_overlay_sprites]
+#[derive(Component, Debug, Clone, Copy)]
+pub struct SpriteOverlay(pub usize);
+
+/// Set the sprites drawn on top of the component at `pos`, spawning them as children the first time.\
+/// Each overlay is an offset from the bottom left of the component and the atlas name to use.\
+/// For use inside of [super::model::GridComponent::tick] where we only have access to the [World]
+pub fn set_overlay_sprites(world: &mut World, pos: &[usize; 2], overlays: &[(Vec2, &str)]) {
+    let mut components = world.query::<(Entity, &GridLink, &TextureAtlasSprite, Option<&Children>)>();
+    let found = components.iter(world).find(|(_, link, _, _)| link.0 == *pos).map(|(entity, _, sprite, children)| {
+        (entity, sprite.index, sprite.flip_x, children.map(|c| c.to_vec()).unwrap_or_default())
+    });
+    let (parent, parent_index, flipped, children) = match found {
+        Some(f) => f,
+        None => return,
+    };
+
+    let atlas_handle = match world.get_resource::<MainTextureAtlas>() {
+        Some(main_atlas) => main_atlas.handle.clone(),
+        None => return,
+    };
+    let (indices, offsets) = {
+        let atlas = match atlas_from_world(world) {
+            Some(atlas) => atlas,
+            None => return,
+        };
+        let indices: Vec<usize> = overlays.iter().map(|(_, sprite_name)| {
+            match atlas.get_texture_index(&Handle::weak((*sprite_name).into())) {
+                Some(idx) => idx,
+                None => panic!("Attempted to load none existent texture {}", sprite_name),
+            }
+        }).collect();
+        // A mirrored component needs its overlays mirrored aswell as they do not inherit the flip
+        let parent_width = atlas.textures[parent_index].width();
+        let offsets: Vec<Vec2> = overlays.iter().zip(indices.iter()).map(|((offset, _), idx)| {
+            match flipped {
+                true => Vec2::new(parent_width - offset.x - atlas.textures[*idx].width(), offset.y),
+                false => *offset,
+            }
+        }).collect();
+        (indices, offsets)
+    };
+
+    // Update the overlays which already exist
+    let mut existing = vec![false; overlays.len()];
+    let mut overlay_sprites = world.query::<(&SpriteOverlay, &mut TextureAtlasSprite, &mut Transform)>();
+    for child in children {
+        if let Ok((overlay, mut sprite, mut transform)) = overlay_sprites.get_mut(world, child) {
+            if let Some(idx) = indices.get(overlay.0) {
+                sprite.index = *idx;
+                sprite.flip_x = flipped;
+                transform.translation = offsets[overlay.0].extend(0.5);
+                existing[overlay.0] = true;
+            }
+        }
+    }
+
+    // Spawn any which are missing
+    for (i, (offset, idx)) in offsets.into_iter().zip(indices).enumerate() {
+        if existing[i] {continue;}
+        let mut sprite = TextureAtlasSprite::new(idx);
+        sprite.anchor = Anchor::BottomLeft;
+        sprite.flip_x = flipped;
+        let child = world.spawn((
+            SpriteSheetBundle {
+                sprite,
+                transform: Transform::from_translation(offset.extend(0.5)),
+                texture_atlas: atlas_handle.clone(),
+                ..Default::default()
+            },
+            SpriteOverlay(i),
+            FocusPolicy::Pass, // Let clicks through to the component underneath
+            Name::new("Component overlay"),
+        )).id();
+        world.entity_mut(parent).add_child(child);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use strum::IntoEnumIterator;
+
+    use super::*;
+
+    /// Every rotation with and without mirroring
+    fn orientations() -> impl Iterator<Item = Orientation> {
+        (0..4).flat_map(|rotation| [false, true].map(|mirrored| Orientation { rotation, mirrored }))
+    }
+
+    #[test]
+    fn invert_undoes_apply() {
+        for size in [[1, 1], [2, 1], [1, 2], [3, 2], [2, 3]] {
+            for orientation in orientations() {
+                let turned_size = orientation.grid_size(size);
+                for x in 0..size[0] {
+                    for y in 0..size[1] {
+                        for side in Side::iter() {
+                            let (offset, turned_side) = orientation.apply([x, y], side, size);
+                            assert!(offset[0] < turned_size[0] && offset[1] < turned_size[1], "{:?} on {:?} moved [{}, {}] off the component", orientation, size, x, y);
+                            assert_eq!(orientation.invert(offset, turned_side, size), ([x, y], side), "{:?} on {:?}", orientation, size);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn apply_turns_clockwise_after_mirroring() {
+        let size = [2, 1];
+        let quarter = Orientation { rotation: 1, mirrored: false };
+        assert_eq!(quarter.apply([1, 0], Side::Right, size), ([0, 0], Side::Down));
+        assert_eq!(quarter.apply([0, 0], Side::Left, size), ([0, 1], Side::Up));
+
+        let mirrored = Orientation { rotation: 0, mirrored: true };
+        assert_eq!(mirrored.apply([0, 0], Side::Left, size), ([1, 0], Side::Right));
+
+        let both = Orientation { rotation: 1, mirrored: true };
+        assert_eq!(both.apply([0, 0], Side::Left, size), ([0, 0], Side::Down));
+    }
+}
@@ -10,10 +10,12 @@ use crate::game::PlacementGridEntity;
 use crate::GameState;
 use crate::components::placement::GridLink;
 use crate::components::placement::Size as SizeComponent;
+use super::helpers::Orientation;
 use crate::ui::shared::drag_v2;
 use super::helpers;
 use super::helpers::calc_grid_pos;
-use super::model::{SimulationData, CellState};
+use super::model::{SimulationData, CellState, GridComponent, PortGridError};
+use super::run::SimState;
 
 pub struct GridComponentInteractionPlugin;
 
@@ -22,6 +24,7 @@ impl Plugin for GridComponentInteractionPlugin {
         app.add_event::<GridComponentClick>()
         .add_event::<GridClick>()
         .add_event::<UpdateComponentEvent>()
+        .add_event::<OrientComponentEvent>()
         .init_resource::<SelectedComponent>()
         .add_systems((
             drag_v2,
@@ -29,6 +32,7 @@ impl Plugin for GridComponentInteractionPlugin {
             show_activated_icon,
             grid_click_disable,
             update_component_listener,
+            orient_component_listener,
         ).distributive_run_if(in_state(GameState::InGame)));
     }
 }
@@ -49,10 +53,24 @@ impl GridComponentClick {
         mut close: EventReader<GridComponentClick>,
         grid_component: Query<(Entity, &GridLink)>,
         mut selected_component: ResMut<SelectedComponent>,
+        sim_state: Res<State<SimState>>,
+        mut sim_data: ResMut<SimulationData>,
+        mut component_sprites: Query<&mut TextureAtlasSprite, With<GridLink>>,
+        atlases: Res<Assets<TextureAtlas>>,
+        main_atlas: Res<MainTextureAtlas>,
     ) {
+        let atlas = atlases.get(&main_atlas.handle).unwrap();
         for event in close.iter() {
             if let Ok((_, link)) = grid_component.get(event.entity) {
                 selected_component.0 = Some(link.0);
+                // Switches, buttons etc. can only be used while the simulation is running
+                if sim_state.0 == SimState::Active || sim_state.0 == SimState::Paused {
+                    if let CellState::Real(entity, component, _) = &mut sim_data.grid.grid[link.0[0]][link.0[1]] {
+                        if let Ok(mut sprite) = component_sprites.get_mut(*entity) {
+                            component.interact(sprite.as_mut(), atlas);
+                        }
+                    }
+                }
             }
         }
     }
@@ -74,9 +92,9 @@ fn show_activated_icon(
 
     if let Some(position) = selected_component.0 {
         let cell = &sim_data.grid.grid[position[0]][position[1]];
-        if let CellState::Real(_, c) = cell {
+        if let CellState::Real(_, c, orientation) = cell {
             if let Ok(mut shape) = outline.get_single_mut() {
-                let size = c.dummy().get_size();
+                let size = orientation.pixel_size(c.size());
 
                 shape.0.translation = calc_grid_pos(&grid_bottom_left, &UVec2::new(position[0] as u32, position[1] as u32)).extend(shape.0.translation.z);
                 *shape.2 = Visibility::Visible;
@@ -136,8 +154,8 @@ fn update_component_listener (
         if event.surround { // Get positions of surroundings
             let adjacent = {
                 let cell = &mut sim_data.grid.grid[event.pos[0]][event.pos[1]];
-                if let CellState::Real(_, c) = cell {
-                        helpers::get_adjacent(&event.pos, &c.dummy().get_grid_size())
+                if let CellState::Real(_, c, orientation) = cell {
+                        helpers::get_adjacent(&event.pos, &c.oriented_grid_size(*orientation))
                 } else {error!("Attempted to update event on none real"); return}
             };
             // Update surroundings
@@ -146,4 +164,39 @@ fn update_component_listener (
             }
         }
     }
-}
\ No newline at end of file
+}
+
+/// Turn the component at `pos` to face a new way, sent from the component's options
+#[derive(Debug, Clone)]
+pub struct OrientComponentEvent {
+    pub pos: [usize; 2],
+    pub orientation: Orientation,
+}
+
+fn orient_component_listener (
+    mut listener: EventReader<OrientComponentEvent>,
+    mut sim_data: ResMut<SimulationData>,
+    mut selected_component: ResMut<SelectedComponent>,
+    placement_grid: Query<(&Transform, &SizeComponent), With<PlacementGridEntity>>,
+    mut component_transforms: Query<&mut Transform, (With<GridLink>, Without<PlacementGridEntity>)>,
+    mut component_sprites: Query<&mut TextureAtlasSprite, With<GridLink>>,
+    atlases: Res<Assets<TextureAtlas>>,
+    main_atlas: Res<MainTextureAtlas>,
+) {
+    let atlas = atlases.get(&main_atlas.handle).unwrap();
+    let grid = placement_grid.single();
+    let grid_bottom_left = grid.0.translation.truncate() - (grid.1.0.as_vec2() * 0.5);
+    for event in listener.iter() {
+        match sim_data.reorient_component(&event.pos, event.orientation, &mut component_sprites, atlas) {
+            Ok((entity, size)) => {
+                if let Ok(mut transform) = component_transforms.get_mut(entity) {
+                    *transform = helpers::component_transform(&grid_bottom_left, &event.pos, size, event.orientation);
+                }
+                selected_component.set_changed(); // Redraw the selection outline at the new size
+            },
+            Err(PortGridError::CantFit) => info!("Not enough room to turn component at {:?}", event.pos),
+            Err(PortGridError::NotComponent) => error!("Attempted to turn none real component at {:?}", event.pos),
+            Err(PortGridError::Locked) => info!("The component at {:?} is locked by the level", event.pos),
+        }
+    }
+}
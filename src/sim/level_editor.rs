@@ -0,0 +1,442 @@
+//! Making levels in game. The board holds the start components along with a reference solution,
+//! [LevelDraft] holds everything else in a level file until it is exported to [LEVEL_DIR].
+use std::{fmt, fs::File, path::{Path, PathBuf}};
+
+use bevy::{prelude::*, utils::HashMap};
+use serde_json::Value;
+
+use crate::{
+    GameState, MainTextureAtlas,
+    components::placement::{GridLink, Size},
+    game::{GridSize, PlacementGridEntity, GRID_CELL_AMOUNT_HEIGHT, GRID_CELL_AMOUNT_WIDTH},
+    campaign::numbered_levels,
+};
+use super::{
+    definitions::ComponentRegistry,
+    generators::{InputGenerator, InputSource, OutputSource},
+    levels::{LevelData, LevelDataLoad, LevelError, SimIOPadded, TestCaseLoad, LEVEL_DIR, MAIN_CASE_NAME},
+    model::{CellState, Component, ComponentGrid, DummyComponent, SimulationData},
+    net::NetResolution,
+    palette::{component_key, Palette},
+    packages::PackageLibrary,
+    save_load::SaveData,
+    test_cases::TestResults,
+};
+
+/// Largest grid the board can show
+pub const MAX_GRID_SIZE: [usize; 2] = [GRID_CELL_AMOUNT_WIDTH as usize, GRID_CELL_AMOUNT_HEIGHT as usize];
+
+pub struct LevelEditorPlugin;
+
+impl Plugin for LevelEditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LevelDraft>()
+            .add_event::<EditorBoardEvent>()
+            .add_system(editor_board_listener.run_if(in_state(GameState::InGame)));
+    }
+}
+
+/// The level being made, used while [crate::level_select::CurrentLevel] is `None`
+#[derive(Resource, Debug, Clone)]
+pub struct LevelDraft {
+    /// Exported to `{LEVEL_DIR}/{number}.json`
+    pub number: u16,
+    pub name: String,
+    pub desc: String,
+    pub grid_size: [usize; 2],
+    pub net_resolution: NetResolution,
+    pub palette: Palette,
+    /// The first case is the one at the top of the file
+    pub cases: Vec<DraftCase>,
+    /// Components the player is given, anything else on the board is only the reference solution.
+    /// Providers and observers are always given.
+    pub start: Vec<[usize; 2]>,
+    /// Given components the player can't move, remove or change
+    pub locked: Vec<[usize; 2]>,
+    /// The level as it was when a test play last passed, so a changed level has to be played again before export
+    pub verified: Option<Value>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DraftCase {
+    pub name: String,
+    pub hidden: bool,
+    pub inputs: Vec<DraftInput>,
+    pub outputs: Vec<DraftOutput>,
+}
+
+/// An input as it is being edited, listed values are kept as typed in `values`
+#[derive(Debug, Clone)]
+pub struct DraftInput {
+    pub id: String,
+    pub source: InputSource,
+    /// Used by [InputSource::Values] and the pattern of [InputGenerator::Repeat]
+    pub values: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct DraftOutput {
+    pub id: String,
+    pub source: OutputSource,
+    /// Used by [OutputSource::Values]
+    pub values: String,
+}
+
+/// Why the board can't be test played as the reference solution, [LevelDataLoad::validate] only sees the given components
+#[derive(Debug)]
+pub enum DraftError {
+    /// The board is bigger than `grid_size`, so the level would be played on a smaller one
+    BoardTooLarge { board_size: [usize; 2], grid_size: [usize; 2] },
+    /// The reference solution uses a component the palette leaves out
+    NotAllowed(String),
+    /// The reference solution places more of a component than the palette's limit
+    OverLimit { name: String, placed: usize, limit: usize },
+}
+
+impl fmt::Display for DraftError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DraftError::BoardTooLarge { board_size, grid_size } => write!(
+                f, "The board is {}x{} but the grid size is {}x{}, resize the board first", board_size[0], board_size[1], grid_size[0], grid_size[1],
+            ),
+            DraftError::NotAllowed(name) => write!(f, "The reference solution uses {} which the palette does not allow", name),
+            DraftError::OverLimit { name, placed, limit } => write!(
+                f, "The reference solution places {} {} but the palette limits it to {}", placed, name, limit,
+            ),
+        }
+    }
+}
+
+impl Default for LevelDraft {
+    fn default() -> Self {
+        LevelDraft {
+            number: numbered_levels(Path::new(LEVEL_DIR)).last().map(|l| l + 1).unwrap_or(1),
+            name: "New level".to_string(),
+            desc: String::new(),
+            grid_size: MAX_GRID_SIZE,
+            net_resolution: NetResolution::default(),
+            palette: Palette::default(),
+            cases: vec![DraftCase::new(MAIN_CASE_NAME)],
+            start: Vec::new(),
+            locked: Vec::new(),
+            verified: None,
+        }
+    }
+}
+
+impl LevelDraft {
+    /// Edit an existing level, the start components are put on the board separately
+    pub fn from_load(number: u16, load: &LevelDataLoad) -> Self {
+        let main_case = DraftCase::from_sources(MAIN_CASE_NAME, false, &load.provided_inputs, &load.expected_outputs);
+        let extra_cases = load.test_cases.iter()
+            .map(|c| DraftCase::from_sources(&c.name, c.hidden, &c.provided_inputs, &c.expected_outputs));
+        let mut start = Vec::new();
+        for (x, column) in load.start.grid.iter().enumerate() {
+            for (y, cell) in column.iter().enumerate() {
+                if let CellState::Real(..) = cell {
+                    start.push([x, y]);
+                }
+            }
+        }
+        LevelDraft {
+            number,
+            name: load.name.clone(),
+            desc: load.desc.clone(),
+            grid_size: load.grid_size,
+            net_resolution: load.net_resolution,
+            palette: load.palette.clone(),
+            cases: std::iter::once(main_case).chain(extra_cases).collect(),
+            start,
+            locked: load.start.locked.clone(),
+            verified: None,
+        }
+    }
+
+    pub fn is_start(&self, component: &Component, position: [usize; 2]) -> bool {
+        matches!(component, Component::Provider(_) | Component::Observer(_)) || self.start.contains(&position)
+    }
+
+    pub fn set_start(&mut self, position: [usize; 2], start: bool) {
+        self.start.retain(|p| *p != position);
+        if start {
+            self.start.push(position);
+        } else {
+            self.locked.retain(|p| *p != position);
+        }
+    }
+
+    pub fn set_locked(&mut self, position: [usize; 2], locked: bool) {
+        self.locked.retain(|p| *p != position);
+        if locked {
+            self.locked.push(position);
+        }
+    }
+
+    /// The level file, with only the given components of `board` in its start grid
+    pub fn to_load(&self, board: &ComponentGrid) -> LevelDataLoad {
+        let mut start = ComponentGrid {
+            grid: vec![vec![CellState::Empty; self.grid_size[1]]; self.grid_size[0]],
+            locked: Vec::new(),
+        };
+        for (x, column) in board.grid.iter().enumerate().take(self.grid_size[0]) {
+            for (y, cell) in column.iter().enumerate().take(self.grid_size[1]) {
+                if let CellState::Real(_, component, _) = cell {
+                    if self.is_start(component, [x, y]) {
+                        start.grid[x][y] = cell.clone();
+                        if self.locked.contains(&[x, y]) {
+                            start.locked.push([x, y]);
+                        }
+                    }
+                }
+            }
+        }
+        start.rebuild_references();
+
+        let (main_case, extra_cases) = self.cases.split_first().expect("The main case is never removed");
+        let main_case = main_case.to_load();
+        LevelDataLoad {
+            name: self.name.clone(),
+            desc: self.desc.clone(),
+            grid_size: self.grid_size,
+            provided_inputs: main_case.provided_inputs,
+            expected_outputs: main_case.expected_outputs,
+            start,
+            net_resolution: self.net_resolution,
+            test_cases: extra_cases.iter().map(|c| c.to_load()).collect(),
+            palette: self.palette.clone(),
+        }
+    }
+
+    /// Whether a player could build the reference solution on `board`, given the level's size and palette
+    pub fn check_board(&self, board: &ComponentGrid) -> Vec<DraftError> {
+        let mut errors = Vec::new();
+        let board_size = [board.grid.len(), board.grid.first().map(|c| c.len()).unwrap_or(0)];
+        if board_size[0] > self.grid_size[0] || board_size[1] > self.grid_size[1] {
+            errors.push(DraftError::BoardTooLarge { board_size, grid_size: self.grid_size });
+        }
+
+        // Limits don't count the given components so only count what the player would have to place
+        let mut placed: HashMap<String, (DummyComponent, usize)> = HashMap::new();
+        for (x, column) in board.grid.iter().enumerate() {
+            for (y, cell) in column.iter().enumerate() {
+                if let CellState::Real(_, component, _) = cell {
+                    if !self.is_start(component, [x, y]) {
+                        placed.entry(component_key(component.dummy())).or_insert((component.dummy(), 0)).1 += 1;
+                    }
+                }
+            }
+        }
+        let mut placed: Vec<(String, (DummyComponent, usize))> = placed.into_iter().collect();
+        placed.sort_by(|a, b| a.0.cmp(&b.0));
+        for (name, (component, count)) in placed {
+            if !self.palette.allows(component) {
+                errors.push(DraftError::NotAllowed(name));
+            } else if let Some(limit) = self.palette.limits.get(&name).filter(|limit| count > **limit) {
+                errors.push(DraftError::OverLimit { name, placed: count, limit: *limit });
+            }
+        }
+        errors
+    }
+
+    pub fn path(&self) -> PathBuf {
+        PathBuf::from(format!("{}/{}.json", LEVEL_DIR, self.number))
+    }
+
+    /// Whether the level is unchanged since a test play last passed
+    pub fn is_verified(&self, board: &ComponentGrid) -> bool {
+        self.verified.is_some() && self.verified == serde_json::to_value(self.to_load(board)).ok()
+    }
+
+    pub fn mark_verified(&mut self, board: &ComponentGrid) {
+        self.verified = serde_json::to_value(self.to_load(board)).ok();
+    }
+
+    /// Write the level to [Self::path], only if it would load without problems
+    pub fn export(&self, board: &ComponentGrid) -> Result<PathBuf, Vec<LevelError>> {
+        let level = self.to_load(board);
+        let errors = level.validate();
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        let path = self.path();
+        let file = File::create(&path).map_err(|e| vec![LevelError::Open(e.to_string())])?;
+        serde_json::to_writer_pretty(file, &level).map_err(|e| vec![LevelError::Parse(e.to_string())])?;
+        Ok(path)
+    }
+
+    /// Give every case an input for each provider and an output for each observer on the board it is missing
+    pub fn add_missing_ids(&mut self, board: &ComponentGrid) {
+        for cell in board.grid.iter().flatten() {
+            match cell {
+                CellState::Real(_, Component::Provider(provider), _) => {
+                    for case in self.cases.iter_mut().filter(|c| !c.inputs.iter().any(|i| i.id == provider.id())) {
+                        case.inputs.push(DraftInput::new(provider.id(), InputSource::Values(Vec::new())));
+                    }
+                },
+                CellState::Real(_, Component::Observer(observer), _) => {
+                    for case in self.cases.iter_mut().filter(|c| !c.outputs.iter().any(|o| o.id == observer.id())) {
+                        case.outputs.push(DraftOutput::new(observer.id(), OutputSource::Values(Vec::new())));
+                    }
+                },
+                _ => {},
+            }
+        }
+    }
+}
+
+impl DraftCase {
+    pub fn new(name: &str) -> Self {
+        DraftCase { name: name.to_string(), hidden: false, inputs: Vec::new(), outputs: Vec::new() }
+    }
+
+    fn from_sources(name: &str, hidden: bool, inputs: &HashMap<String, InputSource>, outputs: &HashMap<String, OutputSource>) -> Self {
+        let mut inputs: Vec<DraftInput> = inputs.iter().map(|(id, source)| DraftInput::new(id, source.clone())).collect();
+        let mut outputs: Vec<DraftOutput> = outputs.iter().map(|(id, source)| DraftOutput::new(id, source.clone())).collect();
+        inputs.sort_by(|a, b| a.id.cmp(&b.id));
+        outputs.sort_by(|a, b| a.id.cmp(&b.id));
+        DraftCase { name: name.to_string(), hidden, inputs, outputs }
+    }
+
+    fn to_load(&self) -> TestCaseLoad {
+        TestCaseLoad {
+            name: self.name.clone(),
+            hidden: self.hidden,
+            provided_inputs: self.inputs.iter().map(|i| (i.id.clone(), i.source())).collect(),
+            expected_outputs: self.outputs.iter().map(|o| (o.id.clone(), o.source())).collect(),
+        }
+    }
+}
+
+impl DraftInput {
+    pub fn new(id: &str, source: InputSource) -> Self {
+        let values = match &source {
+            InputSource::Values(values) => format_values(values),
+            InputSource::Generated(InputGenerator::Repeat { pattern, .. }) => format_values(pattern),
+            _ => String::new(),
+        };
+        DraftInput { id: id.to_string(), source, values }
+    }
+
+    /// `source` with the typed values put in
+    pub fn source(&self) -> InputSource {
+        match &self.source {
+            InputSource::Values(_) => InputSource::Values(parse_values(&self.values)),
+            InputSource::Generated(InputGenerator::Repeat { times, .. }) => {
+                InputSource::Generated(InputGenerator::Repeat { pattern: parse_values(&self.values), times: *times })
+            },
+            source => source.clone(),
+        }
+    }
+}
+
+impl DraftOutput {
+    pub fn new(id: &str, source: OutputSource) -> Self {
+        let values = match &source {
+            OutputSource::Values(values) => format_values(values),
+            _ => String::new(),
+        };
+        DraftOutput { id: id.to_string(), source, values }
+    }
+
+    /// `source` with the typed values put in
+    pub fn source(&self) -> OutputSource {
+        match &self.source {
+            OutputSource::Values(_) => OutputSource::Values(parse_values(&self.values)),
+            source => source.clone(),
+        }
+    }
+}
+
+/// Values split by commas or spaces, anything which isn't a number from 0 to 255 is left out
+pub fn parse_values(text: &str) -> Vec<u8> {
+    text.split(|c: char| c == ',' || c.is_whitespace())
+        .filter_map(|v| v.parse().ok())
+        .collect()
+}
+
+pub fn format_values(values: &[u8]) -> String {
+    values.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(", ")
+}
+
+/// Replace the board while editing a level
+pub enum EditorBoardEvent {
+    /// An empty board of the draft's size
+    New,
+    /// Load the level with this number into the draft and its start components onto the board
+    Open(u16),
+    /// Change the board to the draft's size, keeping everything which still fits
+    Resize,
+}
+
+/// Rebuilds the board for [EditorBoardEvent]s, then gives it [LevelData] from the draft so the circuit can be run
+fn editor_board_listener(
+    mut listener: EventReader<EditorBoardEvent>,
+    mut commands: Commands,
+    mut draft: ResMut<LevelDraft>,
+    mut sim_data: ResMut<SimulationData>,
+    mut size: ResMut<GridSize>,
+    placement_grid: Query<(&Sprite, &Transform, &Size), With<PlacementGridEntity>>,
+    atlases: Res<Assets<TextureAtlas>>,
+    main_atlas: Res<MainTextureAtlas>,
+    registry: Res<ComponentRegistry>,
+    library: Res<PackageLibrary>,
+    despawns: Query<Entity, With<GridLink>>,
+) {
+    for event in listener.iter() {
+        let grid_size = draft.grid_size;
+        let board = match event {
+            EditorBoardEvent::New => ComponentGrid {
+                grid: vec![vec![CellState::Empty; grid_size[1]]; grid_size[0]],
+                locked: Vec::new(),
+            },
+            EditorBoardEvent::Open(number) => {
+                let path = PathBuf::from(format!("{}/{}.json", LEVEL_DIR, number));
+                match LevelDataLoad::load(&path, &registry, &library) {
+                    Ok(load) => {
+                        *draft = LevelDraft::from_load(*number, &load);
+                        // Locks are kept in the draft, the author can still change everything
+                        let mut start = load.start;
+                        start.locked.clear();
+                        start
+                    },
+                    Err(errors) => {
+                        for e in errors {
+                            error!("Could not open level {:?} in the editor: {}", path, e);
+                        }
+                        continue;
+                    },
+                }
+            },
+            EditorBoardEvent::Resize => {
+                let mut board = ComponentGrid {
+                    grid: vec![vec![CellState::Empty; grid_size[1]]; grid_size[0]],
+                    locked: Vec::new(),
+                };
+                for (x, column) in sim_data.grid.grid.iter().enumerate().take(grid_size[0]) {
+                    for (y, cell) in column.iter().enumerate().take(grid_size[1]) {
+                        if let CellState::Real(_, component, orientation) = cell {
+                            let component_size = component.oriented_grid_size(*orientation);
+                            if x + component_size[0] <= grid_size[0] && y + component_size[1] <= grid_size[1] {
+                                board.grid[x][y] = cell.clone();
+                            }
+                        }
+                    }
+                }
+                board
+            },
+        };
+
+        for entity in despawns.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        let save = SaveData { grid_size: GridSize(draft.grid_size), component_grid: board, score: None };
+        let (new_sim_data, new_size) = save.create_world(&mut commands, atlases.get(&main_atlas.handle).unwrap(), main_atlas.as_ref(), &registry, &library, &placement_grid);
+        *sim_data = new_sim_data;
+        *size = new_size;
+
+        let mut level_data = LevelData::from_load(draft.to_load(&sim_data.grid));
+        commands.insert_resource(SimIOPadded::from_level_data(&mut level_data));
+        commands.insert_resource(level_data);
+        commands.insert_resource(TestResults::default());
+    }
+}
@@ -1,10 +1,13 @@
-use std::{fs::File, path::PathBuf};
+use std::{fmt, fs::File, path::{Path, PathBuf}};
 
-use bevy::{prelude::{Resource, Res, ResMut, Commands, Entity, Transform, Query, Assets, With, DespawnRecursiveExt, EventReader}, reflect::{Reflect, FromReflect}, utils::HashMap, sprite::{TextureAtlas, Sprite}};
+use bevy::{prelude::{Resource, Res, ResMut, Commands, Entity, Transform, Query, Assets, With, DespawnRecursiveExt, EventReader, error}, reflect::{Reflect, FromReflect}, utils::HashMap, sprite::{TextureAtlas, Sprite}};
 use serde::{Deserialize, Serialize};
-use crate::{game::{GridSize, PlacementGridEntity}, MainTextureAtlas, components::placement::{GridLink, Size}};
+use crate::{game::{GridSize, PlacementGridEntity}, MainTextureAtlas, components::placement::{GridLink, Size}, campaign::{campaign_dirs, Campaign, MANIFEST_NAME}};
 
-use super::{model::{ComponentGrid, SimulationData, CellState}, port_grid::PortGrid};
+use super::{model::{ComponentGrid, SimulationData, CellState, Component, DummyComponent}, port_grid::PortGrid, packages::PackageLibrary, definitions::ComponentRegistry, net::NetResolution, generators::{InputSource, OutputSource}, test_cases::TestResults, palette::{self, Palette, PaletteError}};
+
+/// Every level the game ships with, checked by `--check-levels`
+pub const LEVEL_DIR: &str = "data/levels";
 
 /// Stores the relevant level state, these should be kept when levels are loaded.\
 /// As opposed to [LevelData], this stores also the start positions & grid size
@@ -17,12 +20,253 @@ pub struct LevelDataLoad {
     /// Grid Size, Typically should be 7x7, Undefined behaviour otherwise.
     pub grid_size: [usize; 2],
     // Using bevy hashmap which simply re-exports hashbrown (I believe)
-    /// Inputs (relative to game grid)
-    pub provided_inputs: HashMap<String, Vec<u8>>,
-    /// Expected outputs (relative to game grid)
-    pub expected_outputs: HashMap<String, Vec<u8>>,
+    /// Inputs (relative to game grid), either listed or generated, see [super::generators]
+    #[reflect(ignore)]
+    pub provided_inputs: HashMap<String, InputSource>,
+    /// Expected outputs (relative to game grid), either listed or worked out from the inputs
+    #[reflect(ignore)]
+    pub expected_outputs: HashMap<String, OutputSource>,
     /// Starting state, should always be loaded before any save.
     pub start: ComponentGrid,
+    /// How nets with more than one driver are resolved, wires can override this.
+    #[serde(default)]
+    pub net_resolution: NetResolution,
+    /// More inputs and expected outputs to check a solution with, on top of the ones above
+    #[reflect(ignore)]
+    #[serde(default)]
+    pub test_cases: Vec<TestCaseLoad>,
+    /// Which components can be placed and how many, see [super::palette]
+    #[reflect(ignore)]
+    #[serde(default)]
+    pub palette: Palette,
+}
+
+/// The name given to the inputs and expected outputs at the top of a level file
+pub const MAIN_CASE_NAME: &str = "Example";
+
+/// An extra set of inputs and expected outputs for a level, every case is run when the player submits
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestCaseLoad {
+    pub name: String,
+    /// Hidden cases never show their values, so a solution can't just be made to match them
+    #[serde(default)]
+    pub hidden: bool,
+    pub provided_inputs: HashMap<String, InputSource>,
+    pub expected_outputs: HashMap<String, OutputSource>,
+}
+
+/// A [TestCaseLoad] with its inputs generated and expected outputs worked out
+#[derive(Debug, Clone, Default)]
+pub struct TestCase {
+    pub name: String,
+    pub hidden: bool,
+    pub provided_inputs: HashMap<String, Vec<u8>>,
+    pub expected_outputs: HashMap<String, Vec<u8>>,
+}
+
+/// A problem with a level file, all of them are found at once by [LevelDataLoad::validate]
+#[derive(Debug, Clone)]
+pub enum LevelError {
+    Open(String),
+    Parse(String),
+    /// `grid_size` and the size of `start.grid` are different
+    GridSizeMismatch { grid_size: [usize; 2], start_size: [usize; 2] },
+    /// An observer with this id has no entry in the `expected_outputs` of a test case
+    MissingExpectedOutput { id: String, position: [usize; 2], case: String },
+    /// A provider with this id has no entry in the `provided_inputs` of a test case
+    MissingProvidedInput { id: String, position: [usize; 2], case: String },
+    /// A start component reaching past the edge of the grid
+    OffGrid { name: String, position: [usize; 2] },
+    /// The reference for an expected output could not be compiled
+    Reference { id: String, case: String, error: String },
+    DuplicateCase(String),
+    /// A cell in `start.locked` without a component starting there
+    LockedEmpty([usize; 2]),
+    /// A name in the palette which isn't a component
+    UnknownComponent(String),
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LevelError::Open(e) => write!(f, "Could not open the file: {}", e),
+            LevelError::Parse(e) => write!(f, "Could not parse the file: {}", e),
+            LevelError::GridSizeMismatch { grid_size, start_size } => write!(
+                f, "grid_size is {}x{} but the start grid is {}x{}", grid_size[0], grid_size[1], start_size[0], start_size[1],
+            ),
+            LevelError::MissingExpectedOutput { id, position, case } => write!(
+                f, "The observer at {}, {} has id \"{}\" which is not in the expected_outputs of {}", position[0], position[1], id, case,
+            ),
+            LevelError::MissingProvidedInput { id, position, case } => write!(
+                f, "The provider at {}, {} has id \"{}\" which is not in the provided_inputs of {}", position[0], position[1], id, case,
+            ),
+            LevelError::OffGrid { name, position } => write!(
+                f, "The {} at {}, {} goes past the edge of the grid", name, position[0], position[1],
+            ),
+            LevelError::Reference { id, case, error } => write!(f, "The reference for {} in {}: {}", id, case, error),
+            LevelError::DuplicateCase(name) => write!(f, "There is more than one test case called {}", name),
+            LevelError::UnknownComponent(name) => write!(f, "The palette lists {} which is not a component", name),
+            LevelError::LockedEmpty(position) => write!(f, "The locked cell at {}, {} has no component starting there", position[0], position[1]),
+        }
+    }
+}
+
+impl LevelDataLoad {
+    /// Read and check a level, data driven components and packages are looked up first so their sizes are known
+    pub fn load(path: &Path, registry: &ComponentRegistry, library: &PackageLibrary) -> Result<Self, Vec<LevelError>> {
+        let file = File::open(path).map_err(|e| vec![LevelError::Open(e.to_string())])?;
+        let mut level: LevelDataLoad = serde_json::from_reader(file).map_err(|e| vec![LevelError::Parse(e.to_string())])?;
+        registry.attach(&mut level.start);
+        library.attach(&mut level.start);
+        let errors = level.validate();
+        // The level's inputs and outputs are always locked so the player can't take them away
+        for (x, column) in level.start.grid.iter().enumerate() {
+            for (y, cell) in column.iter().enumerate() {
+                if let CellState::Real(_, Component::Provider(_) | Component::Observer(_), _) = cell {
+                    if !level.start.locked.contains(&[x, y]) {
+                        level.start.locked.push([x, y]);
+                    }
+                }
+            }
+        }
+        match errors.is_empty() {
+            true => Ok(level),
+            false => Err(errors),
+        }
+    }
+
+    /// Every test case, starting with the one at the top of the file, with the inputs generated and expected outputs worked out
+    pub fn expand(&self) -> Result<Vec<TestCase>, LevelError> {
+        let main_case = (MAIN_CASE_NAME, false, &self.provided_inputs, &self.expected_outputs);
+        let extra_cases = self.test_cases.iter().map(|c| (c.name.as_str(), c.hidden, &c.provided_inputs, &c.expected_outputs));
+        let mut cases = Vec::new();
+        for (name, hidden, provided_inputs, expected_outputs) in std::iter::once(main_case).chain(extra_cases) {
+            let inputs: HashMap<String, Vec<u8>> = provided_inputs.iter()
+                .map(|(id, source)| (id.clone(), source.generate()))
+                .collect();
+            let mut outputs = HashMap::new();
+            for (id, source) in expected_outputs {
+                let expected = source.generate(id, &inputs)
+                    .map_err(|e| LevelError::Reference { id: id.clone(), case: name.to_string(), error: e.to_string() })?;
+                outputs.insert(id.clone(), expected);
+            }
+            cases.push(TestCase { name: name.to_string(), hidden, provided_inputs: inputs, expected_outputs: outputs });
+        }
+        Ok(cases)
+    }
+
+    /// Every problem with the level, empty if it is fine to load
+    pub fn validate(&self) -> Vec<LevelError> {
+        let mut errors = Vec::new();
+
+        if let Err(e) = self.expand() {
+            errors.push(e);
+        }
+        for (i, case) in self.test_cases.iter().enumerate() {
+            let earlier = self.test_cases[..i].iter().any(|c| c.name == case.name);
+            if earlier || case.name == MAIN_CASE_NAME {
+                errors.push(LevelError::DuplicateCase(case.name.clone()));
+            }
+        }
+        let case_keys = std::iter::once((MAIN_CASE_NAME, &self.provided_inputs, &self.expected_outputs))
+            .chain(self.test_cases.iter().map(|c| (c.name.as_str(), &c.provided_inputs, &c.expected_outputs)));
+        let case_keys: Vec<(&str, Vec<&String>, Vec<&String>)> = case_keys
+            .map(|(name, inputs, outputs)| (name, inputs.keys().collect(), outputs.keys().collect()))
+            .collect();
+
+        let start_size = [self.start.grid.len(), self.start.grid.first().map(|c| c.len()).unwrap_or(0)];
+        let ragged = self.start.grid.iter().any(|c| c.len() != start_size[1]);
+        if start_size != self.grid_size || ragged {
+            errors.push(LevelError::GridSizeMismatch { grid_size: self.grid_size, start_size });
+        }
+
+        for name in self.palette.unknown_keys() {
+            errors.push(LevelError::UnknownComponent(name));
+        }
+
+        for position in &self.start.locked {
+            let real = self.start.grid.get(position[0]).and_then(|c| c.get(position[1]));
+            if !matches!(real, Some(CellState::Real(..))) {
+                errors.push(LevelError::LockedEmpty(*position));
+            }
+        }
+
+        for (x, column) in self.start.grid.iter().enumerate() {
+            for (y, cell) in column.iter().enumerate() {
+                let (component, orientation) = match cell {
+                    CellState::Real(_, component, orientation) => (component, orientation),
+                    _ => continue,
+                };
+                let size = component.oriented_grid_size(*orientation);
+                if x + size[0] > self.grid_size[0] || y + size[1] > self.grid_size[1] {
+                    errors.push(LevelError::OffGrid { name: component.dummy().name().to_string(), position: [x, y] });
+                }
+                // Every case has to give each observer and provider something, or they would read nothing
+                for (case, inputs, outputs) in &case_keys {
+                    match component {
+                        Component::Observer(observer) if !outputs.iter().any(|k| *k == observer.id()) => {
+                            errors.push(LevelError::MissingExpectedOutput { id: observer.id().to_string(), position: [x, y], case: case.to_string() });
+                        },
+                        Component::Provider(provider) if !inputs.iter().any(|k| *k == provider.id()) => {
+                            errors.push(LevelError::MissingProvidedInput { id: provider.id().to_string(), position: [x, y], case: case.to_string() });
+                        },
+                        _ => {},
+                    }
+                }
+            }
+        }
+
+        errors
+    }
+}
+
+/// Check every campaign manifest in [LEVEL_DIR] and [crate::campaign::PACKS_DIR] and every level they list, printing any problems.\
+/// A broken manifest has its numbered levels checked instead, like the game falls back to.
+/// Returns the exit code, 1 if anything has a problem.
+pub fn check_levels() -> i32 {
+    let registry = ComponentRegistry::load();
+    let library = PackageLibrary::load(&registry);
+    let mut failed = false;
+    for (id, dir) in campaign_dirs() {
+        let manifest = dir.join(MANIFEST_NAME);
+        let campaign = match Campaign::load(&id, &dir) {
+            Ok(campaign) => {
+                println!("{}: ok", manifest.display());
+                campaign
+            },
+            Err(errors) => {
+                failed = true;
+                println!("{}:", manifest.display());
+                for error in errors {
+                    println!("    {}", error);
+                }
+                Campaign::numbered(&id, &dir)
+            },
+        };
+
+        // Missing files were already reported with the manifest
+        let paths = campaign.levels().map(|(_, level)| campaign.path(level)).filter(|path| path.exists());
+        for path in paths {
+            match LevelDataLoad::load(&path, &registry, &library) {
+                Ok(_) => println!("{}: ok", path.display()),
+                Err(errors) => {
+                    failed = true;
+                    println!("{}:", path.display());
+                    for error in errors {
+                        println!("    {}", error);
+                    }
+                },
+            }
+        }
+    }
+    if failed {1} else {0}
+}
+
+/// Problems with the last level which failed to load, shown in game until dismissed
+#[derive(Resource, Debug, Default)]
+pub struct LevelErrors {
+    pub path: PathBuf,
+    pub errors: Vec<LevelError>,
 }
 
 #[derive(Debug, Clone, Resource, Reflect)]
@@ -35,6 +279,18 @@ pub struct LevelData {
     pub provided_inputs: HashMap<String, Vec<u8>>,
     /// Expected outputs (relative to game grid)
     pub expected_outputs: HashMap<String, Vec<u8>>,
+    /// How nets with more than one driver are resolved, wires can override this.
+    pub net_resolution: NetResolution,
+    /// Every test case, the inputs and outputs above are from the one being run on the board
+    #[reflect(ignore)]
+    pub test_cases: Vec<TestCase>,
+    /// Index into `test_cases` of the case being run on the board
+    pub current_case: usize,
+    #[reflect(ignore)]
+    pub palette: Palette,
+    /// How many of each component the level starts with, these don't count against the palette's limits
+    #[reflect(ignore)]
+    pub start_counts: HashMap<String, usize>,
 }
 
 #[derive(Debug, Clone, Copy, Reflect, FromReflect)]
@@ -69,8 +325,19 @@ impl SimIOPadded {
     }
 
     pub fn add_output(&mut self, level_data: &mut LevelData, _: usize, id: &str, val: Option<u8>) {
+        // Only happens in the editor, before the level has expected outputs for this id
+        if !level_data.expected_outputs.contains_key(id) || !self.expected_outputs.contains_key(id) {return}
         if let Some(val) = val {
-            let expected = level_data.expected_outputs[id][self.output_pointer];
+            // Anything after the last expected output is wrong
+            let expected = match level_data.expected_outputs[id].get(self.output_pointer) {
+                Some(expected) => *expected,
+                None => {
+                    self.correct_so_far = false;
+                    self.expected_outputs.get_mut(id).unwrap().push(None);
+                    self.observed_outputs.get_mut(id).unwrap().push(Some((val, ResultType::Incorrect)));
+                    return;
+                },
+            };
             self.expected_outputs.get_mut(id).unwrap().push(Some(expected));
             self.output_pointer += 1;
 
@@ -89,12 +356,43 @@ impl SimIOPadded {
 }
 
 impl LevelData {
+    /// The level should have passed [LevelDataLoad::validate], a reference which fails expects nothing
     pub fn from_load(load: LevelDataLoad) -> LevelData {
-        LevelData {
+        let test_cases = load.expand().unwrap_or_default();
+        let start_counts = palette::count_components(&load.start);
+        let mut level_data = LevelData {
             name: load.name,
             desc: load.desc,
-            provided_inputs: load.provided_inputs,
-            expected_outputs: load.expected_outputs,
+            provided_inputs: HashMap::new(),
+            expected_outputs: HashMap::new(),
+            net_resolution: load.net_resolution,
+            test_cases,
+            current_case: 0,
+            palette: load.palette,
+            start_counts,
+        };
+        level_data.select_case(0);
+        level_data
+    }
+
+    /// How many of the component the player has placed on `grid`
+    pub fn placed(&self, component: DummyComponent, grid: &ComponentGrid) -> usize {
+        let key = palette::component_key(component);
+        let on_grid = palette::count_components(grid).get(&key).copied().unwrap_or(0);
+        on_grid.saturating_sub(self.start_counts.get(&key).copied().unwrap_or(0))
+    }
+
+    /// Whether the palette lets the player place one more of the component
+    pub fn can_place(&self, component: DummyComponent, grid: &ComponentGrid) -> Result<(), PaletteError> {
+        self.palette.check(component, self.placed(component, grid))
+    }
+
+    /// Run a different test case on the board, the [SimIOPadded] needs remaking after
+    pub fn select_case(&mut self, index: usize) {
+        if let Some(case) = self.test_cases.get(index) {
+            self.provided_inputs = case.provided_inputs.clone();
+            self.expected_outputs = case.expected_outputs.clone();
+            self.current_case = index;
         }
     }
 }
@@ -104,20 +402,31 @@ pub fn load_level_listener(
     mut commands: Commands,
     mut sim_data_res: ResMut<SimulationData>,
     mut grid_size: ResMut<GridSize>,
+    mut level_errors: ResMut<LevelErrors>,
     placement_grid: Query<(&Sprite, &Transform, &Size), With<PlacementGridEntity>>,
     atlases: Res<Assets<TextureAtlas>>,
     main_atlas: Res<MainTextureAtlas>,
+    registry: Res<ComponentRegistry>,
+    library: Res<PackageLibrary>,
     despawns: Query<Entity, With<GridLink>>,
 ) {
     for event in listener.iter() {
+        let level_data_load = match LevelDataLoad::load(&event.0, &registry, &library) {
+            Ok(level) => level,
+            Err(errors) => {
+                for e in &errors {
+                    error!("Could not load level {:?}: {}", event.0, e);
+                }
+                *level_errors = LevelErrors { path: event.0.clone(), errors };
+                continue;
+            },
+        };
+        level_errors.errors.clear();
 
         for entity in despawns.iter() {
             commands.entity(entity).despawn_recursive();
         }
-        
-        let file = File::open(event.0.clone())
-            .expect("Could not find level file");
-        let level_data_load: LevelDataLoad = serde_json::from_reader(file).expect("Could not parse level");
+
         let grid = placement_grid.single();
         let size = grid.2;
         let grid_bottom_left = grid.1.translation.truncate() - (size.0.as_vec2() * 0.5);
@@ -125,16 +434,19 @@ pub fn load_level_listener(
         grid_size.0 = level_data_load.grid_size.clone();
 
         let mut sim_data = SimulationData {
-            grid: ComponentGrid { grid: vec![vec![ CellState::Empty; grid_size.0[1]]; grid_size.0[0]] },
+            grid: ComponentGrid { grid: vec![vec![ CellState::Empty; grid_size.0[1]]; grid_size.0[0]], locked: Vec::new() },
             port_grid: PortGrid::new_with_size(grid_size.0[1],  grid_size.0[0]),
+            nets: Vec::new(),
         };
 
         let atlas = atlases.get(&main_atlas.handle).unwrap();
 
+        // Set first so the locked components get their border as they are loaded
+        sim_data.grid.locked = level_data_load.start.locked.clone();
         for x in 0..level_data_load.start.grid.len() {
             for y in 0..level_data_load.start.grid[x].len() {
-                if let CellState::Real(_, component) = level_data_load.start.grid[x][y].clone() {
-                    sim_data.load_component(&mut commands, component, &grid_bottom_left, atlas, &main_atlas, &[x,y])
+                if let CellState::Real(_, component, orientation) = level_data_load.start.grid[x][y].clone() {
+                    sim_data.load_component(&mut commands, component, &grid_bottom_left, atlas, &main_atlas, &[x,y], orientation)
                 }
             }
         }
@@ -144,6 +456,7 @@ pub fn load_level_listener(
         *sim_data_res = sim_data;
         commands.insert_resource(level_data);
         commands.insert_resource(sim_io);
+        commands.insert_resource(TestResults::default());
     }
 }
 
@@ -1,8 +1,17 @@
 pub mod components;
+pub mod definitions;
+pub mod expression;
+pub mod generators;
 pub mod helpers;
+pub mod level_editor;
 pub mod levels;
 pub mod model;
+pub mod net;
+pub mod packages;
+pub mod palette;
 pub mod port_grid;
 pub mod run;
 pub mod save_load;
+pub mod scoring;
+pub mod test_cases;
 pub mod interactions;
\ No newline at end of file
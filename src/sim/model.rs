@@ -7,12 +7,13 @@
 // Would require extra code for P2P (port to port) connections not through a wire
 // Can already get a mental map for this
 
-use std::sync::{Arc, atomic::{AtomicU8, Ordering}};
+use std::sync::Arc;
 
 // Improvement: We add a new 'reference' component which just redirects any calls onto the actual cell the component is in.
 // This means that instead of going through every component for ports we just go through the ones which are adjacent
 use super::{
-    helpers::{self, Side, spawn_component_sprite},
+    helpers::{self, Side, Orientation, spawn_component_sprite},
+    net::Net,
     port_grid::{{Port as PortGridPort}, PortGrid}, interactions::UpdateComponentEvent,
 };
 use crate::{
@@ -32,6 +33,9 @@ pub struct SimulationData {
     pub grid: ComponentGrid,
     #[reflect(ignore)]
     pub port_grid: PortGrid,
+    /// Every net made by the last build, reset at the start of each tick
+    #[reflect(ignore)]
+    pub nets: Vec<Arc<Net>>,
 }
 
 impl SimulationData {
@@ -42,20 +46,21 @@ impl SimulationData {
         atlas: &TextureAtlas,
         main_atlas: &Res<MainTextureAtlas>,
         component_sprites: &mut Query<&mut TextureAtlasSprite, With<GridLink>>,
-        dummy_component: DummyComponent,
+        mut component: Component,
         position: &[usize; 2],
+        orientation: Orientation,
     ) -> Result<(), PortGridError> {
-        if self.grid.can_fit(position, &dummy_component.get_grid_size())
+        if self.grid.can_fit(position, &component.oriented_grid_size(orientation))
         {
-            let mut sprite = TextureAtlasSprite::new(dummy_component.get_sprite_index(atlas));
+            let mut sprite = TextureAtlasSprite::new(component.dummy().get_sprite_index(atlas));
             sprite.anchor = Anchor::BottomLeft;
-            let mut component = dummy_component.build_default();
             component.on_place(position, self, &mut sprite, atlas);
-            let entity_id = spawn_component_sprite(commands, sprite, grid_bottom_left, position, main_atlas.as_ref(), dummy_component);
-            self.grid.place_component(entity_id, component, position);
+            let entity_id = spawn_component_sprite(commands, sprite, grid_bottom_left, position, main_atlas.as_ref(), &component, orientation);
+            let ports = component.oriented_ports(orientation);
+            let adjacent = helpers::get_adjacent(position, &component.oriented_grid_size(orientation));
+            self.grid.place_component(entity_id, component, position, orientation);
             self.port_grid
-                .modify_bulk(Some(PortGridPort::default()), dummy_component.ports(), position);
-            let adjacent = helpers::get_adjacent(position, &dummy_component.get_grid_size());
+                .modify_bulk(Some(PortGridPort::default()), ports.iter().collect(), position);
 
             for component in adjacent {
                 self.update_component(&component, component_sprites, atlas);
@@ -76,34 +81,116 @@ impl SimulationData {
         grid_bottom_left: &Vec2,
         atlas: &TextureAtlas,
         main_atlas: &MainTextureAtlas,
-        grid_position: &[usize; 2]
+        grid_position: &[usize; 2],
+        orientation: Orientation,
     ) {
-        let dummy_component = component.dummy();
         let mut sprite = match &component {
             Component::WirePiece(w) => {
                 let sprite_name = super::components::wire::sides_to_sprite_name(&w.connected_sides, "wire_", "_");
                 let index = atlas.get_texture_index(&Handle::weak(sprite_name.into())).expect("Could not find correct wire varient");
                 TextureAtlasSprite::new(index)
             }
+            Component::WireCrossover(c) => {
+                let index = atlas.get_texture_index(&Handle::weak(WireCrossover::sprite_name(&c.connected_sides).into())).expect("Could not find correct crossover varient");
+                TextureAtlasSprite::new(index)
+            }
+            Component::ToggleSwitch(s) => {
+                let index = atlas.get_texture_index(&Handle::weak(s.sprite_name().into())).expect("Could not find switch sprite");
+                TextureAtlasSprite::new(index)
+            }
+            Component::MinMax(m) => {
+                let index = atlas.get_texture_index(&Handle::weak(m.sprite_name().into())).expect("Could not find min / max sprite");
+                TextureAtlasSprite::new(index)
+            }
+            Component::BitShift(b) => {
+                let index = atlas.get_texture_index(&Handle::weak(b.sprite_name().into())).expect("Could not find bit shift sprite");
+                TextureAtlasSprite::new(index)
+            }
+            Component::Package(p) => {
+                let mut sprite = TextureAtlasSprite::new(component.dummy().get_sprite_index(atlas));
+                p.set_sprite(&mut sprite, atlas);
+                sprite
+            }
+            Component::Scripted(s) => {
+                let mut sprite = TextureAtlasSprite::new(component.dummy().get_sprite_index(atlas));
+                s.set_sprite(&mut sprite, atlas);
+                sprite
+            }
             _ => TextureAtlasSprite::new(component.dummy().get_sprite_index(atlas))
         };
         sprite.anchor = Anchor::BottomLeft;
-        let entity_id = spawn_component_sprite(commands, sprite, grid_bottom_left, grid_position, main_atlas, dummy_component);
-        self.grid.place_component(entity_id, component, grid_position);
-        self.port_grid.modify_bulk(Some(PortGridPort::default()), dummy_component.ports(), grid_position);
+        let entity_id = spawn_component_sprite(commands, sprite, grid_bottom_left, grid_position, main_atlas, &component, orientation);
+        if self.grid.is_locked(grid_position) {
+            helpers::spawn_locked_border(commands, entity_id, component.size());
+        }
+        let ports = component.oriented_ports(orientation);
+        self.grid.place_component(entity_id, component, grid_position, orientation);
+        self.port_grid.modify_bulk(Some(PortGridPort::default()), ports.iter().collect(), grid_position);
     }
 
     pub fn remove_component(
         &mut self,
-        component: DummyComponent,
         position: [usize; 2],
     ) -> Result<(), PortGridError> {
-        self.grid.remove_component(component, &position);
+        if self.grid.is_locked(&position) {
+            return Err(PortGridError::Locked);
+        }
+        let (ports, size) = match &self.grid.grid[position[0]][position[1]] {
+            CellState::Real(_, c, o) => (c.oriented_ports(*o), c.oriented_grid_size(*o)),
+            _ => return Err(PortGridError::NotComponent),
+        };
+        self.grid.remove_component(&position, size);
         self.port_grid
-            .modify_bulk(None, component.ports(), &position);
+            .modify_bulk(None, ports.iter().collect(), &position);
         Ok(())
     }
 
+    /// Turn an already placed component, moving its ports to match.\
+    /// If it no longer fits it is left as it was.\
+    /// Returns the sprite entity which should be moved with [helpers::component_transform]
+    pub fn reorient_component(
+        &mut self,
+        position: &[usize; 2],
+        orientation: Orientation,
+        component_sprites: &mut Query<&mut TextureAtlasSprite, With<GridLink>>,
+        atlas: &TextureAtlas,
+    ) -> Result<(Entity, Vec2), PortGridError> {
+        if self.grid.is_locked(position) {
+            return Err(PortGridError::Locked);
+        }
+        let (entity_id, component, old_orientation) = match std::mem::replace(&mut self.grid.grid[position[0]][position[1]], CellState::Empty) {
+            CellState::Real(e, c, o) => (e, c, o),
+            other => {
+                self.grid.grid[position[0]][position[1]] = other;
+                return Err(PortGridError::NotComponent);
+            }
+        };
+        let (old_size, new_size) = (component.oriented_grid_size(old_orientation), component.oriented_grid_size(orientation));
+        let (old_ports, new_ports) = (component.oriented_ports(old_orientation), component.oriented_ports(orientation));
+        let pixel_size = component.size();
+        self.grid.remove_component(position, old_size);
+        if self.grid.can_fit(position, &new_size) == false {
+            self.grid.place_component(entity_id, component, position, old_orientation);
+            return Err(PortGridError::CantFit);
+        }
+        self.grid.place_component(entity_id, component, position, orientation);
+        self.port_grid.modify_bulk(None, old_ports.iter().collect(), position);
+        self.port_grid.modify_bulk(Some(PortGridPort::default()), new_ports.iter().collect(), position);
+
+        if let Ok(mut sprite) = component_sprites.get_mut(entity_id) {
+            sprite.flip_x = orientation.mirrored;
+        }
+
+        // Both the old and new surroundings may have wires which need to connect or disconnect
+        let mut adjacent = helpers::get_adjacent(position, &old_size);
+        adjacent.extend(helpers::get_adjacent(position, &new_size));
+        adjacent.push(*position);
+        for component in adjacent {
+            self.update_component(&component, component_sprites, atlas);
+        }
+        Ok((entity_id, pixel_size))
+    }
+
     pub fn update_component(
         &mut self,
         position: &[usize; 2],
@@ -113,7 +200,7 @@ impl SimulationData {
         let cell = self.grid.grid.get_mut(position[0])?.get_mut(position[1])?;
         //let cell = std::mem::replace(self.grid.grid.get_mut(position[0])?.get_mut(position[1])?, CellState::Empty);
 
-        if let CellState::Real(id, comp) = cell {
+        if let CellState::Real(id, comp, _) = cell {
             let mut sprite = component_sprites.get_mut(*id).unwrap();
             let mut current = std::mem::replace(comp, Component::SignalPassthrough(SignalPassthrough::default()));
             let comp = comp as *mut Component;
@@ -132,13 +219,25 @@ impl SimulationData {
 #[derive(Debug, Default, Reflect, Serialize, Deserialize, Clone)]
 pub struct ComponentGrid {
     pub grid: Vec<Vec<CellState>>,
+    /// Cells of components the level gives the player and won't let them change
+    #[reflect(ignore)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub locked: Vec<[usize; 2]>,
 }
 
 pub enum PortGridError {
     CantFit,
+    NotComponent,
+    /// The level doesn't let the player change this component
+    Locked,
 }
 
 impl ComponentGrid {
+    /// Whether the component with its bottom left at `position` can't be moved, removed or changed
+    pub fn is_locked(&self, position: &[usize; 2]) -> bool {
+        self.locked.contains(position)
+    }
+
     pub fn can_fit(&self, position: &[usize; 2], size: &[usize; 2]) -> bool {
         for x in position[0]..(position[0] + size[0]) {
             let row = match self.grid.get(x) {
@@ -155,7 +254,7 @@ impl ComponentGrid {
                     Some(c) => match c {
                         CellState::Empty => {}
                         CellState::Reference(_) => return false,
-                        CellState::Real(_, _) => return false,
+                        CellState::Real(..) => return false,
                     },
                 }
             }
@@ -164,15 +263,15 @@ impl ComponentGrid {
     }
 
     /// Place a component in the grid, does not perform any overlap checks, these are done elsewhere. See [`Self::add_default_component()`]
-    fn place_component(&mut self, entity_id: Entity, component: Component, position: &[usize; 2]) {
-        let component_size = component.dummy().get_grid_size();
+    fn place_component(&mut self, entity_id: Entity, component: Component, position: &[usize; 2], orientation: Orientation) {
+        let component_size = component.oriented_grid_size(orientation);
         let mut first = true; // Used to determin if to insert a real component or a grid reference
         for i in position[0]..(position[0] + component_size[0]) {
             for j in position[1]..(position[1] + component_size[1]) {
                 match first {
                     true => {
                         first = false;
-                        self.grid[i][j] = CellState::Real(entity_id, component.clone());
+                        self.grid[i][j] = CellState::Real(entity_id, component.clone(), orientation);
                     }
                     false => self.grid[i][j] = CellState::Reference(*position),
                 }
@@ -180,19 +279,46 @@ impl ComponentGrid {
         }
     }
 
-    fn remove_component(&mut self, component: DummyComponent, position: &[usize; 2]) {
-        let component_size = component.get_grid_size();
+    fn remove_component(&mut self, position: &[usize; 2], component_size: [usize; 2]) {
         for i in position[0]..(position[0] + component_size[0]) {
             for j in position[1]..(position[1] + component_size[1]) {
                 self.grid[i][j] = CellState::Empty;
             }
         }
     }
+
+    /// Point every [CellState::Reference] back at its real component.\
+    /// The positions are not saved so this is needed after loading a grid which is not placed component by component.
+    pub fn rebuild_references(&mut self) {
+        for x in 0..self.grid.len() {
+            for y in 0..self.grid[x].len() {
+                if let CellState::Reference(_) = self.grid[x][y] {
+                    self.grid[x][y] = CellState::Empty;
+                }
+            }
+        }
+        for x in 0..self.grid.len() {
+            for y in 0..self.grid[x].len() {
+                let size = match &self.grid[x][y] {
+                    CellState::Real(_, component, orientation) => component.oriented_grid_size(*orientation),
+                    _ => continue,
+                };
+                for i in x..(x + size[0]).min(self.grid.len()) {
+                    for j in y..(y + size[1]).min(self.grid[i].len()) {
+                        if [i, j] != [x, y] {
+                            self.grid[i][j] = CellState::Reference([x, y]);
+                        }
+                    }
+                }
+            }
+        }
+    }
 }
 /// Contains Marker varients to pass around when wanting to create or refer to a type without all the data attached
 #[derive(Debug, Eq, PartialEq, Ord, PartialOrd, EnumIter, Clone, Copy)]
 pub enum DummyComponent {
     WirePiece,
+    WireCrossover,
     GateNot,
     GateAnd,
     SignalCopy,
@@ -200,6 +326,32 @@ pub enum DummyComponent {
     Counter,
     Observer,
     Provider,
+    Constant,
+    ToggleSwitch,
+    PushButton,
+    Delay,
+    Led,
+    SevenSegment,
+    PixelMatrix,
+    SchmittTrigger,
+    Scaler,
+    Mixer,
+    MinMax,
+    AbsDifference,
+    RandomSource,
+    Package,
+    Scripted,
+    BitShift,
+    BitMask,
+    BitExtract,
+    BitPack,
+    FifoQueue,
+    LifoStack,
+    StateMachine,
+    TunnelSend,
+    TunnelReceive,
+    PullUp,
+    PullDown,
 }
 
 #[derive(Debug, Clone, Reflect, FromReflect, Serialize, Deserialize)]
@@ -210,8 +362,8 @@ pub enum CellState {
     /// Stores the grid co-ordinates for master component
     #[serde(rename = "Ref")]
     Reference(#[serde(skip)] [usize; 2]),
-    /// Contains a master component
-    Real(#[serde(skip, default = "default_entity_fix")] Entity, Component),
+    /// Contains a master component and which way it is turned
+    Real(#[serde(skip, default = "default_entity_fix")] Entity, Component, #[serde(default)] Orientation),
 }
 
 fn default_entity_fix() -> Entity {
@@ -223,6 +375,7 @@ fn default_entity_fix() -> Entity {
 #[enum_dispatch]
 pub enum Component {
     WirePiece(Wire),
+    WireCrossover(WireCrossover),
     GateNot(GateNot),
     GateAnd(GateAnd),
     SignalCopy(SignalCopy),
@@ -230,6 +383,32 @@ pub enum Component {
     Counter(Counter),
     Observer(Observer),
     Provider(Provider),
+    Constant(Constant),
+    ToggleSwitch(ToggleSwitch),
+    PushButton(PushButton),
+    Delay(Delay),
+    Led(Led),
+    SevenSegment(SevenSegment),
+    PixelMatrix(PixelMatrix),
+    SchmittTrigger(SchmittTrigger),
+    Scaler(Scaler),
+    Mixer(Mixer),
+    MinMax(MinMax),
+    AbsDifference(AbsDifference),
+    RandomSource(RandomSource),
+    Package(Package),
+    Scripted(Scripted),
+    BitShift(BitShift),
+    BitMask(BitMask),
+    BitExtract(BitExtract),
+    BitPack(BitPack),
+    FifoQueue(FifoQueue),
+    LifoStack(LifoStack),
+    StateMachine(StateMachine),
+    TunnelSend(TunnelSend),
+    TunnelReceive(TunnelReceive),
+    PullUp(PullUp),
+    PullDown(PullDown),
 }
 
 /// A struct to contain any (for player only) visual events to help with understanding whats happening\
@@ -274,9 +453,13 @@ pub trait GridComponent {
     /// Fetch a Vec of ports for use in the port grid
     fn ports(&self) -> Vec<&([usize; 2], Side)>;
 
-    fn set_port(&mut self, offset: [usize; 2], side: Side, set_to: Arc<AtomicU8>) -> Result<(),() >;
+    fn set_port(&mut self, offset: [usize; 2], side: Side, set_to: Arc<Net>) -> Result<(),() >;
 
     fn gui_options(&mut self, ui: &mut Ui, sim_halted: bool, dummy: DummyComponent, pos: &[usize; 2], updates: &mut EventWriter<UpdateComponentEvent>);
+
+    /// When the player clicks the component while the simulation is running (or paused)\
+    /// Only interactive components such as switches and buttons need to do anything here
+    fn interact(&mut self, _sprite: &mut TextureAtlasSprite, _atlas: &TextureAtlas) {}
 }
 
 
@@ -284,29 +467,30 @@ pub trait GridComponent {
 /// .get() to read\
 /// .set() to write
 #[derive(Default, Clone, Debug)]
-pub struct ComponentPortData(Option<Arc<AtomicU8>>);
+pub struct ComponentPortData(Option<Arc<Net>>);
 
 impl ComponentPortData {
     /// Read the value of a port, if no connection return the default value (0).
     pub fn get(&self) -> u8 {
         match &self.0 {
             None => 0,
-            Some(p) => p.load(Ordering::Relaxed),
+            Some(p) => p.load(),
         }
     }
 
-    /// Set the value of a port, if no connection ignore.
+    /// Set the value of a port, if no connection ignore.\
+    /// Other writes to the net in the same tick are combined by its [super::net::NetResolution].
     pub fn set(&self, val: u8) {
         match &self.0 {
             None => {},
             Some(p) => {
-                p.store(val, Ordering::Relaxed);
+                p.drive(val);
             }
         }
     }
 
-    /// Take in an [Option<Arc<AtomicU8>>] and sets the internal state.
-    pub fn set_link(&mut self, link: Option<Arc<AtomicU8>>) {
+    /// Take in an [Option<Arc<Net>>] and sets the internal state.
+    pub fn set_link(&mut self, link: Option<Arc<Net>>) {
         self.0 = link;
     }
 }
\ No newline at end of file
@@ -0,0 +1,261 @@
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use bevy::reflect::{FromReflect, Reflect};
+use serde::{Deserialize, Serialize};
+
+/// What a net does when more than one component writes to it in the same tick.\
+/// The first write each tick always replaces the value from the last tick, later writes are combined with it.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Reflect, FromReflect)]
+pub enum NetResolution {
+    /// Highest value wins
+    #[default]
+    WiredOr,
+    /// Lowest value wins
+    WiredAnd,
+    /// Values are added, stopping at 255
+    Sum,
+    /// Two different values is a mistake, the simulation pauses and says where
+    Error,
+}
+
+impl NetResolution {
+    pub const ALL: [NetResolution; 4] = [NetResolution::WiredOr, NetResolution::WiredAnd, NetResolution::Sum, NetResolution::Error];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NetResolution::WiredOr => "Wired OR (max)",
+            NetResolution::WiredAnd => "Wired AND (min)",
+            NetResolution::Sum => "Sum",
+            NetResolution::Error => "Error",
+        }
+    }
+
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => NetResolution::WiredAnd,
+            2 => NetResolution::Sum,
+            3 => NetResolution::Error,
+            _ => NetResolution::WiredOr,
+        }
+    }
+
+    fn as_u8(&self) -> u8 {
+        match self {
+            NetResolution::WiredOr => 0,
+            NetResolution::WiredAnd => 1,
+            NetResolution::Sum => 2,
+            NetResolution::Error => 3,
+        }
+    }
+}
+
+/// A group of ports joined together by wires, made in [super::run::build_nets] and shared between every component on it
+#[derive(Debug, Default)]
+pub struct Net {
+    value: AtomicU8,
+    /// Stored as a [NetResolution] so wires can change it while the net is being flood filled
+    resolution: AtomicU8,
+    /// Whether anything has written to the net yet this tick
+    written: AtomicBool,
+    /// Whether a pull-up or pull-down is on the net, giving it `weak` when nothing else drives it
+    pulled: AtomicBool,
+    weak: AtomicU8,
+    conflict: AtomicBool,
+    /// Whether a wire on the net has picked its resolution
+    overridden: AtomicBool,
+    /// Whether wires on the net picked different resolutions, a conflict which lasts until the net is rebuilt
+    mixed_overrides: AtomicBool,
+    /// A cell the net passes through, to point at when there is a conflict
+    origin: [usize; 2],
+}
+
+impl Net {
+    pub fn new(resolution: NetResolution, origin: [usize; 2]) -> Self {
+        Net {
+            resolution: AtomicU8::new(resolution.as_u8()),
+            origin,
+            ..Default::default()
+        }
+    }
+
+    pub fn load(&self) -> u8 {
+        self.value.load(Ordering::Relaxed)
+    }
+
+    /// Write to the net, combining with anything else written this tick by its [NetResolution]
+    pub fn drive(&self, value: u8) {
+        if !self.written.swap(true, Ordering::Relaxed) {
+            self.value.store(value, Ordering::Relaxed);
+            return;
+        }
+        let current = self.value.load(Ordering::Relaxed);
+        let resolved = match self.resolution() {
+            NetResolution::WiredOr => current.max(value),
+            NetResolution::WiredAnd => current.min(value),
+            NetResolution::Sum => current.saturating_add(value),
+            NetResolution::Error => {
+                if current != value {
+                    self.conflict.store(true, Ordering::Relaxed);
+                }
+                value
+            },
+        };
+        self.value.store(resolved, Ordering::Relaxed);
+    }
+
+    pub fn resolution(&self) -> NetResolution {
+        NetResolution::from_u8(self.resolution.load(Ordering::Relaxed))
+    }
+
+    pub fn set_resolution(&self, resolution: NetResolution) {
+        self.resolution.store(resolution.as_u8(), Ordering::Relaxed);
+    }
+
+    /// Used by a wire which picks its own resolution instead of the level's.\
+    /// Otherwise the last wire built would win, so wires picking different ones is a conflict, see [Self::take_conflict]
+    pub fn override_resolution(&self, resolution: NetResolution) {
+        if self.overridden.swap(true, Ordering::Relaxed) && self.resolution() != resolution {
+            self.mixed_overrides.store(true, Ordering::Relaxed);
+        }
+        self.set_resolution(resolution);
+    }
+
+    /// Called before every component ticks so the first write replaces last tick's value.\
+    /// If nothing drove the net last tick it falls back to its weak value, if it has one.
+    pub fn start_tick(&self) {
+        let driven = self.written.swap(false, Ordering::Relaxed);
+        if !driven && self.pulled.load(Ordering::Relaxed) {
+            self.value.store(self.weak.load(Ordering::Relaxed), Ordering::Relaxed);
+        }
+    }
+
+    /// Give the net a weak value, any write from a component overrides it.\
+    /// Called while building so the net starts at this value. If pulled both ways the higher value wins.
+    pub fn pull(&self, value: u8) {
+        let weak = match self.pulled.swap(true, Ordering::Relaxed) {
+            true => self.weak.load(Ordering::Relaxed).max(value),
+            false => value,
+        };
+        self.weak.store(weak, Ordering::Relaxed);
+        self.value.store(weak, Ordering::Relaxed);
+    }
+
+    /// Where the net is, if two different values were written with [NetResolution::Error] or its wires picked different resolutions.\
+    /// Clears a conflict from writes, but different resolutions are reported every time.
+    pub fn take_conflict(&self) -> Option<[usize; 2]> {
+        let written = self.conflict.swap(false, Ordering::Relaxed);
+        match written || self.mixed_overrides.load(Ordering::Relaxed) {
+            true => Some(self.origin),
+            false => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORIGIN: [usize; 2] = [3, 4];
+
+    /// Value on a fresh net after writing each of `values` in one tick
+    fn drive_all(resolution: NetResolution, values: &[u8]) -> Net {
+        let net = Net::new(resolution, ORIGIN);
+        net.start_tick();
+        for value in values {
+            net.drive(*value);
+        }
+        net
+    }
+
+    #[test]
+    fn wired_or_keeps_the_highest() {
+        assert_eq!(drive_all(NetResolution::WiredOr, &[10, 200, 50]).load(), 200);
+    }
+
+    #[test]
+    fn wired_and_keeps_the_lowest() {
+        assert_eq!(drive_all(NetResolution::WiredAnd, &[10, 200, 50]).load(), 10);
+    }
+
+    #[test]
+    fn sum_adds_up_to_255() {
+        assert_eq!(drive_all(NetResolution::Sum, &[10, 20, 30]).load(), 60);
+        assert_eq!(drive_all(NetResolution::Sum, &[200, 100]).load(), 255);
+    }
+
+    #[test]
+    fn error_only_conflicts_on_different_values() {
+        let same = drive_all(NetResolution::Error, &[80, 80]);
+        assert_eq!(same.load(), 80);
+        assert_eq!(same.take_conflict(), None);
+
+        let different = drive_all(NetResolution::Error, &[80, 90]);
+        assert_eq!(different.take_conflict(), Some(ORIGIN));
+    }
+
+    #[test]
+    fn single_driver_is_the_same_for_every_resolution() {
+        for resolution in NetResolution::ALL {
+            let net = drive_all(resolution, &[123]);
+            assert_eq!(net.load(), 123);
+            assert_eq!(net.take_conflict(), None);
+        }
+    }
+
+    #[test]
+    fn start_tick_resets_written() {
+        for resolution in NetResolution::ALL {
+            let net = drive_all(resolution, &[200]);
+            net.start_tick();
+            net.drive(10);
+            assert_eq!(net.load(), 10, "{:?} should replace last tick's value", resolution);
+            assert_eq!(net.take_conflict(), None);
+        }
+    }
+
+    #[test]
+    fn start_tick_keeps_the_value_when_not_pulled() {
+        let net = drive_all(NetResolution::WiredOr, &[200]);
+        net.start_tick();
+        net.start_tick();
+        assert_eq!(net.load(), 200);
+    }
+
+    #[test]
+    fn pulled_net_falls_back_when_not_driven() {
+        let net = Net::new(NetResolution::WiredOr, ORIGIN);
+        net.pull(255);
+        assert_eq!(net.load(), 255);
+        net.start_tick();
+        net.drive(0);
+        assert_eq!(net.load(), 0);
+        net.start_tick();
+        assert_eq!(net.load(), 0);
+        net.start_tick();
+        assert_eq!(net.load(), 255);
+    }
+
+    #[test]
+    fn take_conflict_clears_it() {
+        let net = drive_all(NetResolution::Error, &[1, 2]);
+        assert_eq!(net.take_conflict(), Some(ORIGIN));
+        assert_eq!(net.take_conflict(), None);
+    }
+
+    #[test]
+    fn matching_overrides_are_fine() {
+        let net = Net::new(NetResolution::WiredOr, ORIGIN);
+        net.override_resolution(NetResolution::Sum);
+        net.override_resolution(NetResolution::Sum);
+        assert_eq!(net.resolution(), NetResolution::Sum);
+        assert_eq!(net.take_conflict(), None);
+    }
+
+    #[test]
+    fn different_overrides_always_conflict() {
+        let net = Net::new(NetResolution::WiredOr, ORIGIN);
+        net.override_resolution(NetResolution::Sum);
+        net.override_resolution(NetResolution::WiredAnd);
+        assert_eq!(net.take_conflict(), Some(ORIGIN));
+        assert_eq!(net.take_conflict(), Some(ORIGIN));
+    }
+}
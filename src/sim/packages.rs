@@ -0,0 +1,324 @@
+use std::{collections::BTreeMap, fmt, fs::File, path::PathBuf, sync::Arc};
+use bevy::prelude::*;
+use glob::glob;
+use serde::{Deserialize, Serialize};
+use crate::GameState;
+use super::{
+    components::Package,
+    definitions::ComponentRegistry,
+    helpers::Side,
+    model::{CellState, Component, ComponentGrid},
+    port_grid::{Port, PortGrid},
+};
+
+/// Where the user library of packages is kept, one json file per package
+pub const PACKAGE_DIR: &str = "data/packages";
+/// Packages can be at most this many cells in each direction
+pub const MAX_PACKAGE_SIZE: usize = 3;
+
+pub struct PackagePlugin;
+
+impl Plugin for PackagePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PackageLibrary>()
+            .add_system(reload_library.in_schedule(OnEnter(GameState::InGame)));
+    }
+}
+
+fn reload_library(mut library: ResMut<PackageLibrary>, registry: Res<ComponentRegistry>) {
+    *library = PackageLibrary::load(&registry);
+}
+
+/// A circuit cut out of a grid to be used as a single component
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackageDefinition {
+    pub name: String,
+    #[serde(default)]
+    pub desc: String,
+    /// Size of the package on the grid, not the circuit inside
+    pub size: [usize; 2],
+    pub pins: Vec<PackagePin>,
+    /// The circuit inside of the package
+    pub grid: ComponentGrid,
+}
+
+/// Links a port on the outside of a package to an edge of the circuit inside
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackagePin {
+    pub name: String,
+    /// Cell of the inner circuit on the edge the pin connects to
+    pub inner_pos: [usize; 2],
+    /// Side of `inner_pos` facing out of the inner circuit
+    pub inner_side: Side,
+    /// Where the port is on the outside of the package
+    pub offset: [usize; 2],
+    pub side: Side,
+}
+
+/// An edge of a selected region which has a port or wire leading out of it, so could become a pin\
+/// `inner_pos` is relative to the bottom left of the region
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PinCandidate {
+    pub inner_pos: [usize; 2],
+    pub side: Side,
+}
+
+#[derive(Debug)]
+pub enum PackageError {
+    EmptyName,
+    BadSize,
+    OutsideGrid,
+    /// A component crosses the edge of the selected region
+    CutComponent([usize; 2]),
+    /// Providers and observers belong to the level and cannot be packaged
+    LevelComponent([usize; 2]),
+    /// The package contains an older package with the same name
+    ContainsItself,
+    NoPins,
+    TooManyPins(Side),
+}
+
+impl fmt::Display for PackageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PackageError::EmptyName => write!(f, "The package needs a name"),
+            PackageError::BadSize => write!(f, "Packages must be between 1 and {} cells in each direction", MAX_PACKAGE_SIZE),
+            PackageError::OutsideGrid => write!(f, "The selected region goes off the edge of the grid"),
+            PackageError::CutComponent(pos) => write!(f, "The component at {:?} crosses the edge of the selected region", pos),
+            PackageError::LevelComponent(pos) => write!(f, "The level input / output at {:?} cannot be packaged", pos),
+            PackageError::ContainsItself => write!(f, "A package cannot contain another package with the same name"),
+            PackageError::NoPins => write!(f, "Pick at least one pin"),
+            PackageError::TooManyPins(side) => write!(f, "Too many pins on the {} side for the size of the package", side.as_str()),
+        }
+    }
+}
+
+impl PackageDefinition {
+    /// Every edge of the region with a port or wire leading out of it
+    pub fn pin_candidates(grid: &ComponentGrid, port_grid: &PortGrid, min: [usize; 2], size: [usize; 2]) -> Vec<PinCandidate> {
+        let mut candidates = Vec::new();
+        for x in 0..size[0] {
+            for y in 0..size[1] {
+                let pos = [min[0] + x, min[1] + y];
+                let cell = match grid.grid.get(pos[0]).and_then(|c| c.get(pos[1])) {
+                    Some(c) => c,
+                    None => continue,
+                };
+                let mut outward = Vec::with_capacity(2);
+                if x == 0 {outward.push(Side::Left)}
+                if x == size[0] - 1 {outward.push(Side::Right)}
+                if y == 0 {outward.push(Side::Down)}
+                if y == size[1] - 1 {outward.push(Side::Up)}
+
+                for side in outward {
+                    let leads_out = match cell {
+                        CellState::Real(_, Component::WirePiece(w), _) => w.connected_sides[side],
+                        CellState::Real(_, Component::WireCrossover(c), _) => c.connected_sides[side],
+                        CellState::Empty => false,
+                        _ => matches!(port_grid.get_port_inside(&pos, side), Ok(Some(_))),
+                    };
+                    if leads_out {
+                        candidates.push(PinCandidate { inner_pos: [x, y], side });
+                    }
+                }
+            }
+        }
+        candidates
+    }
+
+    /// Cut the region out of the grid, laying the chosen pins out along the matching edges of the package.\
+    /// Pins keep their order along each edge, top to bottom and left to right.
+    pub fn from_region(
+        grid: &ComponentGrid,
+        min: [usize; 2],
+        size: [usize; 2],
+        name: &str,
+        desc: &str,
+        package_size: [usize; 2],
+        pins: &[(String, PinCandidate)],
+    ) -> Result<Self, PackageError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(PackageError::EmptyName);
+        }
+        if package_size.iter().any(|s| *s == 0 || *s > MAX_PACKAGE_SIZE) {
+            return Err(PackageError::BadSize);
+        }
+        if size[0] == 0 || size[1] == 0 || min[0] + size[0] > grid.grid.len() || min[1] + size[1] > grid.grid[0].len() {
+            return Err(PackageError::OutsideGrid);
+        }
+        if pins.is_empty() {
+            return Err(PackageError::NoPins);
+        }
+
+        let mut inner = vec![vec![CellState::Empty; size[1]]; size[0]];
+        for x in 0..size[0] {
+            for y in 0..size[1] {
+                let pos = [min[0] + x, min[1] + y];
+                inner[x][y] = match &grid.grid[pos[0]][pos[1]] {
+                    CellState::Empty => CellState::Empty,
+                    CellState::Reference(real) => {
+                        if real[0] < min[0] || real[1] < min[1] {
+                            return Err(PackageError::CutComponent(*real));
+                        }
+                        CellState::Reference([real[0] - min[0], real[1] - min[1]])
+                    },
+                    CellState::Real(_, component, orientation) => {
+                        let footprint = component.oriented_grid_size(*orientation);
+                        if x + footprint[0] > size[0] || y + footprint[1] > size[1] {
+                            return Err(PackageError::CutComponent(pos));
+                        }
+                        match component {
+                            Component::Provider(_) | Component::Observer(_) => return Err(PackageError::LevelComponent(pos)),
+                            Component::Package(p) if p.name() == name => return Err(PackageError::ContainsItself),
+                            _ => {},
+                        }
+                        CellState::Real(Entity::PLACEHOLDER, component.clone(), *orientation)
+                    },
+                };
+            }
+        }
+
+        let mut placed_pins = Vec::with_capacity(pins.len());
+        for side in [Side::Left, Side::Right, Side::Up, Side::Down] {
+            let mut on_side: Vec<&(String, PinCandidate)> = pins.iter().filter(|(_, c)| c.side == side).collect();
+            let edge_length = match side.axis() {
+                0 => package_size[0],
+                _ => package_size[1],
+            };
+            if on_side.len() > edge_length {
+                return Err(PackageError::TooManyPins(side));
+            }
+            match side.axis() {
+                0 => on_side.sort_by_key(|(_, c)| c.inner_pos[0]),
+                _ => on_side.sort_by_key(|(_, c)| std::cmp::Reverse(c.inner_pos[1])),
+            }
+            for (i, (pin_name, candidate)) in on_side.into_iter().enumerate() {
+                let offset = match side {
+                    Side::Left => [0, package_size[1] - 1 - i],
+                    Side::Right => [package_size[0] - 1, package_size[1] - 1 - i],
+                    Side::Up => [i, package_size[1] - 1],
+                    Side::Down => [i, 0],
+                };
+                placed_pins.push(PackagePin {
+                    name: pin_name.clone(),
+                    inner_pos: candidate.inner_pos,
+                    inner_side: candidate.side,
+                    offset,
+                    side,
+                });
+            }
+        }
+
+        Ok(PackageDefinition {
+            name: name.to_string(),
+            desc: desc.to_string(),
+            size: package_size,
+            pins: placed_pins,
+            grid: ComponentGrid { grid: inner, locked: Vec::new() },
+        })
+    }
+
+    /// A fresh copy of the inner circuit with its port grid, ready to be built
+    pub fn instantiate(&self) -> (Vec<Vec<CellState>>, PortGrid) {
+        let mut grid = self.grid.clone();
+        grid.rebuild_references();
+        let width = grid.grid.len();
+        let height = grid.grid.first().map(|c| c.len()).unwrap_or(0);
+        let mut port_grid = PortGrid::new_with_size(height, width);
+        for x in 0..width {
+            for y in 0..height {
+                if let CellState::Real(_, component, orientation) = &grid.grid[x][y] {
+                    port_grid.modify_bulk(Some(Port::default()), component.oriented_ports(*orientation).iter().collect(), &[x, y]);
+                }
+            }
+        }
+        (grid.grid, port_grid)
+    }
+
+    fn path(&self) -> PathBuf {
+        let file_name: String = self.name.chars().map(|c| if c.is_ascii_alphanumeric() {c} else {'_'}).collect();
+        PathBuf::from(format!("{}/{}.json", PACKAGE_DIR, file_name))
+    }
+}
+
+/// Every package the player has made, saves refer to these by name
+#[derive(Resource, Debug, Default)]
+pub struct PackageLibrary(pub BTreeMap<String, Arc<PackageDefinition>>);
+
+impl PackageLibrary {
+    /// Read every package in [PACKAGE_DIR], any which fail to parse are skipped.\
+    /// Packages can contain data driven components so those are looked up in `registry`.
+    pub fn load(registry: &ComponentRegistry) -> Self {
+        let mut raw = BTreeMap::new();
+        for path in glob(&format!("{}/*.json", PACKAGE_DIR)).expect("Failed to read glob pattern").flatten() {
+            let parsed = File::open(&path).ok().and_then(|file| serde_json::from_reader::<_, PackageDefinition>(file).ok());
+            match parsed {
+                Some(mut definition) => {
+                    registry.attach(&mut definition.grid);
+                    raw.insert(definition.name.clone(), definition);
+                },
+                None => error!("Could not parse package {:?}", path),
+            }
+        }
+
+        let mut library = BTreeMap::new();
+        let names: Vec<String> = raw.keys().cloned().collect();
+        for name in names {
+            resolve(&name, &mut raw, &mut library);
+        }
+        PackageLibrary(library)
+    }
+
+    /// Write a new package to disk and add it to the library, replacing any with the same name
+    pub fn add(&mut self, definition: PackageDefinition) -> std::io::Result<()> {
+        std::fs::create_dir_all(PACKAGE_DIR)?;
+        let file = File::create(definition.path())?;
+        serde_json::to_writer(file, &definition)?;
+        self.0.insert(definition.name.clone(), Arc::new(definition));
+        Ok(())
+    }
+
+    pub fn build(&self, name: &str) -> Option<Component> {
+        self.0.get(name).map(|definition| Component::Package(Package::new(definition.clone())))
+    }
+
+    /// Saves only store the name of a package, this looks each one up again
+    pub fn attach(&self, grid: &mut ComponentGrid) {
+        for column in grid.grid.iter_mut() {
+            for cell in column.iter_mut() {
+                if let CellState::Real(_, Component::Package(package), _) = cell {
+                    let definition = self.0.get(package.name()).cloned();
+                    if definition.is_none() {
+                        error!("Could not find package {} in the library", package.name());
+                    }
+                    package.set_definition(definition);
+                }
+            }
+        }
+    }
+}
+
+/// Packages can contain other packages so look those up first.\
+/// Each is taken out of `raw` before resolving what it contains so a loop of packages cannot recurse forever
+fn resolve(
+    name: &str,
+    raw: &mut BTreeMap<String, PackageDefinition>,
+    library: &mut BTreeMap<String, Arc<PackageDefinition>>,
+) -> Option<Arc<PackageDefinition>> {
+    if let Some(definition) = library.get(name) {
+        return Some(definition.clone());
+    }
+    let mut definition = raw.remove(name)?;
+    for column in definition.grid.grid.iter_mut() {
+        for cell in column.iter_mut() {
+            if let CellState::Real(_, Component::Package(package), _) = cell {
+                let inner = resolve(package.name(), raw, library);
+                package.set_definition(inner);
+            }
+        }
+    }
+    let definition = Arc::new(definition);
+    library.insert(name.to_string(), definition.clone());
+    Some(definition)
+}
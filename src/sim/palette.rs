@@ -0,0 +1,77 @@
+//! Which components a level lets the player place and how many of each, e.g.
+//! ```json
+//! "palette": {"allowed": ["WirePiece", "GateNot", "GateAnd"], "limits": {"GateNot": 3}}
+//! ```
+use bevy::utils::HashMap;
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+
+use super::model::{CellState, ComponentGrid, DummyComponent};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Palette {
+    /// Only these can be placed, anything can be when left out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed: Option<Vec<String>>,
+    /// Most of a component the player can place, not counting any the level starts with
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub limits: HashMap<String, usize>,
+}
+
+/// Why a component can't be placed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaletteError {
+    NotAllowed,
+    OverBudget,
+}
+
+/// The name levels use for a component, the same as in their `start` grid
+pub fn component_key(component: DummyComponent) -> String {
+    format!("{:?}", component)
+}
+
+/// How many of each component are on the grid, by [component_key]
+pub fn count_components(grid: &ComponentGrid) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for cell in grid.grid.iter().flatten() {
+        if let CellState::Real(_, component, _) = cell {
+            *counts.entry(component_key(component.dummy())).or_insert(0) += 1;
+        }
+    }
+    counts
+}
+
+impl Palette {
+    pub fn allows(&self, component: DummyComponent) -> bool {
+        let key = component_key(component);
+        match &self.allowed {
+            Some(allowed) => allowed.contains(&key),
+            None => true,
+        }
+    }
+
+    /// How many more the player can place with `placed` already down, `None` if there is no limit
+    pub fn remaining(&self, component: DummyComponent, placed: usize) -> Option<usize> {
+        self.limits.get(&component_key(component)).map(|limit| limit.saturating_sub(placed))
+    }
+
+    pub fn check(&self, component: DummyComponent, placed: usize) -> Result<(), PaletteError> {
+        if !self.allows(component) {
+            return Err(PaletteError::NotAllowed);
+        }
+        match self.remaining(component, placed) {
+            Some(0) => Err(PaletteError::OverBudget),
+            _ => Ok(()),
+        }
+    }
+
+    /// Names in the palette which aren't components
+    pub fn unknown_keys(&self) -> Vec<String> {
+        let known: Vec<String> = DummyComponent::iter().map(component_key).collect();
+        self.allowed.iter().flatten()
+            .chain(self.limits.keys())
+            .filter(|key| !known.contains(key))
+            .cloned()
+            .collect()
+    }
+}
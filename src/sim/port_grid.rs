@@ -98,7 +98,10 @@ impl PortGrid {
     ) -> Result<(), PortGridError> {
         match side {
             Side::Up => self.insert_top(position, Side::Down, port),
-            Side::Down => self.insert_top(&[position[0], position[1] - 1], Side::Up, port),
+            Side::Down => {
+                self.get_mut_below(position)?.top.origin_up = port;
+                Ok(())
+            },
             Side::Left => self.insert_left(position, Side::Right, port),
             Side::Right => self.insert_left(&[position[0] + 1, position[1]], Side::Left, port),
         }
@@ -160,6 +163,22 @@ impl PortGrid {
         Ok(item)
     }
 
+    /// The cell storing the bottom edge of `position`, which is the top edge of the cell below.\
+    /// Unlike going through [Self::get_mut] this still works on the bottom row
+    fn get_mut_below(&mut self, position: &[usize; 2]) -> Result<&mut PortGridData, PortGridError> {
+        self.0
+            .get_mut(position[0])
+            .and_then(|column| column.get_mut(position[1]))
+            .ok_or(PortGridError::PositionOutOfBounds)
+    }
+
+    fn get_below(&self, position: &[usize; 2]) -> Result<&PortGridData, PortGridError> {
+        self.0
+            .get(position[0])
+            .and_then(|column| column.get(position[1]))
+            .ok_or(PortGridError::PositionOutOfBounds)
+    }
+
     /// Clones target inserting it into the correct edge
     pub fn modify_bulk(
         &mut self,
@@ -201,7 +220,7 @@ impl PortGrid {
         match side {
             Side::Up => Ok(&mut self.get_mut(position)?.top.origin_up),
             Side::Down => Ok(&mut self
-                .get_mut(&[position[0], position[1].checked_sub(1).ok_or(PortGridError::PositionOutOfBounds)?])?
+                .get_mut_below(position)?
                 .top
                 .origin_down),
             Side::Left => Ok(&mut self.get_mut(position)?.left.origin_left),
@@ -218,7 +237,7 @@ impl PortGrid {
         match side {
             Side::Up => Ok(&self.get(position)?.top.origin_up),
             Side::Down => Ok(&self
-                .get(&[position[0], position[1].checked_sub(1).ok_or(PortGridError::PositionOutOfBounds)?])?
+                .get_below(position)?
                 .top
                 .origin_down),
             Side::Left => Ok(&self.get(position)?.left.origin_left),
@@ -242,11 +261,27 @@ impl PortGrid {
         Ok(item)
     }
 
+    /// The port belonging to the component at this position, as opposed to [Self::get_port] which gets the one facing into it
+    pub fn get_port_inside(&self, position: &[usize; 2], side: Side) -> Result<&Option<Port>, PortGridError> {
+        match side {
+            Side::Up => Ok(&self.get(position)?.top.origin_down),
+            Side::Down => Ok(&self
+                .get_below(position)?
+                .top
+                .origin_up),
+            Side::Left => Ok(&self.get(position)?.left.origin_right),
+            Side::Right => Ok(&self
+                .get(&[position[0] + 1, position[1]])?
+                .left
+                .origin_left),
+        }
+    }
+
     pub fn get_mut_port_inside(&mut self, position: &[usize; 2], side: Side) -> Result<&mut Option<Port>, PortGridError> {
         match side {
             Side::Up => Ok(&mut self.get_mut(position)?.top.origin_down),
             Side::Down => Ok(&mut self
-                .get_mut(&[position[0], position[1].checked_sub(1).ok_or(PortGridError::PositionOutOfBounds)?])?
+                .get_mut_below(position)?
                 .top
                 .origin_up),
             Side::Left => Ok(&mut self.get_mut(position)?.left.origin_right),
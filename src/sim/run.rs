@@ -1,7 +1,7 @@
-use std::sync::{Arc, atomic::AtomicU8};
+use std::{collections::BTreeMap, sync::Arc};
 use bevy::{prelude::*};
 
-use super::{model::{SimulationData, CellState, GridComponent, Component}, helpers::{self, Side}, port_grid::PortGrid};
+use super::{model::{SimulationData, CellState, GridComponent, Component}, helpers::{self, Side}, port_grid::PortGrid, net::{Net, NetResolution}, levels::LevelData};
 
 pub struct SimRunPlugin;
 
@@ -10,6 +10,7 @@ impl Plugin for SimRunPlugin {
         app
         .init_resource::<RunType>()
         .init_resource::<TickCounter>()
+        .init_resource::<NetConflict>()
         .add_state::<SimState>()
         .configure_sets(
             (
@@ -58,6 +59,11 @@ pub enum RunType {
 #[derive(Resource, Debug, Default, Clone, Copy)]
 pub struct TickCounter(usize);
 
+/// Set when two components write different values to a net using [NetResolution::Error],
+/// or the wires on a net pick different resolutions. The simulation pauses until it is fixed
+#[derive(Resource, Debug, Default, Clone, Copy)]
+pub struct NetConflict(pub Option<[usize; 2]>);
+
 impl TickCounter {
     pub fn increment(&mut self) {
         self.0 += 1;
@@ -87,6 +93,10 @@ pub fn sim_tick(
         RunType::Continuous => {},
     }
 
+    for net in &world.get_resource::<SimulationData>().unwrap().nets {
+        net.start_tick();
+    }
+
     let raw_world = world as *mut World;
     unsafe  {
         let mut sim_data = raw_world.as_mut().unwrap().get_resource_mut::<SimulationData>().unwrap();
@@ -97,7 +107,7 @@ pub fn sim_tick(
                 match &mut grid[x][y] {
                     CellState::Empty => {continue;},
                     CellState::Reference(_) => {continue;},
-                    CellState::Real(_, comp) => {
+                    CellState::Real(_, comp, _) => {
                         comp.tick([x,y], tick_count, raw_world.as_mut().unwrap());
                     },
                 }
@@ -105,18 +115,45 @@ pub fn sim_tick(
         }
     }
 
+    // Packages report conflicts inside them directly, so only take a new one if there is not already one
+    let conflict = world.get_resource::<SimulationData>().unwrap().nets.iter().filter_map(|net| net.take_conflict()).next();
+    let conflict = {
+        let mut net_conflict = world.get_resource_mut::<NetConflict>().unwrap();
+        if net_conflict.0.is_none() {
+            net_conflict.0 = conflict;
+        }
+        net_conflict.0
+    };
+    if let Some(position) = conflict {
+        error!("Multiple drivers on the net at {:?}", position);
+        world.insert_resource(NextState(Some(SimState::Paused)));
+    }
+
     // Increment tick counter
     world.get_resource_mut::<TickCounter>().unwrap().increment();
 }
 
 pub fn build_simulation(
-    mut sim_data: ResMut<SimulationData>
+    mut sim_data: ResMut<SimulationData>,
+    mut net_conflict: ResMut<NetConflict>,
+    level_data: Option<Res<LevelData>>,
 ) {
     println!("BUILDING");
     let sim_data = sim_data.as_mut();
-    let grid = &mut sim_data.grid.grid;
-    let port_grid = &mut sim_data.port_grid;
+    let resolution = level_data.map(|l| l.net_resolution).unwrap_or_default();
+    sim_data.nets = build_nets(&mut sim_data.grid.grid, &mut sim_data.port_grid, resolution);
+    net_conflict.0 = None;
+    dbg!(&sim_data.grid.grid);
+}
 
+/// Build every component then join up all the ports into nets, each sharing a single [Net]\
+/// Nets use `resolution` unless a wire on them overrides it, every net is returned so they can be reset each tick.\
+/// Also used by packages for the circuit inside of them
+pub fn build_nets(
+    grid: &mut Vec<Vec<CellState>>,
+    port_grid: &mut PortGrid,
+    resolution: NetResolution,
+) -> Vec<Arc<Net>> {
     for x in &mut port_grid.0 {
         for y in x {
             y.left.reset_build();
@@ -124,44 +161,105 @@ pub fn build_simulation(
         }
     }
 
-    let mut checked_grid = vec![vec![false; grid[0].len()]; grid.len()];
+    // Every component is built before any nets are joined so build() never throws away a link made by set_port()
+    for x in grid.iter_mut() {
+        for cell in x.iter_mut() {
+            if let CellState::Real(_, component, _) = cell {
+                if let Component::Package(package) = component {
+                    package.set_resolution(resolution);
+                }
+                component.build();
+            }
+        }
+    }
+
+    let mut checked_grid = vec![vec![[false; 2]; grid[0].len()]; grid.len()];
+    let mut nets = join_tunnels(grid, port_grid, &mut checked_grid, resolution);
     for x in 0..grid.len() {
         for y in 0..grid[x].len() {
-            let mut cell = &mut grid[x][y];
-            //let mut cell = std::mem::replace(&mut grid[x][y], CellState::Empty); // TODO: Revamp this code so I am never replacing the cell, this pains me to do.
-            if let CellState::Real(_, component) = &mut cell {
-                component.build();
-                // We do these map shenanigans as the .ports returns the [usize; 2]  REFERENCES, this meant that component had to be kept in scope even after the .ports has finished
-                // As a solution we just convert a Vec<&[usize; 2], Side> into a Vec<[usize; 2], Side>
-                let ports: Vec<([usize; 2], Side)> = component.ports().iter().map(|(pos, side)| {(pos.clone(), side.clone())}).collect();
-                for (offset, side) in ports {
-                    let position = [x+offset[0], y+offset[1]];
-                    if let Some(side_pos) = helpers::combine_offset(&position, &side.as_offset()) {
-                        if let Ok(pp) = port_grid.get_mut_port(&side_pos, side.reverse()) {
-                            if let Some(p) = pp {
-                                if p.checked == false {
-                                    let shared = Arc::new(AtomicU8::new(0)); // If statement mountain.
-                                    flood_fill(grid, port_grid, shared, side_pos, side.reverse(), &mut checked_grid);
-                                }
+            // Ports are stored unturned on the component so move them to where they actually are on the grid
+            let ports: Vec<([usize; 2], Side)> = match &grid[x][y] {
+                CellState::Real(_, component, orientation) => component.oriented_ports(*orientation),
+                _ => continue,
+            };
+            for (offset, side) in ports {
+                let position = [x+offset[0], y+offset[1]];
+                if let Some(side_pos) = helpers::combine_offset(&position, &side.as_offset()) {
+                    if let Ok(pp) = port_grid.get_mut_port(&side_pos, side.reverse()) {
+                        if let Some(p) = pp {
+                            if p.checked == false {
+                                let shared = Arc::new(Net::new(resolution, position)); // If statement mountain.
+                                flood_fill(grid, port_grid, shared.clone(), side_pos, side.reverse(), &mut checked_grid);
+                                nets.push(shared);
                             }
                         }
-                    } else {continue;}
+                    }
+                } else {continue;}
+            }
+        }
+    }
+    nets
+}
 
+/// Every tunnel receiver joins the net of the sender with the same label.\
+/// Done before any other nets are built so those reached through a tunnel are already checked and not given a net of their own.
+fn join_tunnels(
+    grid: &mut Vec<Vec<CellState>>,
+    port_grid: &mut PortGrid,
+    checked_grid: &mut Vec<Vec<[bool; 2]>>,
+    resolution: NetResolution,
+) -> Vec<Arc<Net>> {
+    // Label -> (whether it has a sender, positions of every tunnel using it)
+    let mut labels: BTreeMap<String, (bool, Vec<[usize; 2]>)> = BTreeMap::new();
+    for x in 0..grid.len() {
+        for y in 0..grid[x].len() {
+            let (label, is_sender) = match &grid[x][y] {
+                CellState::Real(_, Component::TunnelSend(tunnel), _) => (tunnel.label(), true),
+                CellState::Real(_, Component::TunnelReceive(tunnel), _) => (tunnel.label(), false),
+                _ => continue,
+            };
+            if label.is_empty() {continue}
+            let entry = labels.entry(label.to_string()).or_default();
+            entry.0 |= is_sender;
+            entry.1.push([x, y]);
+        }
+    }
+
+    let mut nets = Vec::new();
+    for (_, (has_sender, positions)) in labels {
+        if !has_sender {continue}
+        let shared = Arc::new(Net::new(resolution, positions[0]));
+        nets.push(shared.clone());
+        for position in positions {
+            let (offset, side) = match &mut grid[position[0]][position[1]] {
+                CellState::Real(_, component, orientation) => {
+                    let (own_offset, own_side) = *component.ports()[0];
+                    component.set_port(own_offset, own_side, shared.clone()).expect("Tunnels always have their own port");
+                    component.oriented_ports(*orientation)[0]
+                },
+                _ => continue,
+            };
+            // Then carry on into whatever is attached, the same as the nets built in [build_nets]
+            let port_pos = [position[0]+offset[0], position[1]+offset[1]];
+            if let Some(side_pos) = helpers::combine_offset(&port_pos, &side.as_offset()) {
+                if let Ok(Some(p)) = port_grid.get_mut_port(&side_pos, side.reverse()) {
+                    if p.checked == false {
+                        flood_fill(grid, port_grid, shared.clone(), side_pos, side.reverse(), checked_grid);
+                    }
                 }
             }
-            //grid[x][y] = cell;
         }
     }
-    dbg!(grid);
+    nets
 }
 
 pub fn flood_fill(
     grid: &mut Vec<Vec<CellState>>,
     port_grid: &mut PortGrid,
-    source_arc: Arc<AtomicU8>,
+    source_arc: Arc<Net>,
     position: [usize; 2],
     origin_side: Side,
-    has_propagated: &mut Vec<Vec<bool>>
+    has_propagated: &mut Vec<Vec<[bool; 2]>> // Tracked per axis (see [Side::axis]) so crossovers can carry two nets, wires mark both
 ) {
     let mut call_on_sides: Vec<Side> = Vec::new();
     if let Some(c) = grid.get_mut(position[0]) {
@@ -170,8 +268,9 @@ pub fn flood_fill(
                 CellState::Empty => {return;},
                 CellState::Reference(real_pos) => {
                     let real_pos = real_pos.clone();
-                    if let CellState::Real(_,c) = &mut grid[real_pos[0]][real_pos[1]] {
-                        c.set_port(get_difference(&position, &real_pos), origin_side, source_arc.clone()).expect("Portgrid & component grid missmatch");
+                    if let CellState::Real(_, c, orientation) = &mut grid[real_pos[0]][real_pos[1]] {
+                        let (offset, side) = orientation.invert(get_difference(&position, &real_pos), origin_side, c.grid_size());
+                        c.set_port(offset, side, source_arc.clone()).expect("Portgrid & component grid missmatch");
                     }
 
                     if let Ok(p) = port_grid.get_mut_port_inside(&position, origin_side) {
@@ -180,21 +279,37 @@ pub fn flood_fill(
                         }
                     }
                 },
-                CellState::Real(_, component) => {
-                    if let Component::WirePiece(piece) = component {
-                        if has_propagated[position[0]][position[1]] == false {
-                            has_propagated[position[0]][position[1]] = true;
-                            //dbg!(piece.connected_sides);
-                            let connected_sides = piece.connected_sides.iter().filter(|(_, connected)| {**connected}).map(|(side, _)| {side});
-                            call_on_sides.extend(connected_sides);
-                        }
-                    } else {
-                        if let Ok(p) = port_grid.get_mut_port_inside(&position, origin_side) {
-                            if let Some(port) = p.as_mut() {
-                                port.mark_checked(true);
-                                component.set_port([0,0], origin_side, source_arc.clone()).expect("Component grid and port grid missmatch");
+                CellState::Real(_, component, orientation) => {
+                    match component {
+                        Component::WirePiece(piece) => {
+                            if has_propagated[position[0]][position[1]] == [false; 2] {
+                                has_propagated[position[0]][position[1]] = [true; 2];
+                                if let Some(resolution) = piece.resolution {
+                                    source_arc.override_resolution(resolution);
+                                }
+                                //dbg!(piece.connected_sides);
+                                let connected_sides = piece.connected_sides.iter().filter(|(_, connected)| {**connected}).map(|(side, _)| {side});
+                                call_on_sides.extend(connected_sides);
                             }
-                        }
+                        },
+                        Component::WireCrossover(crossover) => {
+                            let axis = origin_side.axis();
+                            if has_propagated[position[0]][position[1]][axis] == false {
+                                has_propagated[position[0]][position[1]][axis] = true;
+                                // Only carry on along the axis we came in on, keeping the two nets seperate
+                                let connected_sides = [origin_side, origin_side.reverse()].into_iter().filter(|side| crossover.connected_sides[*side]);
+                                call_on_sides.extend(connected_sides);
+                            }
+                        },
+                        _ => {
+                            if let Ok(p) = port_grid.get_mut_port_inside(&position, origin_side) {
+                                if let Some(port) = p.as_mut() {
+                                    port.mark_checked(true);
+                                    let (offset, side) = orientation.invert([0,0], origin_side, component.grid_size());
+                                    component.set_port(offset, side, source_arc.clone()).expect("Component grid and port grid missmatch");
+                                }
+                            }
+                        },
                     }
                 },
             }
@@ -4,7 +4,7 @@ use bevy::{prelude::*, ecs::system::SystemState};
 use flate2::{write::ZlibEncoder, Compression, bufread::ZlibDecoder};
 use serde::{Deserialize, Serialize};
 use crate::{game::{GridSize, PlacementGridEntity}, MainTextureAtlas, components::placement::{Size, GridLink}, GameState};
-use super::{run::SimState, model::{SimulationData, ComponentGrid, CellState}, port_grid::PortGrid, levels::{load_level_listener, LoadLevelEvent}};
+use super::{run::SimState, model::{SimulationData, ComponentGrid, CellState}, port_grid::PortGrid, levels::{load_level_listener, LoadLevelEvent, LevelErrors}, packages::PackageLibrary, definitions::ComponentRegistry, scoring::{Score, SolutionScore}};
 pub struct SimLoadPlugin;
 
 impl Plugin for SimLoadPlugin {
@@ -12,6 +12,7 @@ impl Plugin for SimLoadPlugin {
         app.add_event::<SaveEvent>()
         .add_event::<LoadEvent>()
         .add_event::<LoadLevelEvent>()
+        .init_resource::<LevelErrors>()
         .add_system(save_listener.run_if(in_state(GameState::InGame)))
         .add_system(load_listener.run_if(in_state(GameState::InGame)))
         .add_system(load_level_listener.run_if(in_state(GameState::InGame)));
@@ -22,6 +23,9 @@ impl Plugin for SimLoadPlugin {
 pub struct SaveData {
     pub grid_size: GridSize,
     pub component_grid: ComponentGrid,
+    /// Score from the last passing submit, if the circuit had one when saved
+    #[serde(default)]
+    pub score: Option<Score>,
 }
 
 impl FromWorld for SaveData {
@@ -37,26 +41,31 @@ impl FromWorld for SaveData {
         SaveData {
             grid_size: world.get_resource::<GridSize>().unwrap().clone(),
             component_grid: component_grid,
+            score: world.get_resource::<SolutionScore>().and_then(|s| s.0),
         }
     }
 }
 
 impl SaveData {
-    pub fn create_world(self, commands: &mut Commands, atlas: &TextureAtlas, main_atlas: &MainTextureAtlas, placement_grid: &Query<(&Sprite, &Transform, &Size), With<PlacementGridEntity>>) -> (SimulationData, GridSize) {
+    pub fn create_world(mut self, commands: &mut Commands, atlas: &TextureAtlas, main_atlas: &MainTextureAtlas, registry: &ComponentRegistry, library: &PackageLibrary, placement_grid: &Query<(&Sprite, &Transform, &Size), With<PlacementGridEntity>>) -> (SimulationData, GridSize) {
         let grid = placement_grid.single();
         let size = grid.2;
         let grid_bottom_left = grid.1.translation.truncate() - (size.0.as_vec2() * 0.5);
 
         let grid_size = self.grid_size;
         let mut sim_data = SimulationData {
-            grid: ComponentGrid { grid: vec![vec![ CellState::Empty; grid_size.0[1]]; grid_size.0[0]] },
+            grid: ComponentGrid { grid: vec![vec![ CellState::Empty; grid_size.0[1]]; grid_size.0[0]], locked: Vec::new() },
             port_grid: PortGrid::new_with_size(grid_size.0[1],  grid_size.0[0]),
+            nets: Vec::new(),
         };
 
+        registry.attach(&mut self.component_grid);
+        library.attach(&mut self.component_grid);
+        sim_data.grid.locked = self.component_grid.locked.clone();
         for x in 0..self.component_grid.grid.len() {
             for y in 0..self.component_grid.grid[x].len() {
-                if let CellState::Real(_, component) = self.component_grid.grid[x][y].clone() {
-                    sim_data.load_component(commands, component, &grid_bottom_left, atlas, main_atlas, &[x,y])
+                if let CellState::Real(_, component, orientation) = self.component_grid.grid[x][y].clone() {
+                    sim_data.load_component(commands, component, &grid_bottom_left, atlas, main_atlas, &[x,y], orientation)
                 }
             }
         }
@@ -98,7 +107,10 @@ fn load_listener(
     placement_grid: Query<(&Sprite, &Transform, &Size), With<PlacementGridEntity>>,
     atlases: Res<Assets<TextureAtlas>>,
     main_atlas: Res<MainTextureAtlas>,
-    despawns: Query<Entity, With<GridLink>>
+    registry: Res<ComponentRegistry>,
+    library: Res<PackageLibrary>,
+    despawns: Query<Entity, With<GridLink>>,
+    mut score: ResMut<SolutionScore>,
 ) {
     for ev in listener.iter() {
         // Clear all pre-existing sprites.
@@ -112,8 +124,9 @@ fn load_listener(
         let reader = ZlibDecoder::new(BufReader::new(DecoderReader::new(file, &base64::prelude::BASE64_STANDARD_NO_PAD)));
 
         let level_data: SaveData = serde_json::from_reader(reader).expect("Could not parse level");
+        score.0 = level_data.score;
         // Recreate [SimulationData] etc.
-        let (new_sim_data, new_size) = level_data.create_world(&mut commands, atlases.get(&main_atlas.handle).unwrap(), main_atlas.as_ref(), &placement_grid);
+        let (new_sim_data, new_size) = level_data.create_world(&mut commands, atlases.get(&main_atlas.handle).unwrap(), main_atlas.as_ref(), &registry, &library, &placement_grid);
         *sim_data = new_sim_data;
         *size = new_size;
     }
@@ -0,0 +1,171 @@
+//! Scores for a passing solution, kept with the save and compared against every earlier solution in [HISTORY_FILE]
+use std::{collections::BTreeMap, fs::File, path::Path};
+
+use bevy::{prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+
+use crate::{GameState, campaign::LevelId, level_select::CurrentLevel};
+use super::{
+    levels::LevelData,
+    model::{CellState, Component, ComponentGrid, SimulationData},
+    palette::component_key,
+    test_cases::TestResults,
+};
+
+/// Every score reached on this machine, by level. Shared by copying the file around, no server needed.
+pub const HISTORY_FILE: &str = "data/score-history.json";
+
+pub struct ScoringPlugin;
+
+impl Plugin for ScoringPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SolutionScore>()
+            .init_resource::<ScoreHistory>()
+            .add_system(score_listener.run_if(in_state(GameState::InGame)));
+    }
+}
+
+/// Lower is better for all of them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Score {
+    /// Ticks taken to finish, added up over every test case
+    pub cycles: usize,
+    /// Placed components, not counting wires or any the level gives
+    pub components: usize,
+    /// Cells in the smallest box around everything placed
+    pub area: usize,
+    /// Placed wires and crossovers, not counting any the level gives
+    pub wires: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    Cycles,
+    Components,
+    Area,
+    Wires,
+}
+
+impl Metric {
+    pub const ALL: [Metric; 4] = [Metric::Cycles, Metric::Components, Metric::Area, Metric::Wires];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Metric::Cycles => "Cycles",
+            Metric::Components => "Components",
+            Metric::Area => "Area",
+            Metric::Wires => "Wires",
+        }
+    }
+
+    pub fn of(&self, score: &Score) -> usize {
+        match self {
+            Metric::Cycles => score.cycles,
+            Metric::Components => score.components,
+            Metric::Area => score.area,
+            Metric::Wires => score.wires,
+        }
+    }
+}
+
+impl Score {
+    /// `start_counts` are the components the level gives, from [LevelData::start_counts], which the player isn't scored on.\
+    /// Locked ones can't be moved so are left out of the area as well.
+    pub fn measure(grid: &ComponentGrid, cycles: usize, start_counts: &HashMap<String, usize>) -> Self {
+        let mut score = Score { cycles, ..Default::default() };
+        let mut counts: HashMap<String, (bool, usize)> = HashMap::new();
+        let mut min = [usize::MAX; 2];
+        let mut max = [0; 2];
+        for (x, column) in grid.grid.iter().enumerate() {
+            for (y, cell) in column.iter().enumerate() {
+                let (component, orientation) = match cell {
+                    CellState::Real(_, component, orientation) => (component, orientation),
+                    _ => continue,
+                };
+                let is_wire = match component {
+                    Component::Provider(_) | Component::Observer(_) => continue,
+                    Component::WirePiece(_) | Component::WireCrossover(_) => true,
+                    _ => false,
+                };
+                counts.entry(component_key(component.dummy())).or_insert((is_wire, 0)).1 += 1;
+                if grid.locked.contains(&[x, y]) {continue}
+                let size = component.oriented_grid_size(*orientation);
+                min = [min[0].min(x), min[1].min(y)];
+                max = [max[0].max(x + size[0]), max[1].max(y + size[1])];
+            }
+        }
+        for (key, (is_wire, count)) in counts {
+            let placed = count.saturating_sub(start_counts.get(&key).copied().unwrap_or(0));
+            match is_wire {
+                true => score.wires += placed,
+                false => score.components += placed,
+            }
+        }
+        if min[0] != usize::MAX {
+            score.area = (max[0] - min[0]) * (max[1] - min[1]);
+        }
+        score
+    }
+}
+
+/// Score of the circuit on the board, set when every test case passes or a scored save is loaded
+#[derive(Resource, Debug, Default)]
+pub struct SolutionScore(pub Option<Score>);
+
+#[derive(Resource, Debug, Default, Serialize, Deserialize)]
+/// Keyed by [LevelId] as it is displayed
+pub struct ScoreHistory(pub BTreeMap<String, Vec<Score>>);
+
+/// A missing file is just an empty history
+impl FromWorld for ScoreHistory {
+    fn from_world(_: &mut World) -> Self {
+        File::open(HISTORY_FILE).ok()
+            .and_then(|file| serde_json::from_reader(file).map_err(|e| error!("Could not read {}: {}", HISTORY_FILE, e)).ok())
+            .unwrap_or_default()
+    }
+}
+
+impl ScoreHistory {
+    pub fn level(&self, level: &LevelId) -> &[Score] {
+        self.0.get(&level.to_string()).map(|s| s.as_slice()).unwrap_or(&[])
+    }
+
+    /// Submitting the same solution again does not add it twice
+    pub fn record(&mut self, level: &LevelId, score: Score) {
+        let scores = self.0.entry(level.to_string()).or_default();
+        if scores.contains(&score) {return}
+        scores.push(score);
+        if let Err(e) = self.save(Path::new(HISTORY_FILE)) {
+            error!("Could not save score history: {}", e);
+        }
+    }
+
+    fn save(&self, path: &Path) -> std::io::Result<()> {
+        let file = File::create(path)?;
+        serde_json::to_writer_pretty(file, self).map_err(std::io::Error::from)
+    }
+}
+
+/// Scores the board whenever a submit passes, and forgets the score when one fails
+fn score_listener(
+    results: Res<TestResults>,
+    sim_data: Res<SimulationData>,
+    current_level: Res<CurrentLevel>,
+    level_data: Option<Res<LevelData>>,
+    mut score: ResMut<SolutionScore>,
+    mut history: ResMut<ScoreHistory>,
+) {
+    if !results.is_changed() {return}
+    if !results.all_passed() {
+        score.0 = None;
+        return;
+    }
+    let cycles = results.0.iter().map(|r| r.ticks).sum();
+    let no_counts = HashMap::new();
+    let start_counts = level_data.as_ref().map(|l| &l.start_counts).unwrap_or(&no_counts);
+    let new_score = Score::measure(&sim_data.grid, cycles, start_counts);
+    score.0 = Some(new_score);
+    if let Some(level) = &current_level.0 {
+        history.record(level, new_score);
+    }
+}
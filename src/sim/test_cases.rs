@@ -0,0 +1,243 @@
+use bevy::prelude::*;
+
+use crate::GameState;
+use super::{
+    definitions::ComponentRegistry,
+    levels::{LevelData, SimIOPadded, ResultType},
+    model::{CellState, ComponentGrid, GridComponent, SimulationData},
+    packages::PackageLibrary,
+    port_grid::PortGrid,
+    run::{self, NetConflict},
+};
+
+/// How long a case keeps running after its last input, for outputs still making their way through the circuit
+pub const EXTRA_TICKS: usize = 100;
+
+pub struct TestCasePlugin;
+
+impl Plugin for TestCasePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SubmitEvent>()
+            .init_resource::<TestResults>()
+            .add_system(submit_listener.run_if(in_state(GameState::InGame)));
+    }
+}
+
+/// Run every test case of the level against the circuit on the board
+pub struct SubmitEvent;
+
+#[derive(Debug, Clone)]
+pub struct CaseResult {
+    pub name: String,
+    pub hidden: bool,
+    pub passed: bool,
+    /// Correct outputs seen before the first mistake, out of `expected`
+    pub correct: usize,
+    pub expected: usize,
+    /// Two components wrote different values to a net set to error
+    pub conflict: bool,
+    /// How many ticks ran before the case finished or failed
+    pub ticks: usize,
+}
+
+/// Results of the last submit, cleared when a level is loaded
+#[derive(Resource, Debug, Default)]
+pub struct TestResults(pub Vec<CaseResult>);
+
+impl TestResults {
+    pub fn all_passed(&self) -> bool {
+        !self.0.is_empty() && self.0.iter().all(|r| r.passed)
+    }
+}
+
+fn submit_listener(
+    mut events: EventReader<SubmitEvent>,
+    sim_data: Res<SimulationData>,
+    level_data: Option<Res<LevelData>>,
+    registry: Res<ComponentRegistry>,
+    library: Res<PackageLibrary>,
+    mut results: ResMut<TestResults>,
+) {
+    if events.iter().count() == 0 {return}
+    let level_data = match level_data {
+        Some(l) => l,
+        None => return,
+    };
+    results.0 = (0..level_data.test_cases.len())
+        .map(|i| run_case(&sim_data.grid, &sim_data.port_grid, &level_data, i, &registry, &library))
+        .collect();
+}
+
+/// Run one test case on a copy of the circuit in a world of its own, so the board is left alone
+pub fn run_case(
+    grid: &ComponentGrid,
+    port_grid: &PortGrid,
+    level_data: &LevelData,
+    case_index: usize,
+    registry: &ComponentRegistry,
+    library: &PackageLibrary,
+) -> CaseResult {
+    let mut case_level = level_data.clone();
+    case_level.select_case(case_index);
+    let case = &level_data.test_cases[case_index];
+    let expected: usize = case.expected_outputs.values().map(|v| v.len()).sum();
+    let ticks = case.provided_inputs.values().map(|v| v.len()).max().unwrap_or(0) + EXTRA_TICKS;
+
+    let mut grid = fresh_copy(grid, registry, library);
+    let mut port_grid = port_grid.clone();
+    let nets = run::build_nets(&mut grid.grid, &mut port_grid, case_level.net_resolution);
+
+    let mut world = World::new();
+    let sim_io = SimIOPadded::from_level_data(&mut case_level);
+    world.insert_resource(case_level);
+    world.insert_resource(sim_io);
+    world.insert_resource(NetConflict::default());
+
+    let mut conflict = false;
+    let mut ticks_run = 0;
+    for tick in 0..ticks {
+        ticks_run = tick + 1;
+        for net in &nets {
+            net.start_tick();
+        }
+        for x in 0..grid.grid.len() {
+            for y in 0..grid.grid[x].len() {
+                if let CellState::Real(_, component, _) = &mut grid.grid[x][y] {
+                    component.tick([x, y], tick, &mut world);
+                }
+            }
+        }
+
+        conflict = nets.iter().filter_map(|net| net.take_conflict()).count() > 0
+            || world.resource::<NetConflict>().0.is_some();
+        let sim_io = world.resource::<SimIOPadded>();
+        let received: usize = sim_io.observed_outputs.values().map(|o| o.iter().filter(|v| v.is_some()).count()).sum();
+        if conflict || !sim_io.correct_so_far || received >= expected {break}
+    }
+
+    let sim_io = world.resource::<SimIOPadded>();
+    let correct: usize = sim_io.observed_outputs.values()
+        .map(|o| o.iter().take_while(|v| !matches!(v, Some((_, ResultType::Incorrect)))).filter(|v| v.is_some()).count())
+        .sum();
+    CaseResult {
+        name: case.name.clone(),
+        hidden: case.hidden,
+        passed: !conflict && sim_io.correct_so_far && correct == expected,
+        correct,
+        expected,
+        conflict,
+        ticks: ticks_run,
+    }
+}
+
+/// A copy of the circuit with none of its ports linked to the nets on the board
+fn fresh_copy(grid: &ComponentGrid, registry: &ComponentRegistry, library: &PackageLibrary) -> ComponentGrid {
+    let mut copy = grid.clone();
+    for cell in copy.grid.iter_mut().flatten() {
+        if let CellState::Real(_, component, _) = cell {
+            // Ports are never saved, so a round trip through serde leaves them unlinked
+            let value = serde_json::to_value(&*component).expect("Components can always be saved");
+            *component = serde_json::from_value(value).expect("Components can always be loaded");
+        }
+    }
+    registry.attach(&mut copy);
+    library.attach(&mut copy);
+    copy
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::sim::{
+        components::{GateNot, Led, Wire},
+        helpers::{Orientation, Side},
+        levels::{LevelDataLoad, LEVEL_DIR},
+        model::Component,
+        port_grid::Port,
+    };
+
+    /// Level 1, with its input at 0, 3 and output at 6, 3, and `components` added to the board
+    fn level_1_with(components: Vec<([usize; 2], Component)>, registry: &ComponentRegistry, library: &PackageLibrary) -> (ComponentGrid, PortGrid, LevelData) {
+        let load = LevelDataLoad::load(&Path::new(LEVEL_DIR).join("1.json"), registry, library).expect("Level 1 loads");
+        let mut grid = load.start.clone();
+        let level_data = LevelData::from_load(load);
+        for (position, component) in components {
+            grid.grid[position[0]][position[1]] = CellState::Real(Entity::from_raw(0), component, Orientation::default());
+        }
+
+        let width = grid.grid.len();
+        let height = grid.grid[0].len();
+        let mut port_grid = PortGrid::new_with_size(height, width);
+        for x in 0..width {
+            for y in 0..height {
+                if let CellState::Real(_, component, orientation) = &grid.grid[x][y] {
+                    port_grid.modify_bulk(Some(Port::default()), component.oriented_ports(*orientation).iter().collect(), &[x, y]);
+                }
+            }
+        }
+        (grid, port_grid, level_data)
+    }
+
+    fn straight_wire() -> Component {
+        let mut wire = Wire::default();
+        wire.connected_sides[Side::Left] = true;
+        wire.connected_sides[Side::Right] = true;
+        Component::WirePiece(wire)
+    }
+
+    /// Every case, hidden ones included, the same as a submit
+    fn run_all(grid: &ComponentGrid, port_grid: &PortGrid, level_data: &LevelData, registry: &ComponentRegistry, library: &PackageLibrary) -> Vec<CaseResult> {
+        (0..level_data.test_cases.len()).map(|i| run_case(grid, port_grid, level_data, i, registry, library)).collect()
+    }
+
+    #[test]
+    fn wire_passes_every_case() {
+        let registry = ComponentRegistry::load();
+        let library = PackageLibrary::load(&registry);
+        let wires = (1..6).map(|x| ([x, 3], straight_wire())).collect();
+        let (grid, port_grid, level_data) = level_1_with(wires, &registry, &library);
+
+        let results = run_all(&grid, &port_grid, &level_data, &registry, &library);
+        assert_eq!(results.len(), 2);
+        assert!(results[1].hidden, "Level 1's second case is hidden");
+        for result in &results {
+            assert!(result.passed, "{} failed", result.name);
+            assert!(!result.conflict);
+            assert_eq!(result.correct, result.expected);
+            assert_eq!(result.expected, 50);
+        }
+        assert!(TestResults(results).all_passed());
+    }
+
+    #[test]
+    fn not_gate_fails_every_case() {
+        let registry = ComponentRegistry::load();
+        let library = PackageLibrary::load(&registry);
+        let mut components: Vec<([usize; 2], Component)> = (2..6).map(|x| ([x, 3], straight_wire())).collect();
+        components.push(([1, 3], Component::GateNot(GateNot::default())));
+        let (grid, port_grid, level_data) = level_1_with(components, &registry, &library);
+
+        let results = run_all(&grid, &port_grid, &level_data, &registry, &library);
+        assert_eq!(results.len(), 2);
+        for result in &results {
+            assert!(!result.passed, "{} passed", result.name);
+            assert_eq!(result.expected, 50);
+            assert!(result.correct < result.expected);
+        }
+        assert!(!TestResults(results).all_passed());
+    }
+
+    /// Components which change their sprite have none to change in a test case's world
+    #[test]
+    fn run_case_with_led() {
+        let registry = ComponentRegistry::load();
+        let library = PackageLibrary::load(&registry);
+        let (grid, port_grid, level_data) = level_1_with(vec![([3, 0], Component::Led(Led::default()))], &registry, &library);
+
+        let result = run_case(&grid, &port_grid, &level_data, 0, &registry, &library);
+        assert!(result.ticks > 0);
+        assert!(!result.passed);
+    }
+}
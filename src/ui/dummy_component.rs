@@ -1,5 +1,9 @@
 use crate::components::placement::Size;
-use crate::sim::model::DummyComponent;
+use crate::sim::levels::LevelData;
+use crate::sim::model::{DummyComponent, SimulationData};
+use crate::sim::palette::Palette;
+use crate::sim::definitions::ComponentRegistry;
+use crate::sim::packages::PackageLibrary;
 use crate::ui::shared::*;
 use crate::GameState;
 use crate::MainTextureAtlas;
@@ -8,59 +12,206 @@ use bevy::{core::Name, prelude::*};
 use strum::IntoEnumIterator;
 pub struct ComponentTrayPlugin;
 
+/// Where the first column of the tray starts, further columns are added to the left
+const TRAY_LEFT: f32 = 225.0;
+const TRAY_BOTTOM: f32 = -175.0;
+const TRAY_TOP: f32 = 175.0;
+const TRAY_COLUMN_WIDTH: f32 = 66.0;
+
 impl Plugin for ComponentTrayPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system(enter_system.in_schedule(OnEnter(GameState::InGame)));
+        app.add_systems((refresh_tray, update_budget_text).distributive_run_if(in_state(GameState::InGame)));
     }
 }
 
-fn enter_system(
+/// One thing which can be dragged out of the tray
+struct TrayEntry {
+    component: DummyComponent,
+    sprite_name: String,
+    size: Vec2,
+    /// Name of the package or data driven component
+    definition: Option<String>,
+}
+
+/// Spawns the tray on entering the game and again whenever a package is added to the library or the level's palette changes
+fn refresh_tray(
     mut commands: Commands,
+    ass: Res<AssetServer>,
     atlases: Res<Assets<TextureAtlas>>,
     main_atlas: Res<MainTextureAtlas>,
+    library: Res<PackageLibrary>,
+    registry: Res<ComponentRegistry>,
+    level_data: Option<Res<LevelData>>,
+    existing: Query<Entity, With<TrayComponent>>,
+    mut last_palette: Local<Option<Palette>>,
 ) {
+    // Observers change the level data every tick, so compare the palette itself
+    let palette = level_data.map(|l| l.palette.clone());
+    if !library.is_changed() && !existing.is_empty() && *last_palette == palette {
+        return;
+    }
+    *last_palette = palette.clone();
+    for entity in existing.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
     let atlas = atlases.get(&main_atlas.handle).unwrap();
-    let mut current_down = -175.0;
-    for comp in DummyComponent::iter() {
-        let size = comp.get_size();
-        //let texture: Handle<Image> = ass.load(comp.get_path());
-        let sprite_idx = comp.get_sprite_index(atlas);
-        let mut sprite = TextureAtlasSprite::new(sprite_idx);
-        sprite.anchor = Anchor::BottomLeft;
-        commands.spawn((
-            SpriteSheetBundle {
-                sprite: sprite,
-                transform: Transform {
-                    translation: Vec3 {
-                        x: 225.0,
-                        y: current_down,
-                        z: 20.0,
+    let components = DummyComponent::iter()
+        // Each package and data driven component gets its own entry instead
+        .filter(|comp| *comp != DummyComponent::Package && *comp != DummyComponent::Scripted)
+        .map(|comp| TrayEntry {
+            component: comp,
+            sprite_name: comp.get_sprite_name().to_string(),
+            size: comp.get_size(),
+            definition: None,
+        });
+    let scripted = registry.0.values().map(|definition| TrayEntry {
+        component: DummyComponent::Scripted,
+        sprite_name: definition.sprite_name(),
+        size: Vec2::new(definition.size[0] as f32 * 32.0, definition.size[1] as f32 * 32.0),
+        definition: Some(definition.name.clone()),
+    });
+    let packages = library.0.values().map(|definition| TrayEntry {
+        component: DummyComponent::Package,
+        sprite_name: format!("package_{}x{}", definition.size[0], definition.size[1]),
+        size: Vec2::new(definition.size[0] as f32 * 32.0, definition.size[1] as f32 * 32.0),
+        definition: Some(definition.name.clone()),
+    });
+
+    // Packages start their own column so they are easy to find
+    let mut columns: Vec<Vec<TrayEntry>> = vec![Vec::new()];
+    let mut current_down = TRAY_BOTTOM;
+    let allowed = components.chain(scripted).chain(packages)
+        .filter(|entry| palette.as_ref().map(|p| p.allows(entry.component)).unwrap_or(true));
+    for entry in allowed {
+        let first_package = entry.component == DummyComponent::Package
+            && columns.last().unwrap().iter().all(|e| e.component != DummyComponent::Package);
+        // Start a new column to the left once the current one is full
+        if (current_down + entry.size.y > TRAY_TOP || first_package) && !columns.last().unwrap().is_empty() {
+            current_down = TRAY_BOTTOM;
+            columns.push(Vec::new());
+        }
+        current_down += entry.size.y + 2.0;
+        columns.last_mut().unwrap().push(entry);
+    }
+
+    let mut current_x = TRAY_LEFT;
+    for (i, column) in columns.into_iter().enumerate() {
+        if i != 0 {
+            let widest = column.iter().map(|e| e.size.x + 2.0).fold(TRAY_COLUMN_WIDTH, f32::max);
+            current_x -= widest;
+        }
+        let mut current_down = TRAY_BOTTOM;
+        for entry in column {
+            let sprite_idx = atlas.get_texture_index(&Handle::weak(entry.sprite_name.as_str().into()))
+                .unwrap_or_else(|| entry.component.get_sprite_index(atlas));
+            let mut sprite = TextureAtlasSprite::new(sprite_idx);
+            sprite.anchor = Anchor::BottomLeft;
+            let mut tray_entity = commands.spawn((
+                SpriteSheetBundle {
+                    sprite: sprite,
+                    transform: Transform {
+                        translation: Vec3 {
+                            x: current_x,
+                            y: current_down,
+                            z: 20.0,
+                        },
+                        ..Default::default()
                     },
-                    //scale: Vec3::splat(SCALE),
+                    texture_atlas: main_atlas.handle.clone(),
                     ..Default::default()
                 },
-                texture_atlas: main_atlas.handle.clone(),
-                ..Default::default()
-            },
-            Name::new(format!("Dummy Component - {}", comp.get_sprite_name())),
-            GridLock::new(),
-            crate::game::GameRoot,
-            Draggable::new(),
-            DragTypeReturn::new(),
-            DragOpacity(0.75),
-            Size(comp.get_size().as_uvec2()),
-            ComponentLink(comp),
-        ));
-        current_down += size.y + 2.0;
+                Name::new(format!("Dummy Component - {}", entry.sprite_name)),
+                GridLock::new(),
+                crate::game::GameRoot,
+                Draggable::new(),
+                DragTypeReturn::new(),
+                DragOpacity(0.75),
+                Size(entry.size.as_uvec2()),
+                ComponentLink(entry.component),
+                TrayComponent,
+            ));
+            // Limited components show how many more can be placed, kept up to date by [update_budget_text]
+            let limited = palette.as_ref().map(|p| p.remaining(entry.component, 0).is_some()).unwrap_or(false);
+            if limited {
+                tray_entity.with_children(|parent| {
+                    parent.spawn((
+                        Text2dBundle {
+                            text: Text::from_section(
+                                "",
+                                TextStyle {
+                                    font: ass.load("Pixelboy.ttf"),
+                                    font_size: 12.0,
+                                    color: Color::WHITE,
+                                },
+                            ),
+                            text_anchor: Anchor::BottomRight,
+                            transform: Transform::from_xyz(entry.size.x - 2.0, 2.0, 0.1),
+                            ..Default::default()
+                        },
+                        BudgetText(entry.component),
+                    ));
+                });
+            }
+            if let Some(name) = entry.definition {
+                tray_entity.with_children(|parent| {
+                    parent.spawn(Text2dBundle {
+                        text: Text::from_section(
+                            name.clone(),
+                            TextStyle {
+                                font: ass.load("Pixelboy.ttf"),
+                                font_size: 12.0,
+                                color: Color::WHITE,
+                            },
+                        ),
+                        text_anchor: Anchor::TopCenter,
+                        transform: Transform::from_xyz(entry.size.x / 2.0, entry.size.y - 4.0, 0.1),
+                        ..Default::default()
+                    });
+                });
+                tray_entity.insert(DefinitionLink(name));
+            }
+            current_down += entry.size.y + 2.0;
+        }
+    }
+}
+
+/// Shows how many more of a component the level's palette allows
+fn update_budget_text(
+    level_data: Option<Res<LevelData>>,
+    sim_data: Res<SimulationData>,
+    mut texts: Query<(&BudgetText, &mut Text)>,
+) {
+    let level_data = match level_data {
+        Some(l) => l,
+        None => return,
+    };
+    for (budget, mut text) in texts.iter_mut() {
+        let placed = level_data.placed(budget.0, &sim_data.grid);
+        let remaining = level_data.palette.remaining(budget.0, placed).unwrap_or(0);
+        let section = &mut text.sections[0];
+        let value = format!("x{}", remaining);
+        if section.value != value {
+            section.value = value;
+            section.style.color = if remaining == 0 {Color::RED} else {Color::WHITE};
+        }
     }
 }
 
 #[derive(Component)]
 pub struct TrayComponent;
 
+/// Text on a tray entry counting down as the component is placed
+#[derive(Component)]
+pub struct BudgetText(pub DummyComponent);
+
 #[derive(Component)]
 pub struct ComponentLink(pub DummyComponent);
 
+/// Which package in the [PackageLibrary] or component in the [ComponentRegistry] a tray entry places
+#[derive(Component)]
+pub struct DefinitionLink(pub String);
+
 #[derive(Component)]
 pub struct GridLock {
     pub grab_part: Vec2,
@@ -0,0 +1,408 @@
+use std::path::Path;
+
+use bevy::prelude::{in_state, App, Commands, EventWriter, IntoSystemConfig, Local, Plugin, Res, ResMut, State};
+use bevy_egui::EguiContexts;
+use egui::*;
+use strum::IntoEnumIterator;
+
+use crate::{
+    GameState,
+    campaign::{Campaigns, LevelId, MAIN_CAMPAIGN, MANIFEST_NAME},
+    level_select::CurrentLevel,
+    sim::{
+        definitions::ComponentRegistry,
+        generators::{InputGenerator, InputSource, OutputSource},
+        helpers::UI_DISABLED_MSG,
+        level_editor::{DraftCase, DraftInput, DraftOutput, EditorBoardEvent, LevelDraft, MAX_GRID_SIZE},
+        levels::{LevelData, SimIOPadded, LEVEL_DIR},
+        model::{CellState, Component, DummyComponent, SimulationData},
+        net::NetResolution,
+        packages::PackageLibrary,
+        palette::{component_key, Palette},
+        run::SimState,
+        test_cases::{run_case, TestResults},
+    },
+};
+
+pub struct LevelEditorMenuPlugin;
+
+impl Plugin for LevelEditorMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(level_editor_window.run_if(in_state(GameState::InGame)));
+    }
+}
+
+/// Everything about the level which isn't on the board, shown while no level is loaded
+fn level_editor_window(
+    mut commands: Commands,
+    mut egui_ctx: EguiContexts,
+    current_level: Res<CurrentLevel>,
+    campaigns: Res<Campaigns>,
+    mut draft: ResMut<LevelDraft>,
+    mut sim_data: ResMut<SimulationData>,
+    mut results: ResMut<TestResults>,
+    registry: Res<ComponentRegistry>,
+    library: Res<PackageLibrary>,
+    sim_state: Res<State<SimState>>,
+    mut board_writer: EventWriter<EditorBoardEvent>,
+    mut open_number: Local<u16>,
+    mut messages: Local<Vec<(String, Color32)>>,
+) {
+    if current_level.0.is_some() {return}
+    let sim_halted = sim_state.0 == SimState::Halted;
+
+    egui::Window::new("Level editor").default_pos(Pos2::new(300.0, 0.0)).vscroll(true).show(egui_ctx.ctx_mut(), |ui| {
+        ui.add_enabled_ui(sim_halted, |ui| {
+            CollapsingHeader::new("Level").default_open(true).show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.add(DragValue::new(&mut *open_number).clamp_range(1..=u16::MAX).prefix("Level "));
+                    if ui.button("Open").on_disabled_hover_text(UI_DISABLED_MSG).clicked() {
+                        board_writer.send(EditorBoardEvent::Open(*open_number));
+                    }
+                    if ui.button("New").on_disabled_hover_text(UI_DISABLED_MSG).clicked() {
+                        *draft = LevelDraft::default();
+                        board_writer.send(EditorBoardEvent::New);
+                    }
+                });
+                ui.separator();
+                Grid::new("level_editor_level").num_columns(2).show(ui, |ui| {
+                    ui.label("Number:");
+                    ui.add(DragValue::new(&mut draft.number).clamp_range(1..=u16::MAX));
+                    ui.end_row();
+                    ui.label("Name:");
+                    ui.text_edit_singleline(&mut draft.name);
+                    ui.end_row();
+                    ui.label("Description:");
+                    ui.text_edit_multiline(&mut draft.desc);
+                    ui.end_row();
+                    ui.label("Grid size:");
+                    ui.horizontal(|ui| {
+                        ui.add(DragValue::new(&mut draft.grid_size[0]).clamp_range(1..=MAX_GRID_SIZE[0]));
+                        ui.label("x");
+                        ui.add(DragValue::new(&mut draft.grid_size[1]).clamp_range(1..=MAX_GRID_SIZE[1]));
+                        let board_size = [sim_data.grid.grid.len(), sim_data.grid.grid.first().map(|c| c.len()).unwrap_or(0)];
+                        if ui.add_enabled(board_size != draft.grid_size, Button::new("Resize board"))
+                            .on_hover_text("Anything which no longer fits is removed")
+                            .clicked() {
+                            board_writer.send(EditorBoardEvent::Resize);
+                        }
+                    });
+                    ui.end_row();
+                    ui.label("Nets:");
+                    ComboBox::from_id_source("level_editor_nets")
+                        .selected_text(draft.net_resolution.as_str())
+                        .show_ui(ui, |ui| {
+                            for resolution in NetResolution::ALL {
+                                ui.selectable_value(&mut draft.net_resolution, resolution, resolution.as_str());
+                            }
+                        });
+                    ui.end_row();
+                });
+            });
+
+            CollapsingHeader::new("Components").show(ui, |ui| {
+                ui.label(RichText::new("Given components are in the level, the rest of the board is the reference solution.").weak());
+                components_editor(ui, &mut draft, &mut sim_data);
+            });
+
+            CollapsingHeader::new("Palette").show(ui, |ui| {
+                palette_editor(ui, &mut draft.palette);
+            });
+
+            CollapsingHeader::new("Test cases").show(ui, |ui| {
+                cases_editor(ui, &mut draft);
+                ui.horizontal(|ui| {
+                    if ui.button("Add case").clicked() {
+                        let name = format!("Case {}", draft.cases.len());
+                        draft.cases.push(DraftCase::new(&name));
+                        draft.add_missing_ids(&sim_data.grid);
+                    }
+                    if ui.button("Add missing ids").on_hover_text("Give every case each provider and observer id on the board").clicked() {
+                        draft.add_missing_ids(&sim_data.grid);
+                    }
+                });
+            });
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                let play = ui.button("Test play")
+                    .on_hover_text("Check the level and run every case against the circuit on the board");
+                if play.clicked() {
+                    let load = draft.to_load(&sim_data.grid);
+                    let errors: Vec<String> = load.validate().iter().map(|e| e.to_string())
+                        .chain(draft.check_board(&sim_data.grid).iter().map(|e| e.to_string()))
+                        .collect();
+                    messages.clear();
+                    if !errors.is_empty() {
+                        messages.extend(errors.into_iter().map(|e| (e, Color32::RED)));
+                        draft.verified = None;
+                    } else {
+                        let mut level_data = LevelData::from_load(load);
+                        results.0 = (0..level_data.test_cases.len())
+                            .map(|i| run_case(&sim_data.grid, &sim_data.port_grid, &level_data, i, &registry, &library))
+                            .collect();
+                        if results.all_passed() {
+                            draft.mark_verified(&sim_data.grid);
+                            messages.push(("Every case passes, the level can be exported".to_string(), Color32::GREEN));
+                        } else {
+                            draft.verified = None;
+                            messages.push(("The reference solution fails a case, see the test cases window".to_string(), Color32::RED));
+                        }
+                        commands.insert_resource(SimIOPadded::from_level_data(&mut level_data));
+                        commands.insert_resource(level_data);
+                    }
+                }
+
+                let verified = draft.is_verified(&sim_data.grid);
+                let export = ui.add_enabled(verified, Button::new("Export"))
+                    .on_disabled_hover_text("Test play the level first, it has to pass again after any change");
+                if export.clicked() {
+                    messages.clear();
+                    match draft.export(&sim_data.grid) {
+                        Ok(path) => {
+                            messages.push((format!("Saved to {}", path.display()), Color32::GREEN));
+                            // Level select only shows levels listed in the manifest
+                            let id = LevelId { campaign: MAIN_CAMPAIGN.to_string(), level: draft.number.to_string() };
+                            if campaigns.path(&id).is_none() {
+                                let manifest = Path::new(LEVEL_DIR).join(MANIFEST_NAME);
+                                messages.push((format!("Add level {} to {} to play it", id, manifest.display()), Color32::YELLOW));
+                            }
+                        },
+                        Err(errors) => messages.extend(errors.iter().map(|e| (e.to_string(), Color32::RED))),
+                    }
+                }
+            });
+            if draft.path().exists() {
+                ui.label(RichText::new(format!("Exporting replaces {}", draft.path().display())).color(Color32::YELLOW));
+            }
+        });
+
+        for (message, colour) in messages.iter() {
+            ui.label(RichText::new(message.as_str()).color(*colour));
+        }
+    });
+}
+
+/// Every component on the board, whether it is given to the player and locked, and the ids of providers and observers
+fn components_editor(ui: &mut Ui, draft: &mut LevelDraft, sim_data: &mut SimulationData) {
+    Grid::new("level_editor_components").striped(true).show(ui, |ui| {
+        ui.label("Cell");
+        ui.label("Component");
+        ui.label("Id");
+        ui.label("Given");
+        ui.label("Locked");
+        ui.end_row();
+        for (x, column) in sim_data.grid.grid.iter_mut().enumerate() {
+            for (y, cell) in column.iter_mut().enumerate() {
+                let component = match cell {
+                    CellState::Real(_, component, _) => component,
+                    _ => continue,
+                };
+                ui.label(format!("{}, {}", x, y));
+                ui.label(component.dummy().name());
+                match component {
+                    Component::Provider(provider) => {
+                        let mut id = provider.id().to_string();
+                        if ui.add(TextEdit::singleline(&mut id).desired_width(80.0)).changed() {
+                            provider.set_id(id);
+                        }
+                    },
+                    Component::Observer(observer) => {
+                        let mut id = observer.id().to_string();
+                        if ui.add(TextEdit::singleline(&mut id).desired_width(80.0)).changed() {
+                            observer.set_id(id);
+                        }
+                    },
+                    _ => {
+                        ui.label("");
+                    },
+                }
+
+                let always_given = matches!(component, Component::Provider(_) | Component::Observer(_));
+                let mut given = draft.is_start(component, [x, y]);
+                if ui.add_enabled(!always_given, Checkbox::new(&mut given, ""))
+                    .on_disabled_hover_text("Providers and observers are always given")
+                    .changed() {
+                    draft.set_start([x, y], given);
+                }
+                let mut locked = draft.locked.contains(&[x, y]);
+                if ui.add_enabled(given, Checkbox::new(&mut locked, "")).changed() {
+                    draft.set_locked([x, y], locked);
+                }
+                ui.end_row();
+            }
+        }
+    });
+}
+
+/// Which components the player can place and how many of each
+fn palette_editor(ui: &mut Ui, palette: &mut Palette) {
+    let mut restricted = palette.allowed.is_some();
+    if ui.checkbox(&mut restricted, "Only allow some components").changed() {
+        palette.allowed = restricted.then(|| DummyComponent::iter().map(component_key).collect());
+    }
+    Grid::new("level_editor_palette").striped(true).show(ui, |ui| {
+        ui.label("Component");
+        ui.label("Allowed");
+        ui.label("Limit");
+        ui.end_row();
+        for component in DummyComponent::iter() {
+            let key = component_key(component);
+            ui.label(component.name());
+            let mut allowed = palette.allows(component);
+            if ui.add_enabled(restricted, Checkbox::new(&mut allowed, "")).changed() {
+                if let Some(list) = &mut palette.allowed {
+                    list.retain(|k| *k != key);
+                    if allowed {
+                        list.push(key.clone());
+                    }
+                }
+            }
+            ui.horizontal(|ui| {
+                let mut limited = palette.limits.contains_key(&key);
+                if ui.checkbox(&mut limited, "").changed() {
+                    match limited {
+                        true => palette.limits.insert(key.clone(), 1),
+                        false => palette.limits.remove(&key),
+                    };
+                }
+                if let Some(limit) = palette.limits.get_mut(&key) {
+                    ui.add(DragValue::new(limit));
+                }
+            });
+            ui.end_row();
+        }
+    });
+}
+
+fn cases_editor(ui: &mut Ui, draft: &mut LevelDraft) {
+    let mut remove = None;
+    for (i, case) in draft.cases.iter_mut().enumerate() {
+        ui.push_id(("level_editor_case", i), |ui| {
+            ui.group(|ui| {
+                ui.horizontal(|ui| {
+                    // The first case is the one at the top of the file, it keeps its name and is always shown
+                    if i == 0 {
+                        ui.label(RichText::new(case.name.as_str()).strong());
+                    } else {
+                        ui.add(TextEdit::singleline(&mut case.name).desired_width(100.0));
+                        ui.checkbox(&mut case.hidden, "Hidden");
+                        if ui.small_button("Remove").clicked() {
+                            remove = Some(i);
+                        }
+                    }
+                });
+
+                ui.label("Inputs");
+                let mut remove_input = None;
+                for (j, input) in case.inputs.iter_mut().enumerate() {
+                    ui.push_id(("input", j), |ui| {
+                        ui.horizontal(|ui| {
+                            ui.add(TextEdit::singleline(&mut input.id).desired_width(60.0));
+                            input_editor(ui, input);
+                            if ui.small_button("x").clicked() {
+                                remove_input = Some(j);
+                            }
+                        });
+                    });
+                }
+                if let Some(j) = remove_input {
+                    case.inputs.remove(j);
+                }
+                if ui.small_button("Add input").clicked() {
+                    case.inputs.push(DraftInput::new("input", InputSource::Values(Vec::new())));
+                }
+
+                ui.label("Expected outputs");
+                let mut remove_output = None;
+                for (j, output) in case.outputs.iter_mut().enumerate() {
+                    ui.push_id(("output", j), |ui| {
+                        ui.horizontal(|ui| {
+                            ui.add(TextEdit::singleline(&mut output.id).desired_width(60.0));
+                            output_editor(ui, output);
+                            if ui.small_button("x").clicked() {
+                                remove_output = Some(j);
+                            }
+                        });
+                    });
+                }
+                if let Some(j) = remove_output {
+                    case.outputs.remove(j);
+                }
+                if ui.small_button("Add output").clicked() {
+                    case.outputs.push(DraftOutput::new("output", OutputSource::Values(Vec::new())));
+                }
+            });
+        });
+    }
+    if let Some(i) = remove {
+        draft.cases.remove(i);
+    }
+}
+
+/// Picks listed values or a generator, then edits whichever was picked
+fn input_editor(ui: &mut Ui, input: &mut DraftInput) {
+    let kind = match &input.source {
+        InputSource::Values(_) => "Values",
+        InputSource::Generated(InputGenerator::Random { .. }) => "Random",
+        InputSource::Generated(InputGenerator::Sequence { .. }) => "Sequence",
+        InputSource::Generated(InputGenerator::Repeat { .. }) => "Repeat",
+    };
+    ComboBox::from_id_source("kind").selected_text(kind).width(80.0).show_ui(ui, |ui| {
+        if ui.selectable_label(kind == "Values", "Values").clicked() {
+            input.source = InputSource::Values(Vec::new());
+        }
+        if ui.selectable_label(kind == "Random", "Random").clicked() {
+            input.source = InputSource::Generated(InputGenerator::Random { seed: 0, min: 1, max: 255, count: 50 });
+        }
+        if ui.selectable_label(kind == "Sequence", "Sequence").clicked() {
+            input.source = InputSource::Generated(InputGenerator::Sequence { start: 0, step: 1, count: 50 });
+        }
+        if ui.selectable_label(kind == "Repeat", "Repeat").clicked() {
+            input.source = InputSource::Generated(InputGenerator::Repeat { pattern: Vec::new(), times: 10 });
+        }
+    });
+
+    match &mut input.source {
+        InputSource::Values(_) => {
+            ui.add(TextEdit::singleline(&mut input.values).hint_text("1, 2, 3"));
+        },
+        InputSource::Generated(InputGenerator::Random { seed, min, max, count }) => {
+            ui.add(DragValue::new(seed).prefix("seed "));
+            ui.add(DragValue::new(min).prefix("min "));
+            ui.add(DragValue::new(max).prefix("max "));
+            ui.add(DragValue::new(count).prefix("count "));
+        },
+        InputSource::Generated(InputGenerator::Sequence { start, step, count }) => {
+            ui.add(DragValue::new(start).prefix("start "));
+            ui.add(DragValue::new(step).prefix("step "));
+            ui.add(DragValue::new(count).prefix("count "));
+        },
+        InputSource::Generated(InputGenerator::Repeat { times, .. }) => {
+            ui.add(TextEdit::singleline(&mut input.values).hint_text("pattern").desired_width(100.0));
+            ui.add(DragValue::new(times).prefix("times "));
+        },
+    }
+}
+
+/// Picks listed values or a reference program worked out from the inputs
+fn output_editor(ui: &mut Ui, output: &mut DraftOutput) {
+    let is_values = matches!(output.source, OutputSource::Values(_));
+    ComboBox::from_id_source("kind").selected_text(if is_values {"Values"} else {"Reference"}).width(80.0).show_ui(ui, |ui| {
+        if ui.selectable_label(is_values, "Values").clicked() {
+            output.source = OutputSource::Values(Vec::new());
+        }
+        if ui.selectable_label(!is_values, "Reference").clicked() {
+            output.source = OutputSource::Reference { reference: format!("{} = 0", output.id) };
+        }
+    });
+
+    match &mut output.source {
+        OutputSource::Values(_) => {
+            ui.add(TextEdit::singleline(&mut output.values).hint_text("1, 2, 3"));
+        },
+        OutputSource::Reference { reference } => {
+            ui.add(TextEdit::singleline(reference).code_editor());
+        },
+    }
+}
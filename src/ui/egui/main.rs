@@ -11,7 +11,8 @@ use bevy::{
 use bevy_egui::EguiContexts;
 use egui::{plot::Plot, *};
 
-use crate::{GameState, sim::{run::{SimState, RunType}, save_load::{SaveEvent, LoadEvent}, interactions::{SelectedComponent, UpdateComponentEvent}, levels::{LevelData, SimIOPadded, ResultType}, model::{SimulationData, CellState, GridComponent}}, level_select::CurrentLevel};
+use crate::{GameState, sim::{run::{SimState, RunType, NetConflict}, save_load::{SaveEvent, LoadEvent}, interactions::{SelectedComponent, UpdateComponentEvent, OrientComponentEvent}, levels::{LevelData, SimIOPadded, ResultType, LevelErrors}, model::{SimulationData, CellState, GridComponent}, helpers::UI_DISABLED_MSG}, level_select::CurrentLevel};
+use super::package::PackageMenuState;
 pub struct LeftPanelPlugin;
 
 impl Plugin for LeftPanelPlugin {
@@ -20,7 +21,9 @@ impl Plugin for LeftPanelPlugin {
             .init_resource::<SaveMenuState>()
             .init_resource::<SimIOPadded>()
             .add_system(main_panels.run_if(in_state(GameState::InGame)))
-            .add_system(window_popup.run_if(in_state(GameState::InGame)));
+            .add_system(window_popup.run_if(in_state(GameState::InGame)))
+            .add_system(net_conflict_popup.run_if(in_state(GameState::InGame)))
+            .add_system(level_errors_popup.run_if(in_state(GameState::InGame)));
     }
 }
 
@@ -35,10 +38,12 @@ fn main_panels(
     mut save_writer: EventWriter<SaveEvent>,
     mut load_writer: EventWriter<LoadEvent>,
     mut update_component_writer: EventWriter<UpdateComponentEvent>,
+    mut orient_component_writer: EventWriter<OrientComponentEvent>,
     mut selected_component: ResMut<SelectedComponent>,
     mut sim_data: ResMut<SimulationData>,
     level_data: Option<Res<LevelData>>,
     io_data: Option<Res<SimIOPadded>>,
+    mut package_menu_state: ResMut<PackageMenuState>,
 ) {
     let sim_halted = sim_state.0 == SimState::Halted;
     // At the moment `CurrentLevel` actually refers to the level to load
@@ -74,6 +79,13 @@ fn main_panels(
                 commands.insert_resource(NextState(Some(GameState::MainMenu2)))
             }
 
+            let package_button = ui.add_enabled(sim_halted, egui::Button::new(RichText::new("Package").size(20.0)))
+                .on_hover_text("Turn part of the grid into a reusable component")
+                .on_disabled_hover_text(UI_DISABLED_MSG);
+            if package_button.clicked() {
+                package_menu_state.open = !package_menu_state.open;
+            }
+
             ui.with_layout(Layout::right_to_left(Align::Min), |ui| {
                 let save_button = ui.add_enabled(sim_halted, egui::widgets::ImageButton::new(
                     img_save,
@@ -110,7 +122,7 @@ fn main_panels(
                     }).response;
 
                 if save_dropdown.clicked_by(PointerButton::Primary) {
-                    if let Ok(paths) = glob(&format!("data/levels/user/{}/*.save", cur_level.0.unwrap())) {
+                    if let Ok(paths) = glob(&format!("{}/*.save", cur_level.save_dir())) {
                         let p: Vec<PathBuf> = paths.filter_map(|p| p.ok()).collect();
                         ui_state.files = p;
                     }
@@ -151,22 +163,48 @@ fn main_panels(
                 // No component selected. should display problem description
                 None => {
                     if let Some(level_data) = level_data {
-                        ui.label(RichText::new(format!("Level: {}", cur_level.0.unwrap())).size(25.0).strong().italics());
+                        let title = match &cur_level.0 {
+                            Some(level) => format!("Level: {}", level),
+                            None => "Level editor".to_string(),
+                        };
+                        ui.label(RichText::new(title).size(25.0).strong().italics());
                         ui.separator();
                         ui.label(RichText::new(level_data.name.clone()).size(20.0).strong());
                         ui.separator();
                         ui.label(level_data.desc.clone());
+                        ui.separator();
+                        ui.label(RichText::new("Press R to rotate and F to mirror a component while dragging it from the tray.").weak());
                     }
                 },
                 // Should display a brief explanation of the component, a delete button & any options for it
                 Some(grid_pos) => {
-                    if let CellState::Real(_, component) = &mut sim_data.grid.grid[grid_pos[0]][grid_pos[1]] {
+                    let locked = sim_data.grid.is_locked(grid_pos);
+                    if let CellState::Real(_, component, orientation) = &mut sim_data.grid.grid[grid_pos[0]][grid_pos[1]] {
                         let dummy = component.dummy();
                         ui.label(RichText::new(dummy.name()).size(25.0).strong().monospace());
                         ui.separator();
                         ui.label(RichText::new(dummy.desc()).size(12.0).weak());
                         ui.separator();
-                        component.gui_options(ui, sim_halted, dummy, grid_pos, &mut update_component_writer);
+                        if locked {
+                            ui.label(RichText::new("Locked by the level, it can't be moved or changed.").color(Color32::from_rgb(255, 165, 0)));
+                            ui.separator();
+                        }
+                        if dummy.can_orient() && !locked {
+                            ui.add_enabled_ui(sim_halted, |ui| {
+                                ui.horizontal(|ui| {
+                                    if ui.button("Rotate").on_disabled_hover_text(UI_DISABLED_MSG).clicked() {
+                                        orient_component_writer.send(OrientComponentEvent { pos: *grid_pos, orientation: orientation.rotate_clockwise() });
+                                    }
+                                    if ui.button("Mirror").on_disabled_hover_text(UI_DISABLED_MSG).clicked() {
+                                        orient_component_writer.send(OrientComponentEvent { pos: *grid_pos, orientation: orientation.mirror() });
+                                    }
+                                });
+                            });
+                            ui.separator();
+                        }
+                        ui.add_enabled_ui(!locked, |ui| {
+                            component.gui_options(ui, sim_halted, dummy, grid_pos, &mut update_component_writer);
+                        });
                     }
                 }
             }
@@ -260,6 +298,44 @@ pub struct UiState {
     pub selected_file: Option<PathBuf>,
 }
 
+/// Explains why the simulation paused when a net set to error had two different values written to it,
+/// or wires on one net picked different resolutions
+fn net_conflict_popup(
+    mut egui_ctx: EguiContexts,
+    net_conflict: Res<NetConflict>,
+    sim_state: Res<State<SimState>>,
+) {
+    let position = match net_conflict.0 {
+        Some(p) if sim_state.0 != SimState::Halted => p,
+        _ => return,
+    };
+    egui::Window::new("Net conflict").default_pos(Pos2::new(400.0, 100.0)).collapsible(false).show(egui_ctx.ctx_mut(), |ui| {
+        ui.label(RichText::new(format!("The net at {}, {} has a conflict", position[0], position[1])).color(Color32::RED));
+        ui.label("Either two components wrote different values to a net set to error, or its wires pick different resolutions.");
+        ui.label("Stop the simulation to change the circuit, or give every wire on the net the same resolution.");
+    });
+}
+
+/// Lists everything wrong with a level file which could not be loaded
+fn level_errors_popup(
+    mut egui_ctx: EguiContexts,
+    mut level_errors: ResMut<LevelErrors>,
+) {
+    if level_errors.errors.is_empty() {return}
+    let mut dismiss = false;
+    egui::Window::new("Could not load level").default_pos(Pos2::new(400.0, 100.0)).collapsible(false).show(egui_ctx.ctx_mut(), |ui| {
+        ui.label(RichText::new(level_errors.path.display().to_string()).code());
+        for error in &level_errors.errors {
+            ui.label(RichText::new(error.to_string()).color(Color32::RED));
+        }
+        ui.separator();
+        dismiss = ui.button("Dismiss").clicked();
+    });
+    if dismiss {
+        level_errors.errors.clear();
+    }
+}
+
 fn window_popup(
     current_level: Res<CurrentLevel>,
     mut save_menu_state: ResMut<SaveMenuState>,
@@ -288,7 +364,7 @@ fn window_popup(
                         ..Default::default()
                     };
 
-                    let dir = PathBuf::from(format!("data/levels/user/{}", current_level.0.unwrap()));
+                    let dir = PathBuf::from(current_level.save_dir());
                     let mut location = dir.join(sanitize_filename::sanitize_with_options(&*file_name, options));
                     location.set_extension("save"); // Check valid path
 
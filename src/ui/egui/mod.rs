@@ -1,3 +1,7 @@
 pub mod colours;
+pub mod level_editor;
 pub mod main;
+pub mod package;
+pub mod scoring;
+pub mod test_cases;
 pub mod theming;
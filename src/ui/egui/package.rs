@@ -0,0 +1,192 @@
+use bevy::prelude::{
+    in_state, App, IntoSystemConfig, Plugin, Query, Res, ResMut, Resource, State, Transform, UVec2, Visibility, With, Without,
+};
+use bevy_egui::EguiContexts;
+use bevy_prototype_lyon::{prelude::{Path, ShapePath}, shapes::{self, RectangleOrigin}};
+use egui::*;
+
+use crate::{
+    GameState,
+    components::placement::Size,
+    game::{GridSize, PlacementGridEntity, PackageRegionIndicator},
+    sim::{
+        helpers::{calc_grid_pos, UI_DISABLED_MSG},
+        model::SimulationData,
+        packages::{PackageDefinition, PackageLibrary, PinCandidate, MAX_PACKAGE_SIZE},
+        run::SimState,
+    },
+};
+
+pub struct PackageMenuPlugin;
+
+impl Plugin for PackageMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PackageMenuState>()
+            .add_system(package_window.run_if(in_state(GameState::InGame)))
+            .add_system(show_package_region.run_if(in_state(GameState::InGame)));
+    }
+}
+
+/// The "Create Package" window, opened from the top bar
+#[derive(Resource, Debug)]
+pub struct PackageMenuState {
+    pub open: bool,
+    /// Bottom left of the region to package, in grid cells
+    min: [usize; 2],
+    size: [usize; 2],
+    name: String,
+    desc: String,
+    package_size: [usize; 2],
+    /// Every edge which could be a pin, whether it is used and its name
+    pins: Vec<(PinCandidate, bool, String)>,
+    /// Result of the last attempt to create a package, true if it was an error
+    message: Option<(bool, String)>,
+}
+
+impl Default for PackageMenuState {
+    fn default() -> Self {
+        Self {
+            open: false,
+            min: [0, 0],
+            size: [2, 2],
+            name: String::new(),
+            desc: String::new(),
+            package_size: [1, 1],
+            pins: Vec::new(),
+            message: None,
+        }
+    }
+}
+
+fn package_window(
+    mut egui_ctx: EguiContexts,
+    mut state: ResMut<PackageMenuState>,
+    mut library: ResMut<PackageLibrary>,
+    sim_data: Res<SimulationData>,
+    grid_size: Res<GridSize>,
+    sim_state: Res<State<SimState>>,
+) {
+    if !state.open {return}
+    let sim_halted = sim_state.0 == SimState::Halted;
+    let state = state.as_mut();
+
+    // Keep the region on the grid
+    for axis in 0..2 {
+        state.size[axis] = state.size[axis].clamp(1, grid_size.0[axis].max(1));
+        state.min[axis] = state.min[axis].min(grid_size.0[axis].saturating_sub(state.size[axis]));
+    }
+
+    // Pins are worked out again every frame as the circuit or region can change, names are kept for edges which are still there
+    let candidates = PackageDefinition::pin_candidates(&sim_data.grid, &sim_data.port_grid, state.min, state.size);
+    let old_pins = std::mem::take(&mut state.pins);
+    state.pins = candidates.into_iter().enumerate().map(|(i, candidate)| {
+        match old_pins.iter().find(|(c, _, _)| *c == candidate) {
+            Some((_, used, name)) => (candidate, *used, name.clone()),
+            None => (candidate, true, format!("Pin {}", i + 1)),
+        }
+    }).collect();
+
+    let mut open = state.open;
+    egui::Window::new("Create Package").default_pos(Pos2::new(10000.0, 100.0)).open(&mut open).show(egui_ctx.ctx_mut(), |ui| {
+        ui.add_enabled_ui(sim_halted, |ui| {
+            ui.label(RichText::new("Region of the grid to package").strong());
+            ui.horizontal(|ui| {
+                ui.label("X: ");
+                ui.add(DragValue::new(&mut state.min[0]).clamp_range(0..=grid_size.0[0].saturating_sub(1)));
+                ui.label("Y: ");
+                ui.add(DragValue::new(&mut state.min[1]).clamp_range(0..=grid_size.0[1].saturating_sub(1)));
+            });
+            ui.horizontal(|ui| {
+                ui.label("Width: ");
+                ui.add(DragValue::new(&mut state.size[0]).clamp_range(1..=grid_size.0[0].max(1)));
+                ui.label("Height: ");
+                ui.add(DragValue::new(&mut state.size[1]).clamp_range(1..=grid_size.0[1].max(1)));
+            });
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                ui.label("Name: ");
+                ui.text_edit_singleline(&mut state.name);
+            });
+            ui.horizontal(|ui| {
+                ui.label("Description: ");
+                ui.text_edit_singleline(&mut state.desc);
+            });
+            ui.horizontal(|ui| {
+                ui.label("Package size: ");
+                ui.add(DragValue::new(&mut state.package_size[0]).clamp_range(1..=MAX_PACKAGE_SIZE));
+                ui.label("x");
+                ui.add(DragValue::new(&mut state.package_size[1]).clamp_range(1..=MAX_PACKAGE_SIZE));
+            });
+            ui.separator();
+
+            ui.label(RichText::new("Pins").strong());
+            if state.pins.is_empty() {
+                ui.label(RichText::new("Nothing leads out of the region, run wires to its edge to make pins").weak());
+            }
+            for (candidate, used, name) in state.pins.iter_mut() {
+                ui.horizontal(|ui| {
+                    ui.checkbox(used, "");
+                    ui.add_enabled(*used, TextEdit::singleline(name).desired_width(100.0));
+                    ui.label(RichText::new(format!(
+                        "{}, {} {}",
+                        candidate.inner_pos[0] + state.min[0],
+                        candidate.inner_pos[1] + state.min[1],
+                        candidate.side.as_str(),
+                    )).weak());
+                });
+            }
+            ui.separator();
+
+            let create = ui.add_sized([ui.available_width(), 24.0], Button::new("Create"));
+            if create.on_disabled_hover_text(UI_DISABLED_MSG).clicked() {
+                let pins: Vec<(String, PinCandidate)> = state.pins.iter()
+                    .filter(|(_, used, _)| *used)
+                    .map(|(candidate, _, name)| (name.clone(), *candidate))
+                    .collect();
+                state.message = Some(match PackageDefinition::from_region(&sim_data.grid, state.min, state.size, &state.name, &state.desc, state.package_size, &pins) {
+                    Ok(definition) => {
+                        let name = definition.name.clone();
+                        match library.add(definition) {
+                            Ok(()) => (false, format!("Added {} to the tray", name)),
+                            Err(e) => (true, format!("Could not save package: {}", e)),
+                        }
+                    },
+                    Err(e) => (true, e.to_string()),
+                });
+            }
+        });
+
+        if let Some((is_error, message)) = &state.message {
+            let colour = if *is_error {Color32::RED} else {Color32::GREEN};
+            ui.label(RichText::new(message).color(colour));
+        }
+    });
+    state.open = open;
+}
+
+/// Outline the region which will be packaged while the window is open
+fn show_package_region(
+    state: Res<PackageMenuState>,
+    placement_grid: Query<(&Transform, &Size), With<PlacementGridEntity>>,
+    mut outline: Query<(&mut Transform, &mut Path, &mut Visibility), (With<PackageRegionIndicator>, Without<PlacementGridEntity>)>,
+) {
+    if !state.is_changed() {return}
+    let (mut transform, mut path, mut visibility) = match outline.get_single_mut() {
+        Ok(o) => o,
+        Err(_) => return,
+    };
+    if !state.open {
+        *visibility = Visibility::Hidden;
+        return;
+    }
+
+    let grid = placement_grid.single();
+    let grid_bottom_left = grid.0.translation.truncate() - (grid.1.0.as_vec2() * 0.5);
+    transform.translation = calc_grid_pos(&grid_bottom_left, &UVec2::new(state.min[0] as u32, state.min[1] as u32)).extend(transform.translation.z);
+    *path = ShapePath::build_as(&shapes::Rectangle {
+        extents: bevy::prelude::Vec2::new(state.size[0] as f32 * 32.0, state.size[1] as f32 * 32.0),
+        origin: RectangleOrigin::BottomLeft,
+    });
+    *visibility = Visibility::Visible;
+}
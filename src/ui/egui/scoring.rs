@@ -0,0 +1,85 @@
+use bevy::prelude::{in_state, App, IntoSystemConfig, Plugin, Res};
+use bevy_egui::EguiContexts;
+use egui::*;
+
+use crate::{
+    GameState,
+    level_select::CurrentLevel,
+    sim::scoring::{Metric, ScoreHistory, SolutionScore},
+};
+
+/// Bars in each histogram
+const BUCKETS: usize = 10;
+
+pub struct ScoreMenuPlugin;
+
+impl Plugin for ScoreMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(score_window.run_if(in_state(GameState::InGame)));
+    }
+}
+
+/// Shows the board's score next to how every earlier solution to the level did
+fn score_window(
+    mut egui_ctx: EguiContexts,
+    score: Res<SolutionScore>,
+    history: Res<ScoreHistory>,
+    current_level: Res<CurrentLevel>,
+) {
+    let score = match score.0 {
+        Some(s) => s,
+        None => return,
+    };
+    // The board's own score is recorded as soon as it passes, so leave it out to compare against only the others
+    let past: Vec<_> = current_level.0.as_ref().map(|l| history.level(l)).unwrap_or(&[])
+        .iter().filter(|s| **s != score).collect();
+
+    egui::Window::new("Score").default_pos(Pos2::new(10000.0, 700.0)).show(egui_ctx.ctx_mut(), |ui| {
+        for metric in Metric::ALL {
+            let value = metric.of(&score);
+            let values: Vec<usize> = past.iter().map(|s| metric.of(s)).collect();
+            ui.horizontal(|ui| {
+                ui.label(RichText::new(metric.as_str()).strong());
+                ui.label(RichText::new(value.to_string()).code());
+                if !values.is_empty() {
+                    let beaten = values.iter().filter(|v| **v > value).count();
+                    ui.label(format!("better than {}% of {}", beaten * 100 / values.len(), values.len()));
+                }
+            });
+            histogram(ui, &values, value);
+            ui.add_space(4.0);
+        }
+    });
+}
+
+/// Bars counting how many solutions fell in each range, the range holding `current` is highlighted
+fn histogram(ui: &mut Ui, values: &[usize], current: usize) {
+    let min = values.iter().copied().fold(current, usize::min);
+    let max = values.iter().copied().fold(current, usize::max);
+    // Rounded up so `max` still lands in the last bucket
+    let width = (max - min) / BUCKETS + 1;
+    let mut counts = [0; BUCKETS];
+    for value in values {
+        counts[(value - min) / width] += 1;
+    }
+    let current_bucket = (current - min) / width;
+    let tallest = counts.iter().copied().max().unwrap_or(0).max(1);
+
+    let (rect, _) = ui.allocate_exact_size(vec2(ui.available_width().max(160.0), 40.0), Sense::hover());
+    let painter = ui.painter();
+    painter.rect_stroke(rect, 0.0, Stroke::new(1.0, Color32::DARK_GRAY));
+    let bar_width = rect.width() / BUCKETS as f32;
+    for (i, count) in counts.iter().enumerate() {
+        let height = (rect.height() * *count as f32 / tallest as f32).max(if i == current_bucket {2.0} else {0.0});
+        let left = rect.left() + bar_width * i as f32;
+        let bar = Rect::from_min_max(pos2(left + 1.0, rect.bottom() - height), pos2(left + bar_width - 1.0, rect.bottom()));
+        let colour = if i == current_bucket {Color32::LIGHT_GREEN} else {Color32::GRAY};
+        painter.rect_filled(bar, 0.0, colour);
+    }
+    ui.horizontal(|ui| {
+        ui.small(min.to_string());
+        ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+            ui.small((min + width * BUCKETS - 1).to_string());
+        });
+    });
+}
@@ -0,0 +1,93 @@
+use bevy::prelude::{in_state, App, Commands, EventWriter, IntoSystemConfig, Plugin, Res, ResMut, State};
+use bevy_egui::EguiContexts;
+use egui::*;
+
+use crate::{
+    GameState,
+    sim::{
+        helpers::UI_DISABLED_MSG,
+        levels::{LevelData, SimIOPadded},
+        run::SimState,
+        test_cases::{SubmitEvent, TestResults},
+    },
+};
+
+pub struct TestCaseMenuPlugin;
+
+impl Plugin for TestCaseMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(test_case_window.run_if(in_state(GameState::InGame)));
+    }
+}
+
+/// Lists the level's test cases, lets the player pick which visible one runs on the board and submit against them all
+fn test_case_window(
+    mut commands: Commands,
+    mut egui_ctx: EguiContexts,
+    level_data: Option<ResMut<LevelData>>,
+    results: Res<TestResults>,
+    sim_state: Res<State<SimState>>,
+    mut submit_writer: EventWriter<SubmitEvent>,
+) {
+    let mut level_data = match level_data {
+        Some(l) => l,
+        None => return,
+    };
+    let sim_halted = sim_state.0 == SimState::Halted;
+    let mut select = None;
+
+    egui::Window::new("Test cases").default_pos(Pos2::new(10000.0, 400.0)).default_open(false).show(egui_ctx.ctx_mut(), |ui| {
+        Grid::new("test_case_grid").striped(true).show(ui, |ui| {
+            let mut hidden_count = 0;
+            for (i, case) in level_data.test_cases.iter().enumerate() {
+                if case.hidden {
+                    hidden_count += 1;
+                    ui.label(RichText::new(format!("Hidden test {}", hidden_count)).italics());
+                    ui.label("");
+                } else {
+                    ui.label(case.name.as_str());
+                    let on_board = level_data.current_case == i;
+                    let button = ui.add_enabled(sim_halted && !on_board, Button::new(if on_board {"On board"} else {"Run on board"}))
+                        .on_disabled_hover_text(if on_board {"This case is already on the board"} else {UI_DISABLED_MSG});
+                    if button.clicked() {
+                        select = Some(i);
+                    }
+                }
+
+                match results.0.get(i) {
+                    Some(result) if result.passed => {
+                        ui.label(RichText::new("PASS").color(Color32::GREEN));
+                    },
+                    Some(result) => {
+                        let reason = match result.conflict {
+                            true => "net conflict".to_string(),
+                            false => format!("{}/{}", result.correct, result.expected),
+                        };
+                        ui.label(RichText::new(format!("FAIL ({})", reason)).color(Color32::RED));
+                    },
+                    None => {
+                        ui.label("");
+                    },
+                }
+                ui.end_row();
+            }
+        });
+        ui.separator();
+
+        if !results.0.is_empty() {
+            let passed = results.0.iter().filter(|r| r.passed).count();
+            let colour = if results.all_passed() {Color32::GREEN} else {Color32::RED};
+            ui.label(RichText::new(format!("Passed {} of {}", passed, results.0.len())).color(colour));
+        }
+        let submit = ui.add_sized([ui.available_width(), 24.0], Button::new("Submit"))
+            .on_hover_text("Run every test case, including hidden ones, without touching the board");
+        if submit.clicked() {
+            submit_writer.send(SubmitEvent);
+        }
+    });
+
+    if let Some(i) = select {
+        level_data.select_case(i);
+        commands.insert_resource(SimIOPadded::from_level_data(&mut level_data));
+    }
+}
@@ -1,9 +1,9 @@
-use super::dummy_component::{ComponentLink, GridLock};
-use crate::components::placement::{PlaceComponentEvent, Size};
+use super::dummy_component::{ComponentLink, GridLock, DefinitionLink};
+use crate::components::placement::{PlaceComponentEvent, PlacementOrientation, Size};
 use crate::game::{
     PlacementGridEntity, GRID_CELL_AMOUNT_HEIGHT, GRID_CELL_AMOUNT_WIDTH, GRID_CELL_SIZE,
 };
-use crate::sim::run::SimState;
+use crate::sim::{helpers::Orientation, run::SimState};
 use bevy::prelude::*;
 use bevy_mod_picking::prelude::*;
 use bevy_pixel_camera::PixelProjection;
@@ -59,11 +59,13 @@ pub fn drag_v2(
             Option<&mut GridLock>,
             Option<&Size>,
             Option<&ComponentLink>,
+            Option<&DefinitionLink>,
         ),
         Without<PlacementGridEntity>,
     >,
     placement_grid: Query<(&Sprite, &Transform, With<PlacementGridEntity>)>,
-    sim_state: Res<State<SimState>>
+    sim_state: Res<State<SimState>>,
+    placement_orientation: Res<PlacementOrientation>,
 ) {
     let grid = placement_grid.get_single().unwrap();
     //let bottom_left_corner = grid.1.translation.truncate() + Vec2::new(-112.0,-112.0);
@@ -73,7 +75,7 @@ pub fn drag_v2(
 
     for start in drag_start_events.iter() {
         if sim_state.0 != SimState::Halted {continue}
-        let (_, mut sprite, mut draggable, transform, opacity, must_return, gridlock, size, _) =
+        let (_, mut sprite, mut draggable, transform, opacity, must_return, gridlock, size, _, _) =
             match draggable_entity.get_mut(start.target()) {
                 Ok(b) => b,
                 Err(_) => {
@@ -130,7 +132,7 @@ pub fn drag_v2(
 
         //dbg!(&boxes);
         //dbg!(&dragging.target());::new(DragType::Return(Vec2::ZERO))
-        let (_, _, draggable, mut box_transform, _, _, gridlock, _, _) =
+        let (_, mut sprite, draggable, mut box_transform, _, _, gridlock, size, places, _) =
             match draggable_entity.get_mut(dragging.target()) {
                 Ok(e) => e,
                 Err(_) => {
@@ -159,11 +161,20 @@ pub fn drag_v2(
         } else {
             box_transform.translation = (mouse_pos - draggable.offset).extend(z);
         }
+
+        // Preview which way the component will be placed
+        if let Some(component) = places {
+            let orientation = oriented_for(component, &placement_orientation);
+            box_transform.rotation = orientation.sprite_rotation();
+            let pixel_size = size.map(|s| s.0.as_vec2()).unwrap_or(component.0.get_size());
+            box_transform.translation += orientation.sprite_offset(pixel_size).extend(0.0);
+            sprite.flip_x = orientation.mirrored;
+        }
     }
 
     for end in drag_end_events.iter() {
         if sim_state.0 != SimState::Halted {continue}
-        let (_, mut sprite, _, mut transform, opacity, must_return, gridlock, _, places) =
+        let (_, mut sprite, _, mut transform, opacity, must_return, gridlock, _, places, package) =
             match draggable_entity.get_mut(end.target()) {
                 Ok(b) => b,
                 Err(_) => {
@@ -202,13 +213,25 @@ pub fn drag_v2(
                         place_event_creator.send(PlaceComponentEvent(
                             [grid_slot.x as usize, grid_slot.y as usize],
                             component.0.clone(),
+                            oriented_for(component, &placement_orientation),
+                            package.map(|p| p.0.clone()),
                         ));
                     }
                 }
             }
         }
         if let Some(pos) = must_return {
-            transform.translation = pos.0
+            transform.translation = pos.0;
+            transform.rotation = Quat::IDENTITY;
+            sprite.flip_x = false;
         }
     }
 }
+
+/// The orientation to place a component with, wires always go down unturned
+fn oriented_for(component: &ComponentLink, placement_orientation: &PlacementOrientation) -> Orientation {
+    match component.0.can_orient() {
+        true => placement_orientation.0,
+        false => Orientation::default(),
+    }
+}